@@ -1,13 +1,29 @@
 use std::io::{stdout, Write};
+use std::ops::Range;
 use std::time::Instant;
-use std::{error::Error, path::Path};
+use std::{
+    error::Error,
+    path::{Path, PathBuf},
+};
 
 use atty::Stream;
-use raytracer_lib::{gamma::Gamma, triple::Colour};
+use raytracer_lib::{camera::CamProgressCb, float::*, gamma::Gamma, triple::Colour};
 use simple_process_stats::ProcessStats;
 
 use crate::MainParms;
 
+/// Animation sequence details
+pub(super) struct Sequence {
+    /// Frames to render
+    pub frames: Range<u64>,
+    /// Frames per second
+    pub fps: FltPrim,
+    /// Fraction of each frame the shutter is open for
+    pub shutter: FltPrim,
+    /// Skip frames which have already been written?
+    pub skip_existing: bool,
+}
+
 pub(super) fn render_to_image(state: MainParms, output: &Path) -> Result<(), Box<dyn Error>> {
     // Output camera parameters
     state.dump_camera_parameters(true);
@@ -16,25 +32,7 @@ pub(super) fn render_to_image(state: MainParms, output: &Path) -> Result<(), Box
     let start = Instant::now();
 
     // Render the image
-    let image = state.cam.render(
-        &state.world,
-        &*state.ambience,
-        Some(|l, h| {
-            // Print progress
-            let tty = atty::is(Stream::Stdout);
-            let msg = format!("{} / {} ({}%)", l, h, (l * 100) / h);
-
-            let mut lock = stdout().lock();
-            lock.write_all(msg.as_bytes()).unwrap();
-            if tty {
-                lock.write_all("\r".as_bytes()).unwrap();
-            } else {
-                lock.write_all("\n".as_bytes()).unwrap();
-            }
-            lock.flush().unwrap();
-            drop(lock);
-        }),
-    );
+    let image = render(&state);
 
     println!("Render completed in {:?}", start.elapsed());
 
@@ -44,6 +42,130 @@ pub(super) fn render_to_image(state: MainParms, output: &Path) -> Result<(), Box
     println!("Written to {}", output.display());
 
     // Print CPU statistics
+    print_cpu_stats();
+
+    Ok(())
+}
+
+pub(super) fn render_to_frames(
+    mut state: MainParms,
+    output: &Path,
+    sequence: &Sequence,
+) -> Result<(), Box<dyn Error>> {
+    // Output camera parameters
+    state.dump_camera_parameters(true);
+
+    // Start time
+    let start = Instant::now();
+
+    // Take the frame setup callback so it can be given the parameters
+    let mut frame_setup = state.frame_setup.take();
+
+    for frame in sequence.frames.clone() {
+        let frame_output = frame_path(output, frame);
+
+        if sequence.skip_existing && frame_output.exists() {
+            println!("Frame {frame}: {} exists, skipping", frame_output.display());
+            continue;
+        }
+
+        // Open the shutter at the start of the frame
+        let open = frame as FltPrim / sequence.fps;
+        let close = open + sequence.shutter / sequence.fps;
+
+        state.cam.set_shutter(open, close);
+
+        // Set up the scene for the frame
+        if let Some(frame_setup) = &mut frame_setup {
+            frame_setup(&mut state, frame, open);
+        }
+
+        println!("Frame {frame}: time {open} to {close}");
+
+        // Render the frame
+        let frame_start = Instant::now();
+
+        let image = render(&state);
+
+        // Save the frame
+        save_image(image, &frame_output, &state.gamma)?;
+
+        println!(
+            "Frame {frame}: written to {} in {:?}",
+            frame_output.display(),
+            frame_start.elapsed()
+        );
+    }
+
+    println!("Sequence completed in {:?}", start.elapsed());
+
+    // Print CPU statistics
+    print_cpu_stats();
+
+    Ok(())
+}
+
+/// Returns the file name for a frame of a sequence, with the frame number appended to the
+/// output file stem (out.png becomes out_0001.png)
+fn frame_path(output: &Path, frame: u64) -> PathBuf {
+    let stem = output
+        .file_stem()
+        .map(|stem| stem.to_string_lossy())
+        .unwrap_or_default();
+
+    let name = match output.extension() {
+        Some(ext) => format!("{stem}_{frame:04}.{}", ext.to_string_lossy()),
+        None => format!("{stem}_{frame:04}"),
+    };
+
+    output.with_file_name(name)
+}
+
+fn render(state: &MainParms) -> Vec<Vec<Colour>> {
+    let progresscb: CamProgressCb = Some(|l, h| {
+        // Print progress
+        let tty = atty::is(Stream::Stdout);
+        let msg = format!("{} / {} ({}%)", l, h, (l * 100) / h);
+
+        let mut lock = stdout().lock();
+        lock.write_all(msg.as_bytes()).unwrap();
+        if tty {
+            lock.write_all("\r".as_bytes()).unwrap();
+        } else {
+            lock.write_all("\n".as_bytes()).unwrap();
+        }
+        lock.flush().unwrap();
+        drop(lock);
+    });
+
+    if !state.cam.is_progressive() {
+        return state.cam.render(&state.world, &*state.ambience, progresscb);
+    }
+
+    let (image, stats) = state
+        .cam
+        .render_progressive(&state.world, &*state.ambience, progresscb);
+
+    println!(
+        "Rendered {} passes, {:.1} samples per pixel ({} to {}), noise {:.2}%",
+        stats.passes,
+        stats.samples_per_pixel,
+        stats.min_samples,
+        stats.max_samples,
+        stats.noise * 100.0
+    );
+
+    if state.cam.noise_target().is_some() {
+        println!(
+            "Pixels meeting noise target: {:.1}%",
+            stats.converged * 100.0
+        );
+    }
+
+    image
+}
+
+fn print_cpu_stats() {
     match ProcessStats::get() {
         Ok(stats) => {
             println!(
@@ -53,11 +175,9 @@ pub(super) fn render_to_image(state: MainParms, output: &Path) -> Result<(), Box
         }
         Err(e) => println!("Failed to get process stats ({e})"),
     }
-
-    Ok(())
 }
 
-/// Saves an image vector to a file
+/// Saves an image vector to a file. OpenEXR files are saved without gamma correction
 pub fn save_image(
     image: Vec<Vec<Colour>>,
     output: &Path,
@@ -66,6 +186,22 @@ pub fn save_image(
     let h = image.len();
     let w = image[0].len();
 
+    // Save high dynamic range formats with linear floating point values
+    if output
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("exr"))
+    {
+        let imgbuf = image::Rgb32FImage::from_fn(w as u32, h as u32, |i, j| {
+            let colour = &image[j as usize][i as usize];
+
+            image::Rgb([0, 1, 2].map(|c| flt_prim(colour[c]) as f32))
+        });
+
+        imgbuf.save(output)?;
+
+        return Ok(());
+    }
+
     // Create output image buffer
     let mut imgbuf = image::ImageBuffer::new(w as u32, h as u32);
 
@@ -2,19 +2,24 @@
 
 //! Raytracer binary entry point
 
-use image::render_to_image;
-use raytracer_lib::float::*;
-use std::{error::Error, path::PathBuf};
+use image::{render_to_frames, render_to_image, Sequence};
+use raytracer_lib::{camera::Shading, float::*};
+use settings::{
+    parse_count, parse_defocus_angle, parse_fov, parse_fraction, parse_noise, parse_non_negative,
+    parse_positive, parse_time, parse_triple, Settings, DEFAULT_GAMMA,
+};
+use std::{error::Error, ops::Range, path::PathBuf};
 use window::render_to_window;
 
 use clap::Parser;
 
 mod image;
 mod parms;
+mod settings;
 mod window;
 
 pub use image::save_image;
-pub use parms::MainParms;
+pub use parms::{FrameSetupCb, MainParms, ReloadCb};
 
 #[derive(Parser, Default)]
 #[clap(author, version, about)]
@@ -24,16 +29,151 @@ struct Args {
     output: Option<PathBuf>,
 
     /// Image width
-    #[clap(short = 'x', long = "width")]
+    #[clap(short = 'x', long = "width", value_parser = clap::value_parser!(u16).range(1..))]
     width: Option<u16>,
 
     /// Image height
-    #[clap(short = 'y', long = "height")]
+    #[clap(short = 'y', long = "height", value_parser = clap::value_parser!(u16).range(1..))]
     height: Option<u16>,
 
-    /// No gamma correction
-    #[clap(short = 'g', long = "gamma", default_value_t = 2.2)]
-    gamma: FltPrim,
+    /// Gamma correction factor, 0 for none [default: 2.2]
+    #[clap(short = 'g', long = "gamma", value_parser = parse_non_negative)]
+    gamma: Option<FltPrim>,
+
+    /// Samples per pixel
+    #[clap(short = 's', long = "samples", value_parser = parse_count)]
+    samples: Option<u64>,
+
+    /// Maximum ray bounce depth
+    #[clap(short = 'd', long = "depth", value_parser = parse_count)]
+    depth: Option<u64>,
+
+    /// Vertical field of view in degrees
+    #[clap(long = "fov", value_parser = parse_fov)]
+    fov: Option<FltPrim>,
+
+    /// Point the camera is looking from (x,y,z)
+    #[clap(long = "look-from", value_parser = parse_triple, allow_hyphen_values = true)]
+    look_from: Option<[FltPrim; 3]>,
+
+    /// Point the camera is looking at (x,y,z)
+    #[clap(long = "look-at", value_parser = parse_triple, allow_hyphen_values = true)]
+    look_at: Option<[FltPrim; 3]>,
+
+    /// Camera-relative "up" direction (x,y,z)
+    #[clap(long = "up", value_parser = parse_triple, allow_hyphen_values = true)]
+    up: Option<[FltPrim; 3]>,
+
+    /// Defocus angle in degrees
+    #[clap(long = "defocus-angle", value_parser = parse_defocus_angle)]
+    defocus_angle: Option<FltPrim>,
+
+    /// Focus distance
+    #[clap(long = "focus-dist", value_parser = parse_positive)]
+    focus_dist: Option<FltPrim>,
+
+    /// Render time span
+    #[clap(long = "time-span", value_parser = parse_non_negative)]
+    time_span: Option<FltPrim>,
+
+    /// Number of render threads [default: one per CPU]
+    #[clap(short = 't', long = "threads", value_parser = parse_count)]
+    threads: Option<u64>,
+
+    /// Render progressively until a time limit (seconds, or minutes or hours with an 'm' or 'h'
+    /// suffix)
+    #[clap(long = "time-limit", value_parser = parse_time)]
+    time_limit: Option<f64>,
+
+    /// Render progressively until the noise falls below a level (fraction, or percentage with a
+    /// '%' suffix). Pixels stop at the samples per pixel if they don't reach it
+    #[clap(long = "noise-target", value_parser = parse_noise)]
+    noise_target: Option<FltPrim>,
+
+    /// Settings file to load (as written by --dump-settings). Command line settings take
+    /// precedence
+    #[clap(short = 'c', long = "config")]
+    config: Option<PathBuf>,
+
+    /// Shading for the low resolution previews shown while moving the camera in the window
+    /// (full, normals or albedo)
+    #[clap(long = "preview-shading", value_parser = parse_shading, default_value = "full")]
+    preview_shading: Shading,
+
+    /// Print the effective settings in the settings file format and exit
+    #[clap(long = "dump-settings")]
+    dump_settings: bool,
+
+    /// Render a range of frames as an animation sequence (e.g. 0..240)
+    #[clap(long = "frames", value_parser = parse_frames)]
+    frames: Option<Range<u64>>,
+
+    /// Frames per second of an animation sequence
+    #[clap(long = "fps", value_parser = parse_positive, default_value_t = 24.0)]
+    fps: FltPrim,
+
+    /// Fraction of each animation frame the shutter is open for
+    #[clap(long = "shutter", value_parser = parse_fraction, default_value_t = 0.5)]
+    shutter: FltPrim,
+
+    /// Skip animation frames which have already been written
+    #[clap(long = "skip-existing")]
+    skip_existing: bool,
+}
+
+impl Args {
+    /// Returns the render settings set on the command line
+    fn settings(&self) -> Settings {
+        Settings {
+            width: self.width.map(u64::from),
+            height: self.height.map(u64::from),
+            samples: self.samples,
+            depth: self.depth,
+            fov: self.fov,
+            look_from: self.look_from,
+            look_at: self.look_at,
+            up: self.up,
+            defocus_angle: self.defocus_angle,
+            focus_dist: self.focus_dist,
+            time_span: self.time_span,
+            gamma: self.gamma,
+            threads: self.threads.map(|threads| threads as usize),
+            time_limit: self.time_limit,
+            noise_target: self.noise_target,
+        }
+    }
+}
+
+/// Parses a shading mode
+fn parse_shading(arg: &str) -> Result<Shading, String> {
+    match arg {
+        "full" => Ok(Shading::Full),
+        "normals" => Ok(Shading::Normals),
+        "albedo" => Ok(Shading::Albedo),
+        _ => Err(format!(
+            "Unknown shading mode '{arg}' (expected full, normals or albedo)"
+        )),
+    }
+}
+
+/// Parses a frame range (start..end, end exclusive)
+fn parse_frames(arg: &str) -> Result<Range<u64>, String> {
+    let (start, end) = arg
+        .split_once("..")
+        .ok_or_else(|| format!("Frame range '{arg}' is not in the form start..end"))?;
+
+    let start = start
+        .parse::<u64>()
+        .map_err(|e| format!("Invalid start frame '{start}' ({e})"))?;
+    let end = end
+        .parse::<u64>()
+        .map_err(|e| format!("Invalid end frame '{end}' ({e})"))?;
+
+    if start >= end {
+        return Err(format!("Frame range '{arg}' is empty"));
+    }
+
+    Ok(start..end)
 }
 
 /// Main binary entry point
@@ -41,16 +181,49 @@ pub fn bin_main(mut parms: MainParms) -> Result<(), Box<dyn Error>> {
     // Parse command line arguments
     let args = Args::parse();
 
-    // Set image dimensions if overridden
-    match (args.width, args.height) {
-        (Some(w), None) => parms.cam.set_width(w as u64),
-        (None, Some(h)) => parms.cam.set_height(h as u64),
-        (Some(w), Some(h)) => parms.cam.set_dimensions(w as u64, h as u64),
-        _ => (),
+    // Load the settings file, then override with the command line
+    let mut settings = match &args.config {
+        Some(config) => Settings::load(config)?,
+        None => Settings::default(),
+    };
+
+    settings.merge(args.settings());
+
+    // Apply the settings
+    settings.apply(&mut parms)?;
+
+    // Set the number of render threads
+    if let Some(threads) = settings.threads {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build_global()?;
+    }
+
+    // Dump settings?
+    if args.dump_settings {
+        Settings::new_effective(
+            &parms,
+            settings.gamma.unwrap_or(DEFAULT_GAMMA),
+            rayon::current_num_threads(),
+        )
+        .dump();
+
+        return Ok(());
     }
 
-    // Set gamma correction
-    parms.set_gamma(args.gamma);
+    // Animation sequence?
+    if let Some(frames) = args.frames {
+        let output = args.output.unwrap_or_else(|| PathBuf::from("out.png"));
+
+        let sequence = Sequence {
+            frames,
+            fps: args.fps,
+            shutter: args.shutter,
+            skip_existing: args.skip_existing,
+        };
+
+        return render_to_frames(parms, &output, &sequence);
+    }
 
     // Output to image?
     match args.output {
@@ -60,7 +233,7 @@ pub fn bin_main(mut parms: MainParms) -> Result<(), Box<dyn Error>> {
         }
         None => {
             // Output to window
-            render_to_window(parms)?;
+            render_to_window(parms, &settings, args.preview_shading)?;
         }
     }
 
@@ -1,3 +1,5 @@
+use std::{error::Error, path::PathBuf};
+
 use raytracer_lib::{
     ambient::{ambience::Ambience, ambient_light::AmbientLight},
     camera::Camera,
@@ -7,6 +9,12 @@ use raytracer_lib::{
     triple::Colour,
 };
 
+/// Animation frame setup callback, called with the frame number and the time the shutter opens
+pub type FrameSetupCb<'a> = Box<dyn FnMut(&mut MainParms<'a>, u64, FltPrim) + 'a>;
+
+/// Scene reload callback, called when a watched file changes to rebuild the scene
+pub type ReloadCb<'a> = Box<dyn FnMut(&mut MainParms<'a>) -> Result<(), Box<dyn Error>> + 'a>;
+
 /// Main entry point parameters
 pub struct MainParms<'a> {
     /// The camera to use
@@ -19,43 +27,71 @@ pub struct MainParms<'a> {
     pub gamma: Gamma,
     /// The bounding box of the main scene feature
     pub main_bbox: Option<Aabb>,
+    /// Scene setup for each frame of an animation sequence
+    pub frame_setup: Option<FrameSetupCb<'a>>,
+    /// Scene rebuild for the interactive window when a watched file changes
+    pub reload: Option<ReloadCb<'a>>,
+    /// Files the scene is built from (scene description, textures, meshes)
+    pub watch: Vec<PathBuf>,
 }
 
 impl<'a> MainParms<'a> {
     /// Prints the camera parameters
     pub fn dump_camera_parameters(&self, show_samples: bool) {
-        println!("Camera parameters:");
-
-        let cam = &self.cam;
-
-        // Get camera details
-        let (w, h) = cam.dimensions();
-        let (look_from, look_at, vup) = cam.view();
-        let vfov = cam.vfov();
-        let (defocus_angle, focus_dist) = cam.focus();
-        let time_span = cam.time_span();
-        let samples_per_pixel = cam.samples_per_pixel();
-        let max_depth = cam.max_depth();
-
-        // Calculate vector from the camera to the point we're looking at
-        let view_vec = look_from.vec_to(&look_at);
-
-        // Print the details
-        println!("  Image dimensions         : {w} x {h}");
-        println!("  Look from                : {look_from}");
-        println!("  Look to                  : {look_at}");
-        println!(
-            "  -> Look vector           : {view_vec}, distance {}",
-            view_vec.length()
-        );
-        println!("  Up                       : {vup}");
-        println!("  Vertical field of vision : {vfov}°");
-        println!("  Defocus angle            : {defocus_angle}°");
-        println!("  Focus distance           : {focus_dist}");
-        println!("  Time span                : {time_span}");
-        println!("  Maxiumum depth           : {max_depth}");
-
-        if show_samples {
+        dump_camera_parameters(&self.cam, show_samples)
+    }
+}
+
+/// Prints camera parameters
+pub(crate) fn dump_camera_parameters(cam: &Camera, show_samples: bool) {
+    println!("Camera parameters:");
+
+    // Get camera details
+    let (w, h) = cam.dimensions();
+    let (look_from, look_at, vup) = cam.view();
+    let vfov = cam.vfov();
+    let (defocus_angle, focus_dist) = cam.focus();
+    let (shutter_open, shutter_close) = cam.shutter();
+    let samples_per_pixel = cam.samples_per_pixel();
+    let max_depth = cam.max_depth();
+    let spectral = cam.spectral();
+
+    // Calculate vector from the camera to the point we're looking at
+    let view_vec = look_from.vec_to(&look_at);
+
+    // Print the details
+    println!("  Image dimensions         : {w} x {h}");
+    println!("  Look from                : {look_from}");
+    println!("  Look to                  : {look_at}");
+    println!(
+        "  -> Look vector           : {view_vec}, distance {}",
+        view_vec.length()
+    );
+    println!("  Up                       : {vup}");
+    println!("  Vertical field of vision : {vfov}°");
+    println!("  Defocus angle            : {defocus_angle}°");
+    println!("  Focus distance           : {focus_dist}");
+    println!("  Shutter                  : {shutter_open} to {shutter_close}");
+
+    if cam.animation().is_some() {
+        println!("  Animated view            : yes");
+    }
+    println!("  Maxiumum depth           : {max_depth}");
+
+    if spectral > 0 {
+        println!("  Spectral wavelengths     : {spectral}");
+    }
+
+    if show_samples {
+        if let Some(time_limit) = cam.time_limit() {
+            println!("  Time limit               : {time_limit:?}");
+        }
+
+        if let Some(noise_target) = cam.noise_target() {
+            println!("  Noise target             : {}%", noise_target * 100.0);
+        }
+
+        if !cam.is_progressive() {
             println!("  Samples per pixel        : {samples_per_pixel}");
         }
     }
@@ -70,6 +106,9 @@ impl<'a> MainParms<'a> {
             world,
             ambience: Box::new(AmbientLight::new(Colour::default())),
             main_bbox: None,
+            frame_setup: None,
+            reload: None,
+            watch: Vec::new(),
         }
     }
 
@@ -85,6 +124,9 @@ impl<'a> MainParms<'a> {
             world,
             ambience: Box::new(ambience),
             main_bbox: None,
+            frame_setup: None,
+            reload: None,
+            watch: Vec::new(),
         }
     }
 
@@ -97,4 +139,27 @@ impl<'a> MainParms<'a> {
     pub fn set_main_bbox(&mut self, bbox: Aabb) {
         self.main_bbox = Some(bbox)
     }
+
+    /// Sets the scene setup for each frame of an animation sequence. Frames which are skipped
+    /// are not set up
+    pub fn set_frame_setup(
+        &mut self,
+        frame_setup: impl FnMut(&mut MainParms<'a>, u64, FltPrim) + 'a,
+    ) {
+        self.frame_setup = Some(Box::new(frame_setup))
+    }
+
+    /// Sets the scene rebuild for the interactive window. The window watches the given files
+    /// and calls the rebuild when any of them change, after which the accumulated image is
+    /// restarted. The rebuild replaces the world (and optionally the camera, ambient light,
+    /// main bounding box and watched files), and should leave the parameters unchanged if it
+    /// fails
+    pub fn set_reload(
+        &mut self,
+        watch: Vec<PathBuf>,
+        reload: impl FnMut(&mut MainParms<'a>) -> Result<(), Box<dyn Error>> + 'a,
+    ) {
+        self.watch = watch;
+        self.reload = Some(Box::new(reload))
+    }
 }
@@ -0,0 +1,624 @@
+//! Render settings from a settings file and the command line
+
+use std::{
+    error::Error,
+    fs::read_to_string,
+    io::{self, stdout, Write},
+    path::Path,
+    time::Duration,
+};
+
+use raytracer_lib::{
+    camera::Camera,
+    float::*,
+    triple::{Point3, Vec3},
+};
+
+use crate::MainParms;
+
+/// Default gamma correction factor
+pub(crate) const DEFAULT_GAMMA: FltPrim = 2.2;
+
+/// Settings which make up a camera view (see `Settings::new_view`)
+const VIEW_KEYS: [&str; 6] = [
+    "fov",
+    "look_from",
+    "look_at",
+    "up",
+    "defocus_angle",
+    "focus_dist",
+];
+
+/// Render settings. Settings which are not set keep the scene's own values
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct Settings {
+    /// Image width
+    pub width: Option<u64>,
+    /// Image height
+    pub height: Option<u64>,
+    /// Samples per pixel
+    pub samples: Option<u64>,
+    /// Maximum ray bounce depth
+    pub depth: Option<u64>,
+    /// Vertical field of view in degrees
+    pub fov: Option<FltPrim>,
+    /// Point the camera is looking from
+    pub look_from: Option<[FltPrim; 3]>,
+    /// Point the camera is looking at
+    pub look_at: Option<[FltPrim; 3]>,
+    /// Camera-relative "up" direction
+    pub up: Option<[FltPrim; 3]>,
+    /// Defocus angle in degrees
+    pub defocus_angle: Option<FltPrim>,
+    /// Focus distance
+    pub focus_dist: Option<FltPrim>,
+    /// Render time span
+    pub time_span: Option<FltPrim>,
+    /// Gamma correction factor (0 for none)
+    pub gamma: Option<FltPrim>,
+    /// Number of render threads
+    pub threads: Option<usize>,
+    /// Progressive render time limit in seconds
+    pub time_limit: Option<f64>,
+    /// Progressive render target relative noise level
+    pub noise_target: Option<FltPrim>,
+}
+
+impl Settings {
+    /// Loads settings from a file of "key = value" lines. Blank lines and lines starting with
+    /// '#' are ignored
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let mut settings = Self::default();
+
+        for (line_no, line) in read_to_string(path)?.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            settings
+                .set(line)
+                .map_err(|e| format!("{}, line {}: {e}", path.display(), line_no + 1))?;
+        }
+
+        Ok(settings)
+    }
+
+    /// Sets a setting from a "key = value" line
+    pub fn set(&mut self, line: &str) -> Result<(), String> {
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| format!("'{line}' is not in the form key = value"))?;
+
+        let value = value.trim();
+
+        match key.trim() {
+            "width" => self.width = Some(parse_count(value)?),
+            "height" => self.height = Some(parse_count(value)?),
+            "samples" => self.samples = Some(parse_count(value)?),
+            "depth" => self.depth = Some(parse_count(value)?),
+            "fov" => self.fov = Some(parse_fov(value)?),
+            "look_from" => self.look_from = Some(parse_triple(value)?),
+            "look_at" => self.look_at = Some(parse_triple(value)?),
+            "up" => self.up = Some(parse_triple(value)?),
+            "defocus_angle" => self.defocus_angle = Some(parse_defocus_angle(value)?),
+            "focus_dist" => self.focus_dist = Some(parse_positive(value)?),
+            "time_span" => self.time_span = Some(parse_non_negative(value)?),
+            "gamma" => self.gamma = Some(parse_non_negative(value)?),
+            "threads" => self.threads = Some(parse_count(value)? as usize),
+            "time_limit" => self.time_limit = Some(parse_time(value)?),
+            "noise_target" => self.noise_target = Some(parse_noise(value)?),
+            key => return Err(format!("Unknown setting '{key}'")),
+        }
+
+        Ok(())
+    }
+
+    /// Sets a camera view setting from a "key = value" line. Other settings are rejected
+    pub fn set_view(&mut self, line: &str) -> Result<(), String> {
+        if let Some((key, _)) = line.split_once('=') {
+            let key = key.trim();
+
+            if !VIEW_KEYS.contains(&key) {
+                return Err(format!("'{key}' is not a camera view setting"));
+            }
+        }
+
+        self.set(line)
+    }
+
+    /// Overrides these settings with any settings set in another
+    pub fn merge(&mut self, other: Settings) {
+        macro_rules! merge {
+            ($($field:ident),*) => {
+                $(
+                    if other.$field.is_some() {
+                        self.$field = other.$field;
+                    }
+                )*
+            };
+        }
+
+        merge!(
+            width,
+            height,
+            samples,
+            depth,
+            fov,
+            look_from,
+            look_at,
+            up,
+            defocus_angle,
+            focus_dist,
+            time_span,
+            gamma,
+            threads,
+            time_limit,
+            noise_target
+        );
+    }
+
+    /// Applies the settings to the main parameters
+    pub fn apply(&self, parms: &mut MainParms) -> Result<(), String> {
+        self.apply_camera(&mut parms.cam)?;
+
+        parms.set_gamma(self.gamma.unwrap_or(DEFAULT_GAMMA));
+
+        Ok(())
+    }
+
+    /// Applies the camera settings to a camera. Fails without changing the camera if the
+    /// resulting view can't be used
+    pub fn apply_camera(&self, cam: &mut Camera) -> Result<(), String> {
+        let view = if self.look_from.is_some() || self.look_at.is_some() || self.up.is_some() {
+            let (look_from, look_at, vup) = cam.view();
+
+            let look_from = self.look_from.map(to_point).unwrap_or(look_from);
+            let look_at = self.look_at.map(to_point).unwrap_or(look_at);
+            let vup = self.up.map(to_vec).unwrap_or(vup);
+
+            check_view(&look_from, &look_at, &vup)?;
+
+            Some((look_from, look_at, vup))
+        } else {
+            None
+        };
+
+        // Set image dimensions if overridden
+        match (self.width, self.height) {
+            (Some(w), None) => cam.set_width(w),
+            (None, Some(h)) => cam.set_height(h),
+            (Some(w), Some(h)) => cam.set_dimensions(w, h),
+            _ => (),
+        }
+
+        if let Some(samples) = self.samples {
+            cam.set_samples_per_pixel(samples);
+        }
+
+        if let Some(depth) = self.depth {
+            cam.set_max_depth(depth);
+        }
+
+        if let Some(fov) = self.fov {
+            cam.set_vfov(fov);
+        }
+
+        if let Some((look_from, look_at, vup)) = view {
+            cam.set_view(look_from, look_at, vup);
+        }
+
+        if self.defocus_angle.is_some() || self.focus_dist.is_some() {
+            let (defocus_angle, focus_dist) = cam.focus();
+
+            cam.set_focus(
+                self.defocus_angle.unwrap_or(defocus_angle),
+                self.focus_dist.unwrap_or(focus_dist),
+            );
+        }
+
+        if let Some(time_span) = self.time_span {
+            cam.set_time_span(time_span);
+        }
+
+        if let Some(time_limit) = self.time_limit {
+            cam.set_time_limit(Duration::from_secs_f64(time_limit));
+        }
+
+        if let Some(noise_target) = self.noise_target {
+            cam.set_noise_target(noise_target);
+        }
+
+        Ok(())
+    }
+
+    /// Creates settings for the view of a camera (position, orientation, field of view and focus)
+    pub fn new_view(cam: &Camera) -> Self {
+        let (look_from, look_at, vup) = cam.view();
+        let (defocus_angle, focus_dist) = cam.focus();
+
+        Self {
+            fov: Some(cam.vfov()),
+            look_from: Some(look_from.e.map(flt_prim)),
+            look_at: Some(look_at.e.map(flt_prim)),
+            up: Some(vup.e.map(flt_prim)),
+            defocus_angle: Some(defocus_angle),
+            focus_dist: Some(focus_dist),
+            ..Self::default()
+        }
+    }
+
+    /// Creates the full settings in effect for the main parameters
+    pub fn new_effective(parms: &MainParms, gamma: FltPrim, threads: usize) -> Self {
+        let cam = &parms.cam;
+
+        let (width, height) = cam.dimensions();
+
+        Self {
+            width: Some(width),
+            height: Some(height),
+            samples: Some(cam.samples_per_pixel()),
+            depth: Some(cam.max_depth()),
+            time_span: Some(cam.time_span()),
+            gamma: Some(gamma),
+            threads: Some(threads),
+            time_limit: cam.time_limit().map(|limit| limit.as_secs_f64()),
+            noise_target: cam.noise_target(),
+            ..Self::new_view(cam)
+        }
+    }
+
+    /// Prints the settings in the settings file format
+    pub fn dump(&self) {
+        println!("# Render settings");
+
+        // Ignore failure writing to stdout
+        let _ = self.write(&mut stdout().lock());
+    }
+
+    /// Writes the settings in the settings file format
+    pub fn write(&self, out: &mut impl Write) -> io::Result<()> {
+        macro_rules! write_value {
+            ($($field:ident),*) => {
+                $(
+                    if let Some(value) = self.$field {
+                        writeln!(out, "{} = {value}", stringify!($field))?;
+                    }
+                )*
+            };
+        }
+
+        macro_rules! write_triple {
+            ($($field:ident),*) => {
+                $(
+                    if let Some([x, y, z]) = self.$field {
+                        writeln!(out, "{} = {x}, {y}, {z}", stringify!($field))?;
+                    }
+                )*
+            };
+        }
+
+        write_value!(width, height, samples, depth, fov);
+        write_triple!(look_from, look_at, up);
+        write_value!(defocus_angle, focus_dist, time_span, gamma, threads);
+        write_value!(time_limit, noise_target);
+
+        Ok(())
+    }
+
+    /// Returns the settings as command line arguments
+    pub fn args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+
+        macro_rules! arg {
+            ($($field:ident),*) => {
+                $(
+                    if let Some(value) = self.$field {
+                        args.push(format!("--{}={value}", stringify!($field).replace('_', "-")));
+                    }
+                )*
+            };
+        }
+
+        macro_rules! arg_triple {
+            ($($field:ident),*) => {
+                $(
+                    if let Some([x, y, z]) = self.$field {
+                        args.push(format!("--{}={x},{y},{z}", stringify!($field).replace('_', "-")));
+                    }
+                )*
+            };
+        }
+
+        arg!(width, height, samples, depth, fov);
+        arg_triple!(look_from, look_at, up);
+        arg!(defocus_angle, focus_dist, time_span, gamma, threads);
+        arg!(time_limit, noise_target);
+
+        args
+    }
+}
+
+/// Parses a single value
+fn parse_value<T>(value: &str) -> Result<T, String>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    value
+        .parse::<T>()
+        .map_err(|e| format!("Invalid value '{value}' ({e})"))
+}
+
+/// Parses a single value, checking it meets a requirement
+fn parse_checked<T>(
+    value: &str,
+    valid: impl FnOnce(&T) -> bool,
+    requirement: &str,
+) -> Result<T, String>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    let parsed = parse_value::<T>(value.trim())?;
+
+    if valid(&parsed) {
+        Ok(parsed)
+    } else {
+        Err(format!("Invalid value '{value}' ({requirement})"))
+    }
+}
+
+/// Parses a count which must be at least one
+pub(crate) fn parse_count(value: &str) -> Result<u64, String> {
+    parse_checked(value, |v| *v > 0, "must be at least 1")
+}
+
+/// Parses a value which must be greater than zero
+pub(crate) fn parse_positive(value: &str) -> Result<FltPrim, String> {
+    parse_checked(
+        value,
+        |v: &FltPrim| v.is_finite() && *v > 0.0,
+        "must be positive",
+    )
+}
+
+/// Parses a value which must not be negative
+pub(crate) fn parse_non_negative(value: &str) -> Result<FltPrim, String> {
+    parse_checked(
+        value,
+        |v: &FltPrim| v.is_finite() && *v >= 0.0,
+        "must not be negative",
+    )
+}
+
+/// Parses a fraction from 0 to 1
+pub(crate) fn parse_fraction(value: &str) -> Result<FltPrim, String> {
+    parse_checked(
+        value,
+        |v: &FltPrim| (0.0..=1.0).contains(v),
+        "must be between 0 and 1",
+    )
+}
+
+/// Parses a vertical field of view in degrees
+pub(crate) fn parse_fov(value: &str) -> Result<FltPrim, String> {
+    parse_checked(
+        value,
+        |v: &FltPrim| *v > 0.0 && *v < 180.0,
+        "must be between 0 and 180 degrees",
+    )
+}
+
+/// Parses a defocus angle in degrees
+pub(crate) fn parse_defocus_angle(value: &str) -> Result<FltPrim, String> {
+    parse_checked(
+        value,
+        |v: &FltPrim| *v >= 0.0 && *v < 180.0,
+        "must be at least 0 and less than 180 degrees",
+    )
+}
+
+/// Parses a time in seconds, or in minutes or hours with an 'm' or 'h' suffix
+pub(crate) fn parse_time(value: &str) -> Result<f64, String> {
+    let value = value.trim();
+
+    let (number, scale) = match value.char_indices().last() {
+        Some((pos, 'h')) => (&value[..pos], 3600.0),
+        Some((pos, 'm')) => (&value[..pos], 60.0),
+        Some((pos, 's')) => (&value[..pos], 1.0),
+        _ => (value, 1.0),
+    };
+
+    let seconds = parse_value::<f64>(number.trim())? * scale;
+
+    if seconds.is_finite() && seconds > 0.0 {
+        Ok(seconds)
+    } else {
+        Err(format!("Time '{value}' must be positive"))
+    }
+}
+
+/// Parses a noise level as a fraction, or as a percentage with a '%' suffix
+pub(crate) fn parse_noise(value: &str) -> Result<FltPrim, String> {
+    let value = value.trim();
+
+    let noise = match value.strip_suffix('%') {
+        Some(percent) => parse_value::<FltPrim>(percent.trim())? / 100.0,
+        None => parse_value::<FltPrim>(value)?,
+    };
+
+    if noise.is_finite() && noise > 0.0 {
+        Ok(noise)
+    } else {
+        Err(format!("Noise level '{value}' must be positive"))
+    }
+}
+
+/// Parses three comma separated values, optionally in parentheses
+pub(crate) fn parse_triple(value: &str) -> Result<[FltPrim; 3], String> {
+    let inner = value.trim().trim_start_matches('(').trim_end_matches(')');
+
+    let values = inner
+        .split(',')
+        .map(|v| parse_value::<FltPrim>(v.trim()))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    values
+        .try_into()
+        .map_err(|_| format!("'{value}' is not three comma separated values"))
+}
+
+/// Checks a camera can be oriented from a view
+fn check_view(look_from: &Point3, look_at: &Point3, vup: &Vec3) -> Result<(), String> {
+    let view = look_from.vec_to(look_at);
+
+    if view.length() == 0.0 {
+        return Err("The look from and look at points must be different".to_string());
+    }
+
+    if vup.length() == 0.0 || view.unit_vector().cross(&vup.unit_vector()).length() < 1e-6 {
+        return Err("The up direction must not be parallel to the view direction".to_string());
+    }
+
+    Ok(())
+}
+
+fn to_point(e: [FltPrim; 3]) -> Point3 {
+    Point3::new(e[0], e[1], e[2])
+}
+
+fn to_vec(e: [FltPrim; 3]) -> Vec3 {
+    Vec3::new(e[0], e[1], e[2])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, iter, process};
+
+    use clap::Parser;
+
+    use super::*;
+    use crate::Args;
+
+    fn example_settings() -> Settings {
+        Settings {
+            width: Some(640),
+            height: Some(360),
+            samples: Some(64),
+            depth: Some(20),
+            fov: Some(35.5),
+            look_from: Some([-1.5, 2.0, 10.25]),
+            look_at: Some([0.0, 0.5, 0.0]),
+            up: Some([0.0, 1.0, 0.0]),
+            defocus_angle: Some(0.6),
+            focus_dist: Some(10.0),
+            time_span: Some(1.0),
+            gamma: Some(2.2),
+            threads: Some(4),
+            time_limit: Some(90.0),
+            noise_target: Some(0.02),
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let settings = example_settings();
+
+        // Write and load as a settings file
+        let mut out = Vec::new();
+        settings.write(&mut out).unwrap();
+
+        let path = env::temp_dir().join(format!("settings_test_{}.cfg", process::id()));
+        fs::write(&path, &out).unwrap();
+
+        let loaded = Settings::load(&path);
+        fs::remove_file(&path).unwrap();
+
+        let loaded = loaded.unwrap();
+        assert_eq!(loaded, settings);
+
+        // Pass as command line arguments
+        let args = Args::try_parse_from(iter::once("test".to_string()).chain(loaded.args()));
+
+        assert_eq!(args.unwrap().settings(), settings);
+    }
+
+    #[test]
+    fn test_invalid_values() {
+        for line in [
+            "width = 0",
+            "samples = 0",
+            "depth = -1",
+            "fov = 0",
+            "fov = 180",
+            "look_at = 1, 2",
+            "defocus_angle = -1",
+            "focus_dist = 0",
+            "time_span = -1",
+            "gamma = -2",
+            "time_limit = 0",
+            "noise_target = 0%",
+            "threads = 0",
+            "colour = 1",
+            "samples",
+        ] {
+            assert!(Settings::default().set(line).is_err(), "{line}");
+        }
+
+        for arg in [
+            "--width=0",
+            "--samples=0",
+            "--fov=200",
+            "--focus-dist=-1",
+            "--time-span=-1",
+            "--fps=0",
+            "--fps=NaN",
+            "--shutter=1.5",
+            "--threads=0",
+        ] {
+            assert!(Args::try_parse_from(["test", arg]).is_err(), "{arg}");
+        }
+    }
+
+    #[test]
+    fn test_invalid_view() {
+        let mut cam = Camera::new(100, 1.0, 10, 10);
+
+        cam.set_view(
+            Point3::new(0.0, 0.0, 10.0),
+            Point3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        );
+
+        // Looking at the camera position
+        let settings = Settings {
+            look_at: Some([0.0, 0.0, 10.0]),
+            ..Settings::default()
+        };
+
+        assert!(settings.apply_camera(&mut cam).is_err());
+
+        // Up along the view direction
+        let settings = Settings {
+            fov: Some(20.0),
+            up: Some([0.0, 0.0, -2.0]),
+            ..Settings::default()
+        };
+
+        assert!(settings.apply_camera(&mut cam).is_err());
+
+        // Camera unchanged
+        assert_eq!(cam.vfov(), 90.0);
+        assert_eq!(Settings::new_view(&cam).look_at, Some([0.0, 0.0, 0.0]));
+
+        let settings = Settings {
+            up: Some([1.0, 0.0, 0.0]),
+            ..Settings::default()
+        };
+
+        assert!(settings.apply_camera(&mut cam).is_ok());
+        assert_eq!(Settings::new_view(&cam).up, Some([1.0, 0.0, 0.0]));
+    }
+}
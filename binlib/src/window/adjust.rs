@@ -1,4 +1,4 @@
-use raytracer_lib::{camera::Camera, float::*, triple::Vec3};
+use raytracer_lib::{camera::Camera, float::*, transforms::affine::Affine, triple::Vec3};
 
 pub(crate) fn adjust_vfov(cam: &mut Camera, degrees: FltPrim, clear: &mut bool) {
     let vfov = cam.vfov();
@@ -49,3 +49,87 @@ pub(crate) fn adjust_view(
     cam.set_view(new_look_from, new_look_at, new_vup);
     *clear = true;
 }
+
+/// Returns the camera's right, up and backward unit vectors
+fn camera_frame(cam: &Camera) -> (Vec3, Vec3, Vec3) {
+    let (look_from, look_at, vup) = cam.view();
+
+    let w = look_at.vec_to(&look_from).unit_vector();
+    let u = vup.cross(&w).unit_vector();
+    let v = w.cross(&u);
+
+    (u, v, w)
+}
+
+/// Rotates a vector about the camera's up vector (yaw) then the camera's right vector (pitch).
+/// The pitch is skipped if it would turn the vector to or past the up vector
+fn rotate_yaw_pitch(cam: &Camera, vec: &Vec3, yaw: FltPrim, pitch: FltPrim) -> Vec3 {
+    let (_, _, vup) = cam.view();
+    let (u, _, _) = camera_frame(cam);
+
+    let yawed = Affine::new_rotation(&vup, yaw).vector(vec);
+    let pitched = Affine::new_rotation(&u, pitch).vector(&yawed);
+
+    // Component at right angles to the up vector
+    let up = vup.unit_vector();
+    let level = |v: &Vec3| v - &up * v.dot(&up);
+
+    if pitched.unit_vector().dot(&up).abs() > 0.99 || level(&pitched).dot(&level(&yawed)) <= 0.0 {
+        yawed
+    } else {
+        pitched
+    }
+}
+
+pub(crate) fn orbit_view(cam: &mut Camera, yaw: FltPrim, pitch: FltPrim, clear: &mut bool) {
+    let (look_from, look_at, vup) = cam.view();
+
+    let offset = rotate_yaw_pitch(cam, &look_at.vec_to(&look_from), yaw, pitch);
+
+    cam.set_view(&look_at + offset, look_at, vup);
+    *clear = true;
+}
+
+pub(crate) fn look_around(cam: &mut Camera, yaw: FltPrim, pitch: FltPrim, clear: &mut bool) {
+    let (look_from, look_at, vup) = cam.view();
+
+    let direction = rotate_yaw_pitch(cam, &look_from.vec_to(&look_at), yaw, pitch);
+
+    cam.set_view(look_from.clone(), look_from + direction, vup);
+    *clear = true;
+}
+
+pub(crate) fn pan_view(cam: &mut Camera, right: FltPrim, up: FltPrim, clear: &mut bool) {
+    let (u, v, _) = camera_frame(cam);
+
+    let movement = &u * flt(right) + &v * flt(up);
+
+    adjust_view(cam, movement.clone(), movement, Vec3::default(), clear);
+}
+
+pub(crate) fn dolly_view(cam: &mut Camera, distance: Flt, min_distance: Flt, clear: &mut bool) {
+    let (look_from, look_at, vup) = cam.view();
+
+    let offset = look_at.vec_to(&look_from);
+    let length = offset.length();
+    let new_length = (length - distance).max(min_distance);
+
+    if new_length != length {
+        cam.set_view(&look_at + offset * (new_length / length), look_at, vup);
+        *clear = true;
+    }
+}
+
+pub(crate) fn fly_view(
+    cam: &mut Camera,
+    right: FltPrim,
+    up: FltPrim,
+    forward: FltPrim,
+    clear: &mut bool,
+) {
+    let (u, v, w) = camera_frame(cam);
+
+    let movement = &u * flt(right) + &v * flt(up) - &w * flt(forward);
+
+    adjust_view(cam, movement.clone(), movement, Vec3::default(), clear);
+}
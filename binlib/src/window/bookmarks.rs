@@ -0,0 +1,141 @@
+use std::{
+    collections::BTreeMap,
+    fs::{read_to_string, File},
+    io::{self, BufWriter, Write},
+    path::PathBuf,
+};
+
+use raytracer_lib::camera::Camera;
+
+use crate::settings::Settings;
+
+/// Numbered camera views saved to a file. The file contains a "[n]" line for each bookmark
+/// followed by its view settings in the settings file format. Other settings such as the image
+/// size are not allowed as they can't be changed while the window is open
+pub(super) struct Bookmarks {
+    /// Bookmark file
+    path: PathBuf,
+    /// Camera view settings for each bookmark number
+    marks: BTreeMap<u32, Settings>,
+}
+
+impl Bookmarks {
+    /// Loads bookmarks from a file. A missing or invalid file gives no bookmarks
+    pub fn load(path: PathBuf) -> Self {
+        let mut bookmarks = Self {
+            path,
+            marks: BTreeMap::new(),
+        };
+
+        if let Ok(contents) = read_to_string(&bookmarks.path) {
+            if let Err(e) = bookmarks.parse(&contents) {
+                println!("Ignoring bookmarks in {}: {e}", bookmarks.path.display());
+                bookmarks.marks.clear();
+            }
+        }
+
+        bookmarks
+    }
+
+    /// Parses the contents of a bookmark file
+    fn parse(&mut self, contents: &str) -> Result<(), String> {
+        let mut current = None;
+
+        for (line_no, line) in contents.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let result = match line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                Some(number) => number
+                    .trim()
+                    .parse::<u32>()
+                    .map(|number| current = Some(number))
+                    .map_err(|_| format!("Invalid bookmark number '{number}'")),
+                None => match current {
+                    Some(number) => self.marks.entry(number).or_default().set_view(line),
+                    None => Err("Setting outside a bookmark".to_string()),
+                },
+            };
+
+            result.map_err(|e| format!("line {}: {e}", line_no + 1))?;
+        }
+
+        Ok(())
+    }
+
+    /// Saves the camera view as a numbered bookmark and writes the bookmark file
+    pub fn save(&mut self, number: u32, cam: &Camera) {
+        self.marks.insert(number, Settings::new_view(cam));
+
+        match self.write() {
+            Ok(()) => println!("Bookmark {number} saved to {}", self.path.display()),
+            Err(e) => println!("Failed to write {} ({e})", self.path.display()),
+        }
+    }
+
+    /// Restores the camera view from a numbered bookmark
+    pub fn restore(&self, number: u32, cam: &mut Camera, clear: &mut bool) {
+        match self.marks.get(&number) {
+            Some(settings) => match settings.apply_camera(cam) {
+                Ok(()) => {
+                    println!("Bookmark {number} restored");
+                    *clear = true;
+                }
+                Err(e) => println!("Bookmark {number} can't be restored ({e})"),
+            },
+            None => println!("Bookmark {number} is not set"),
+        }
+    }
+
+    /// Writes the bookmark file
+    fn write(&self) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(&self.path)?);
+
+        writeln!(out, "# Camera bookmarks")?;
+
+        for (number, settings) in &self.marks {
+            writeln!(out)?;
+            writeln!(out, "[{number}]")?;
+            settings.write(&mut out)?;
+        }
+
+        out.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(contents: &str) -> Result<Bookmarks, String> {
+        let mut bookmarks = Bookmarks {
+            path: PathBuf::new(),
+            marks: BTreeMap::new(),
+        };
+
+        bookmarks.parse(contents).map(|()| bookmarks)
+    }
+
+    #[test]
+    fn test_view_settings() {
+        let bookmarks = parse("[1]\nfov = 40\nlook_from = 1, 2, 3\n\n[2]\nfocus_dist = 5\n")
+            .expect("Failed to parse");
+
+        assert_eq!(bookmarks.marks.len(), 2);
+        assert_eq!(bookmarks.marks[&1].fov, Some(40.0));
+        assert_eq!(bookmarks.marks[&1].look_from, Some([1.0, 2.0, 3.0]));
+        assert_eq!(bookmarks.marks[&2].focus_dist, Some(5.0));
+    }
+
+    #[test]
+    fn test_other_settings() {
+        for setting in ["width = 100", "height = 100", "samples = 10", "gamma = 1"] {
+            let result = parse(&format!("[1]\nfov = 40\n{setting}\n"));
+
+            assert!(result.is_err(), "{setting} accepted");
+        }
+    }
+}
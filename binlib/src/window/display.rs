@@ -0,0 +1,280 @@
+//! Rendered buffers and the display transforms used to show them in the window
+
+use raytracer_lib::{camera::Shading, float::*, gamma::Gamma, triple::Colour};
+
+/// Buffer shown in the window
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(super) enum View {
+    /// Rendered image
+    #[default]
+    Beauty,
+    /// Surface normals of the first hit
+    Normals,
+    /// Unlit surface colour of the first hit
+    Albedo,
+    /// Distance to the first hit, nearest brightest
+    Depth,
+    /// Full resolution samples taken for each pixel
+    Samples,
+}
+
+impl View {
+    /// Returns the next view in the cycle
+    pub fn next(self) -> Self {
+        match self {
+            View::Beauty => View::Normals,
+            View::Normals => View::Albedo,
+            View::Albedo => View::Depth,
+            View::Depth => View::Samples,
+            View::Samples => View::Beauty,
+        }
+    }
+
+    /// Returns the name of the view
+    pub fn name(self) -> &'static str {
+        match self {
+            View::Beauty => "beauty",
+            View::Normals => "normals",
+            View::Albedo => "albedo",
+            View::Depth => "depth",
+            View::Samples => "samples",
+        }
+    }
+}
+
+/// Tone mapping operator for the rendered image
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(super) enum ToneMap {
+    /// Clamp values to the displayable range
+    #[default]
+    Clamp,
+    /// Reinhard (x / (1 + x))
+    Reinhard,
+    /// ACES filmic curve (Narkowicz fit)
+    Aces,
+}
+
+impl ToneMap {
+    /// Returns the next tone mapping operator in the cycle
+    pub fn next(self) -> Self {
+        match self {
+            ToneMap::Clamp => ToneMap::Reinhard,
+            ToneMap::Reinhard => ToneMap::Aces,
+            ToneMap::Aces => ToneMap::Clamp,
+        }
+    }
+
+    /// Returns the name of the tone mapping operator
+    pub fn name(self) -> &'static str {
+        match self {
+            ToneMap::Clamp => "clamp",
+            ToneMap::Reinhard => "reinhard",
+            ToneMap::Aces => "aces",
+        }
+    }
+
+    /// Maps a linear component value
+    fn map(self, x: Flt) -> Flt {
+        let x = x.max(flt(0.0));
+
+        match self {
+            ToneMap::Clamp => x,
+            ToneMap::Reinhard => x / (flt(1.0) + x),
+            ToneMap::Aces => {
+                (x * (flt(2.51) * x + flt(0.03))) / (x * (flt(2.43) * x + flt(0.59)) + flt(0.14))
+            }
+        }
+    }
+}
+
+/// Display settings. These are applied when the buffers are drawn so changing them does not
+/// need a re-render
+#[derive(Debug, Clone, PartialEq)]
+pub(super) struct Display {
+    /// Buffer to show
+    pub view: View,
+    /// Exposure adjustment in stops
+    pub exposure: FltPrim,
+    /// Tone mapping operator
+    pub tone_map: ToneMap,
+    /// Apply gamma correction?
+    pub gamma: bool,
+    /// Show the heads up display?
+    pub hud: bool,
+}
+
+impl Default for Display {
+    fn default() -> Self {
+        Self {
+            view: View::default(),
+            exposure: 0.0,
+            tone_map: ToneMap::default(),
+            gamma: true,
+            hud: true,
+        }
+    }
+}
+
+/// Buffers rendered for the current camera
+pub(super) struct Buffers {
+    /// Image width
+    width: u64,
+    /// Low resolution preview colours, shown until a pixel has a full resolution sample
+    preview: Vec<Colour>,
+    /// Accumulated full resolution colours
+    pub accum: Vec<Colour>,
+    /// Full resolution samples taken for each pixel
+    pub samples: Vec<u64>,
+    /// Surface normals as colours
+    normals: Vec<Colour>,
+    /// Unlit surface colours
+    albedo: Vec<Colour>,
+    /// Distance to the first hit
+    depth: Vec<Colour>,
+}
+
+impl Buffers {
+    /// Creates empty buffers for an image size
+    pub fn new(width: u64, height: u64) -> Self {
+        let size = width as usize * height as usize;
+
+        Self {
+            width,
+            preview: vec![Colour::default(); size],
+            accum: vec![Colour::default(); size],
+            samples: vec![0; size],
+            normals: vec![Colour::default(); size],
+            albedo: vec![Colour::default(); size],
+            depth: vec![Colour::default(); size],
+        }
+    }
+
+    /// Clears the full resolution and auxiliary buffers. The preview is kept until it is
+    /// overwritten
+    pub fn clear(&mut self) {
+        self.accum.fill(Colour::default());
+        self.samples.fill(0);
+        self.normals.fill(Colour::default());
+        self.albedo.fill(Colour::default());
+        self.depth.fill(Colour::default());
+    }
+
+    /// Merges a rendered tile into the buffers
+    pub fn merge_tile(
+        &mut self,
+        columns: (u64, u64),
+        lines: (u64, u64),
+        block: u64,
+        shading: Shading,
+        pixels: Vec<Colour>,
+    ) {
+        let w = self.width;
+        let mut pixels = pixels.into_iter();
+
+        if block > 1 {
+            // Fill each block of the preview
+            for y in (lines.0..lines.1).step_by(block as usize) {
+                for x in (columns.0..columns.1).step_by(block as usize) {
+                    let colour = pixels.next().expect("Short tile");
+
+                    for j in y..(y + block).min(lines.1) {
+                        for i in x..(x + block).min(columns.1) {
+                            self.preview[(j * w + i) as usize] = colour.clone();
+                        }
+                    }
+                }
+            }
+
+            return;
+        }
+
+        for j in lines.0..lines.1 {
+            for i in columns.0..columns.1 {
+                let elem = (j * w + i) as usize;
+                let colour = pixels.next().expect("Short tile");
+
+                match shading {
+                    Shading::Full => {
+                        self.accum[elem] += colour;
+                        self.samples[elem] += 1;
+                    }
+                    Shading::Normals => self.normals[elem] = colour,
+                    Shading::Albedo => self.albedo[elem] = colour,
+                    Shading::Depth => self.depth[elem] = colour,
+                }
+            }
+        }
+    }
+
+    /// Draws the buffers into a 0RGB window buffer
+    pub fn draw(&self, display: &Display, gamma: &Gamma, out: &mut [u32]) {
+        let gamma = if display.gamma { gamma } else { &Gamma::None };
+
+        match display.view {
+            View::Beauty => {
+                let scale = flt(display.exposure.exp2());
+
+                for (elem, out) in out.iter_mut().enumerate() {
+                    let colour = match self.samples[elem] {
+                        0 => self.preview[elem].clone(),
+                        samples => &self.accum[elem] / flt(samples as FltPrim),
+                    } * scale;
+
+                    let mapped =
+                        Colour::new_from_array([0, 1, 2].map(|c| display.tone_map.map(colour[c])));
+
+                    *out = to_0rgb(&mapped, gamma);
+                }
+            }
+            View::Normals => draw_colours(&self.normals, &Gamma::None, out),
+            View::Albedo => draw_colours(&self.albedo, gamma, out),
+            View::Depth => {
+                let max = self
+                    .depth
+                    .iter()
+                    .map(|depth| depth[0])
+                    .fold(flt(0.0), |a, b| a.max(b));
+
+                // Nearest is white, furthest dark grey and nothing hit black
+                for (depth, out) in self.depth.iter().zip(out.iter_mut()) {
+                    let value = if depth[0] > 0.0 {
+                        flt(1.0) - flt(0.9) * depth[0] / max
+                    } else {
+                        flt(0.0)
+                    };
+
+                    *out = to_0rgb(&Colour::new_from_array([value; 3]), &Gamma::None);
+                }
+            }
+            View::Samples => {
+                let max = self
+                    .samples
+                    .iter()
+                    .copied()
+                    .max()
+                    .unwrap_or_default()
+                    .max(1);
+
+                for (&samples, out) in self.samples.iter().zip(out.iter_mut()) {
+                    let value = flt(samples as FltPrim / max as FltPrim);
+
+                    *out = to_0rgb(&Colour::new_from_array([value; 3]), &Gamma::None);
+                }
+            }
+        }
+    }
+}
+
+/// Draws a buffer of colours into a 0RGB window buffer
+fn draw_colours(colours: &[Colour], gamma: &Gamma, out: &mut [u32]) {
+    for (colour, out) in colours.iter().zip(out.iter_mut()) {
+        *out = to_0rgb(colour, gamma);
+    }
+}
+
+/// Converts a colour to 0RGB
+fn to_0rgb(colour: &Colour, gamma: &Gamma) -> u32 {
+    let (r, g, b) = colour.to_rgb(gamma);
+
+    ((r as u32) << 16) + ((g as u32) << 8) + b as u32
+}
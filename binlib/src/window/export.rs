@@ -0,0 +1,98 @@
+use std::{env, error::Error, path::PathBuf};
+
+use raytracer_lib::{camera::Camera, float::*, gamma::Gamma, triple::Colour};
+
+use crate::{save_image, settings::Settings};
+
+/// Returns the name of the scene (the executable name) to use for saved files
+pub(super) fn scene_name() -> String {
+    env::current_exe()
+        .ok()
+        .and_then(|exe| {
+            exe.file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+        })
+        .unwrap_or_else(|| "scene".to_string())
+}
+
+/// Saves the accumulated image as PNG and (linear) OpenEXR files with the next free number.
+/// Pixels without any full resolution samples are black
+pub(super) fn save_snapshot(
+    accum: &[Colour],
+    samples: &[u64],
+    width: u64,
+    gamma: &Gamma,
+) -> Result<(), Box<dyn Error>> {
+    // Find the next free file name
+    let name = scene_name();
+
+    let (png, exr) = (1..)
+        .map(|n| {
+            (
+                PathBuf::from(format!("{name}_{n:04}.png")),
+                PathBuf::from(format!("{name}_{n:04}.exr")),
+            )
+        })
+        .find(|(png, exr)| !png.exists() && !exr.exists())
+        .expect("No free file name");
+
+    // Average the samples for each pixel
+    let image = accum
+        .chunks(width as usize)
+        .zip(samples.chunks(width as usize))
+        .map(|(line, samples)| {
+            line.iter()
+                .zip(samples)
+                .map(|(colour, &samples)| match samples {
+                    0 => Colour::default(),
+                    _ => colour / flt(samples as FltPrim),
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    save_image(image.clone(), &png, gamma)?;
+    save_image(image, &exr, gamma)?;
+
+    println!(
+        "Image ({} samples per pixel) written to {} and {}",
+        samples.iter().min().copied().unwrap_or_default(),
+        png.display(),
+        exr.display()
+    );
+
+    Ok(())
+}
+
+/// Prints the camera view as code for a scene
+pub(super) fn print_scene_snippet(cam: &Camera) {
+    let (look_from, look_at, vup) = cam.view();
+    let (defocus_angle, focus_dist) = cam.focus();
+
+    let [fx, fy, fz] = look_from.e.map(flt_prim);
+    let [ax, ay, az] = look_at.e.map(flt_prim);
+    let [ux, uy, uz] = vup.e.map(flt_prim);
+
+    println!("    // Camera");
+    println!("    cam.set_vfov({:?});", cam.vfov());
+    println!();
+    println!("    cam.set_view(");
+    println!("        Point3::new({fx:?}, {fy:?}, {fz:?}),");
+    println!("        Point3::new({ax:?}, {ay:?}, {az:?}),");
+    println!("        Vec3::new({ux:?}, {uy:?}, {uz:?}),");
+    println!("    );");
+
+    if defocus_angle > 0.0 {
+        println!();
+        println!("    cam.set_focus({defocus_angle:?}, {focus_dist:?});");
+    }
+}
+
+/// Prints the camera view as command line arguments
+pub(super) fn print_cli_args(cam: &Camera) {
+    println!(
+        "{} {}",
+        scene_name(),
+        Settings::new_view(cam).args().join(" ")
+    );
+}
@@ -0,0 +1,122 @@
+//! Heads up display text drawn over the window buffer
+
+/// Glyph width in font pixels
+const GLYPH_WIDTH: usize = 5;
+
+/// Glyph height in font pixels
+const GLYPH_HEIGHT: usize = 7;
+
+/// Space around and between lines of text in font pixels
+const SPACING: usize = 2;
+
+/// Text colour (0RGB)
+const TEXT_COLOUR: u32 = 0xffffff;
+
+/// 5x7 bitmap font. Each row is 5 bits, most significant bit leftmost
+const FONT: [(char, [u8; GLYPH_HEIGHT]); 46] = [
+    (' ', [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]),
+    ('0', [0x0e, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0e]),
+    ('1', [0x04, 0x0c, 0x04, 0x04, 0x04, 0x04, 0x0e]),
+    ('2', [0x0e, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1f]),
+    ('3', [0x1f, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0e]),
+    ('4', [0x02, 0x06, 0x0a, 0x12, 0x1f, 0x02, 0x02]),
+    ('5', [0x1f, 0x10, 0x1e, 0x01, 0x01, 0x11, 0x0e]),
+    ('6', [0x06, 0x08, 0x10, 0x1e, 0x11, 0x11, 0x0e]),
+    ('7', [0x1f, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08]),
+    ('8', [0x0e, 0x11, 0x11, 0x0e, 0x11, 0x11, 0x0e]),
+    ('9', [0x0e, 0x11, 0x11, 0x0f, 0x01, 0x02, 0x0c]),
+    ('A', [0x0e, 0x11, 0x11, 0x11, 0x1f, 0x11, 0x11]),
+    ('B', [0x1e, 0x11, 0x11, 0x1e, 0x11, 0x11, 0x1e]),
+    ('C', [0x0e, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0e]),
+    ('D', [0x1c, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1c]),
+    ('E', [0x1f, 0x10, 0x10, 0x1e, 0x10, 0x10, 0x1f]),
+    ('F', [0x1f, 0x10, 0x10, 0x1e, 0x10, 0x10, 0x10]),
+    ('G', [0x0e, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0f]),
+    ('H', [0x11, 0x11, 0x11, 0x1f, 0x11, 0x11, 0x11]),
+    ('I', [0x0e, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0e]),
+    ('J', [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0c]),
+    ('K', [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11]),
+    ('L', [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1f]),
+    ('M', [0x11, 0x1b, 0x15, 0x15, 0x11, 0x11, 0x11]),
+    ('N', [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11]),
+    ('O', [0x0e, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0e]),
+    ('P', [0x1e, 0x11, 0x11, 0x1e, 0x10, 0x10, 0x10]),
+    ('Q', [0x0e, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0d]),
+    ('R', [0x1e, 0x11, 0x11, 0x1e, 0x14, 0x12, 0x11]),
+    ('S', [0x0f, 0x10, 0x10, 0x0e, 0x01, 0x01, 0x1e]),
+    ('T', [0x1f, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04]),
+    ('U', [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0e]),
+    ('V', [0x11, 0x11, 0x11, 0x11, 0x11, 0x0a, 0x04]),
+    ('W', [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0a]),
+    ('X', [0x11, 0x11, 0x0a, 0x04, 0x0a, 0x11, 0x11]),
+    ('Y', [0x11, 0x11, 0x11, 0x0a, 0x04, 0x04, 0x04]),
+    ('Z', [0x1f, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1f]),
+    ('.', [0x00, 0x00, 0x00, 0x00, 0x00, 0x0c, 0x0c]),
+    (':', [0x00, 0x0c, 0x0c, 0x00, 0x0c, 0x0c, 0x00]),
+    ('/', [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00]),
+    ('+', [0x00, 0x04, 0x04, 0x1f, 0x04, 0x04, 0x00]),
+    ('-', [0x00, 0x00, 0x00, 0x1f, 0x00, 0x00, 0x00]),
+    ('%', [0x18, 0x19, 0x02, 0x04, 0x08, 0x13, 0x03]),
+    ('(', [0x02, 0x04, 0x08, 0x08, 0x08, 0x04, 0x02]),
+    (')', [0x08, 0x04, 0x02, 0x02, 0x02, 0x04, 0x08]),
+    ('?', [0x0e, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04]),
+];
+
+/// Draws lines of text in the top left corner of a 0RGB buffer over a darkened background.
+/// Letters are shown in upper case and characters missing from the font as '?'
+pub(super) fn draw_hud(buffer: &mut [u32], width: usize, height: usize, lines: &[String]) {
+    // Scale the font up for larger images
+    let scale = (width / 400).max(1);
+
+    let columns = lines
+        .iter()
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or_default();
+
+    // Darken the background
+    let box_w = ((columns * (GLYPH_WIDTH + 1) + SPACING * 2) * scale).min(width);
+    let box_h = ((lines.len() * (GLYPH_HEIGHT + SPACING) + SPACING) * scale).min(height);
+
+    for y in 0..box_h {
+        for pixel in &mut buffer[y * width..y * width + box_w] {
+            *pixel = (*pixel >> 2) & 0x3f3f3f;
+        }
+    }
+
+    // Draw the text
+    for (line_no, line) in lines.iter().enumerate() {
+        let top = (SPACING + line_no * (GLYPH_HEIGHT + SPACING)) * scale;
+
+        for (col, c) in line.chars().enumerate() {
+            let left = (SPACING + col * (GLYPH_WIDTH + 1)) * scale;
+
+            for (row, bits) in glyph(c).iter().enumerate() {
+                for bit in 0..GLYPH_WIDTH {
+                    if bits & (0x10 >> bit) == 0 {
+                        continue;
+                    }
+
+                    for y in top + row * scale..top + (row + 1) * scale {
+                        for x in left + bit * scale..left + (bit + 1) * scale {
+                            if x < width && y < height {
+                                buffer[y * width + x] = TEXT_COLOUR;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Returns the glyph for a character
+fn glyph(c: char) -> &'static [u8; GLYPH_HEIGHT] {
+    let c = c.to_ascii_uppercase();
+
+    FONT.iter()
+        .find(|(font_char, _)| *font_char == c)
+        .or_else(|| FONT.iter().find(|(font_char, _)| *font_char == '?'))
+        .map(|(_, glyph)| glyph)
+        .expect("No '?' glyph")
+}
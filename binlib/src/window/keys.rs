@@ -1,11 +1,10 @@
 use minifb::{InputCallback, Window};
-use raytracer_lib::{float::*, triple::Vec3};
+use raytracer_lib::{camera::Camera, float::*, triple::Vec3};
 use std::{cell::RefCell, rc::Rc};
 
-use crate::MainParms;
-
 use super::{
-    adjust::{adjust_depth, adjust_focus, adjust_vfov, adjust_view},
+    adjust::{adjust_depth, adjust_focus, adjust_vfov, adjust_view, fly_view},
+    bookmarks::Bookmarks,
     WinState,
 };
 
@@ -48,11 +47,37 @@ pub(super) fn print_help() {
     println!("    b/B n/N => Decrease / increase focus distance");
     println!("  Depth:");
     println!("    [/{{ ]/}} => Decrease / increase ray depth (number of bounces)");
+    println!("  Mouse:");
+    println!("    Left drag   => Orbit around the target (look around in fly mode)");
+    println!("    Right drag  => Pan");
+    println!("    Scroll      => Move towards / away from the target");
+    println!("    Left click  => Show details of the object under the pointer");
+    println!("    p           => Toggle autofocus on the clicked object");
+    println!("  Fly mode:");
+    println!("    m           => Toggle fly mode");
+    println!("    W/w S/s     => Forward / back");
+    println!("    A/a D/d     => Left / right");
+    println!("    Q/q E/e     => Down / up");
+    println!("  Display:");
+    println!("    /           => Cycle buffer (beauty, normals, albedo, depth, samples)");
+    println!("    ,           => Cycle tone mapping (clamp, reinhard, aces)");
+    println!("    .           => Toggle gamma correction");
+    println!("    -/_ =/+     => Decrease / increase exposure");
+    println!("    0           => Reset exposure");
+    println!("    ;           => Toggle heads up display");
+    println!("  Bookmarks:");
+    println!("    F1-F9       => Save camera bookmark");
+    println!("    1-9         => Restore camera bookmark");
+    println!("  Export:");
+    println!("    F10         => Save the image (PNG and OpenEXR)");
+    println!("    F11         => Print the camera as scene code");
+    println!("    F12         => Print the camera as command line arguments");
 }
 
 pub(super) fn process_keys(
-    state: &mut MainParms,
-    winstate: &WinState,
+    cam: &mut Camera,
+    winstate: &mut WinState,
+    bookmarks: &Bookmarks,
     keys: &Rc<RefCell<Vec<u32>>>,
 ) -> bool {
     let mut clear = false;
@@ -60,106 +85,170 @@ pub(super) fn process_keys(
     let mut keys = keys.borrow_mut();
 
     for t in keys.iter() {
-        match char::from_u32(*t) {
+        let key = char::from_u32(*t);
+
+        // Fly mode movement relative to the camera (right, up, forward)
+        if winstate.fly {
+            let small = flt_prim(winstate.move_delta_small);
+            let big = flt_prim(winstate.move_delta_big);
+
+            let movement = match key {
+                Some('w') => Some((0.0, 0.0, small)),
+                Some('W') => Some((0.0, 0.0, big)),
+                Some('s') => Some((0.0, 0.0, -small)),
+                Some('S') => Some((0.0, 0.0, -big)),
+                Some('a') => Some((-small, 0.0, 0.0)),
+                Some('A') => Some((-big, 0.0, 0.0)),
+                Some('d') => Some((small, 0.0, 0.0)),
+                Some('D') => Some((big, 0.0, 0.0)),
+                Some('q') => Some((0.0, -small, 0.0)),
+                Some('Q') => Some((0.0, -big, 0.0)),
+                Some('e') => Some((0.0, small, 0.0)),
+                Some('E') => Some((0.0, big, 0.0)),
+                _ => None,
+            };
+
+            if let Some((right, up, forward)) = movement {
+                fly_view(cam, right, up, forward, &mut clear);
+                continue;
+            }
+        }
+
+        match key {
+            // Fly mode
+            Some('m') => {
+                winstate.fly = !winstate.fly;
+                println!("Fly mode {}", if winstate.fly { "on" } else { "off" });
+            }
+            // Display
+            Some('/') => {
+                winstate.display.view = winstate.display.view.next();
+                println!("Showing {} buffer", winstate.display.view.name());
+            }
+            Some(',') => {
+                winstate.display.tone_map = winstate.display.tone_map.next();
+                println!("Tone mapping {}", winstate.display.tone_map.name());
+            }
+            Some('.') => {
+                winstate.display.gamma = !winstate.display.gamma;
+                println!(
+                    "Gamma correction {}",
+                    if winstate.display.gamma { "on" } else { "off" }
+                );
+            }
+            Some('-') => adjust_exposure(winstate, -0.5),
+            Some('_') => adjust_exposure(winstate, -2.0),
+            Some('=') => adjust_exposure(winstate, 0.5),
+            Some('+') => adjust_exposure(winstate, 2.0),
+            Some('0') => adjust_exposure(winstate, -winstate.display.exposure),
+            Some(';') => winstate.display.hud = !winstate.display.hud,
+            // Autofocus on click
+            Some('p') => {
+                winstate.autofocus = !winstate.autofocus;
+                println!(
+                    "Autofocus {}",
+                    if winstate.autofocus { "on" } else { "off" }
+                );
+            }
             // Vertical FOV
-            Some('z') => adjust_vfov(&mut state.cam, -1.0, &mut clear),
-            Some('Z') => adjust_vfov(&mut state.cam, -5.0, &mut clear),
-            Some('x') => adjust_vfov(&mut state.cam, 1.0, &mut clear),
-            Some('X') => adjust_vfov(&mut state.cam, 5.0, &mut clear),
+            Some('z') => adjust_vfov(cam, -1.0, &mut clear),
+            Some('Z') => adjust_vfov(cam, -5.0, &mut clear),
+            Some('x') => adjust_vfov(cam, 1.0, &mut clear),
+            Some('X') => adjust_vfov(cam, 5.0, &mut clear),
             // Focus
-            Some('c') => adjust_focus(&mut state.cam, -0.1, 0.0, &mut clear),
-            Some('C') => adjust_focus(&mut state.cam, -1.0, 0.0, &mut clear),
-            Some('v') => adjust_focus(&mut state.cam, 0.1, 0.0, &mut clear),
-            Some('V') => adjust_focus(&mut state.cam, 1.0, 0.0, &mut clear),
-            Some('b') => adjust_focus(&mut state.cam, 0.0, -1.0, &mut clear),
-            Some('B') => adjust_focus(&mut state.cam, 0.0, -10.0, &mut clear),
-            Some('n') => adjust_focus(&mut state.cam, 0.0, 1.0, &mut clear),
-            Some('N') => adjust_focus(&mut state.cam, 0.0, 10.0, &mut clear),
+            Some('c') => adjust_focus(cam, -0.1, 0.0, &mut clear),
+            Some('C') => adjust_focus(cam, -1.0, 0.0, &mut clear),
+            Some('v') => adjust_focus(cam, 0.1, 0.0, &mut clear),
+            Some('V') => adjust_focus(cam, 1.0, 0.0, &mut clear),
+            Some('b') => adjust_focus(cam, 0.0, -1.0, &mut clear),
+            Some('B') => adjust_focus(cam, 0.0, -10.0, &mut clear),
+            Some('n') => adjust_focus(cam, 0.0, 1.0, &mut clear),
+            Some('N') => adjust_focus(cam, 0.0, 10.0, &mut clear),
             // Depth
-            Some('[') => adjust_depth(&mut state.cam, -1, &mut clear),
-            Some('{') => adjust_depth(&mut state.cam, -5, &mut clear),
-            Some(']') => adjust_depth(&mut state.cam, 1, &mut clear),
-            Some('}') => adjust_depth(&mut state.cam, 5, &mut clear),
+            Some('[') => adjust_depth(cam, -1, &mut clear),
+            Some('{') => adjust_depth(cam, -5, &mut clear),
+            Some(']') => adjust_depth(cam, 1, &mut clear),
+            Some('}') => adjust_depth(cam, 5, &mut clear),
             // Camera position
             Some('q') => adjust_view(
-                &mut state.cam,
+                cam,
                 Vec3::new(0.0, 0.0, flt_prim(-winstate.move_delta_small)),
                 Vec3::default(),
                 Vec3::default(),
                 &mut clear,
             ),
             Some('Q') => adjust_view(
-                &mut state.cam,
+                cam,
                 Vec3::new(0.0, 0.0, flt_prim(-winstate.move_delta_big)),
                 Vec3::default(),
                 Vec3::default(),
                 &mut clear,
             ),
             Some('w') => adjust_view(
-                &mut state.cam,
+                cam,
                 Vec3::new(0.0, flt_prim(winstate.move_delta_small), 0.0),
                 Vec3::default(),
                 Vec3::default(),
                 &mut clear,
             ),
             Some('W') => adjust_view(
-                &mut state.cam,
+                cam,
                 Vec3::new(0.0, flt_prim(winstate.move_delta_big), 0.0),
                 Vec3::default(),
                 Vec3::default(),
                 &mut clear,
             ),
             Some('e') => adjust_view(
-                &mut state.cam,
+                cam,
                 Vec3::new(0.0, 0.0, flt_prim(winstate.move_delta_small)),
                 Vec3::default(),
                 Vec3::default(),
                 &mut clear,
             ),
             Some('E') => adjust_view(
-                &mut state.cam,
+                cam,
                 Vec3::new(0.0, 0.0, flt_prim(winstate.move_delta_big)),
                 Vec3::default(),
                 Vec3::default(),
                 &mut clear,
             ),
             Some('a') => adjust_view(
-                &mut state.cam,
+                cam,
                 Vec3::new(flt_prim(-winstate.move_delta_small), 0.0, 0.0),
                 Vec3::default(),
                 Vec3::default(),
                 &mut clear,
             ),
             Some('A') => adjust_view(
-                &mut state.cam,
+                cam,
                 Vec3::new(flt_prim(-winstate.move_delta_big), 0.0, 0.0),
                 Vec3::default(),
                 Vec3::default(),
                 &mut clear,
             ),
             Some('s') => adjust_view(
-                &mut state.cam,
+                cam,
                 Vec3::new(0.0, flt_prim(-winstate.move_delta_small), 0.0),
                 Vec3::default(),
                 Vec3::default(),
                 &mut clear,
             ),
             Some('S') => adjust_view(
-                &mut state.cam,
+                cam,
                 Vec3::new(0.0, flt_prim(-winstate.move_delta_big), 0.0),
                 Vec3::default(),
                 Vec3::default(),
                 &mut clear,
             ),
             Some('d') => adjust_view(
-                &mut state.cam,
+                cam,
                 Vec3::new(flt_prim(winstate.move_delta_small), 0.0, 0.0),
                 Vec3::default(),
                 Vec3::default(),
                 &mut clear,
             ),
             Some('D') => adjust_view(
-                &mut state.cam,
+                cam,
                 Vec3::new(flt_prim(winstate.move_delta_big), 0.0, 0.0),
                 Vec3::default(),
                 Vec3::default(),
@@ -167,84 +256,84 @@ pub(super) fn process_keys(
             ),
             // Camera target
             Some('r') => adjust_view(
-                &mut state.cam,
+                cam,
                 Vec3::default(),
                 Vec3::new(0.0, 0.0, flt_prim(-winstate.move_delta_small)),
                 Vec3::default(),
                 &mut clear,
             ),
             Some('R') => adjust_view(
-                &mut state.cam,
+                cam,
                 Vec3::default(),
                 Vec3::new(0.0, 0.0, flt_prim(-winstate.move_delta_big)),
                 Vec3::default(),
                 &mut clear,
             ),
             Some('t') => adjust_view(
-                &mut state.cam,
+                cam,
                 Vec3::default(),
                 Vec3::new(0.0, flt_prim(winstate.move_delta_small), 0.0),
                 Vec3::default(),
                 &mut clear,
             ),
             Some('T') => adjust_view(
-                &mut state.cam,
+                cam,
                 Vec3::default(),
                 Vec3::new(0.0, flt_prim(winstate.move_delta_big), 0.0),
                 Vec3::default(),
                 &mut clear,
             ),
             Some('y') => adjust_view(
-                &mut state.cam,
+                cam,
                 Vec3::default(),
                 Vec3::new(0.0, 0.0, flt_prim(winstate.move_delta_small)),
                 Vec3::default(),
                 &mut clear,
             ),
             Some('Y') => adjust_view(
-                &mut state.cam,
+                cam,
                 Vec3::default(),
                 Vec3::new(0.0, 0.0, flt_prim(winstate.move_delta_big)),
                 Vec3::default(),
                 &mut clear,
             ),
             Some('f') => adjust_view(
-                &mut state.cam,
+                cam,
                 Vec3::default(),
                 Vec3::new(flt_prim(-winstate.move_delta_small), 0.0, 0.0),
                 Vec3::default(),
                 &mut clear,
             ),
             Some('F') => adjust_view(
-                &mut state.cam,
+                cam,
                 Vec3::default(),
                 Vec3::new(flt_prim(-winstate.move_delta_big), 0.0, 0.0),
                 Vec3::default(),
                 &mut clear,
             ),
             Some('g') => adjust_view(
-                &mut state.cam,
+                cam,
                 Vec3::default(),
                 Vec3::new(0.0, flt_prim(-winstate.move_delta_small), 0.0),
                 Vec3::default(),
                 &mut clear,
             ),
             Some('G') => adjust_view(
-                &mut state.cam,
+                cam,
                 Vec3::default(),
                 Vec3::new(0.0, flt_prim(-winstate.move_delta_big), 0.0),
                 Vec3::default(),
                 &mut clear,
             ),
             Some('h') => adjust_view(
-                &mut state.cam,
+                cam,
                 Vec3::default(),
                 Vec3::new(flt_prim(winstate.move_delta_small), 0.0, 0.0),
                 Vec3::default(),
                 &mut clear,
             ),
             Some('H') => adjust_view(
-                &mut state.cam,
+                cam,
                 Vec3::default(),
                 Vec3::new(flt_prim(winstate.move_delta_big), 0.0, 0.0),
                 Vec3::default(),
@@ -252,89 +341,91 @@ pub(super) fn process_keys(
             ),
             // Camera orienation
             Some('u') => adjust_view(
-                &mut state.cam,
+                cam,
                 Vec3::default(),
                 Vec3::default(),
                 Vec3::new(0.0, 0.0, -1.0),
                 &mut clear,
             ),
             Some('U') => adjust_view(
-                &mut state.cam,
+                cam,
                 Vec3::default(),
                 Vec3::default(),
                 Vec3::new(0.0, 0.0, -10.0),
                 &mut clear,
             ),
             Some('i') => adjust_view(
-                &mut state.cam,
+                cam,
                 Vec3::default(),
                 Vec3::default(),
                 Vec3::new(0.0, 1.0, 0.0),
                 &mut clear,
             ),
             Some('I') => adjust_view(
-                &mut state.cam,
+                cam,
                 Vec3::default(),
                 Vec3::default(),
                 Vec3::new(0.0, 10.0, 0.0),
                 &mut clear,
             ),
             Some('o') => adjust_view(
-                &mut state.cam,
+                cam,
                 Vec3::default(),
                 Vec3::default(),
                 Vec3::new(0.0, 0.0, 1.0),
                 &mut clear,
             ),
             Some('O') => adjust_view(
-                &mut state.cam,
+                cam,
                 Vec3::default(),
                 Vec3::default(),
                 Vec3::new(0.0, 0.0, 10.0),
                 &mut clear,
             ),
             Some('j') => adjust_view(
-                &mut state.cam,
+                cam,
                 Vec3::default(),
                 Vec3::default(),
                 Vec3::new(-1.0, 0.0, 0.0),
                 &mut clear,
             ),
             Some('J') => adjust_view(
-                &mut state.cam,
+                cam,
                 Vec3::default(),
                 Vec3::default(),
                 Vec3::new(-10.0, 0.0, 0.0),
                 &mut clear,
             ),
             Some('k') => adjust_view(
-                &mut state.cam,
+                cam,
                 Vec3::default(),
                 Vec3::default(),
                 Vec3::new(0.0, -1.0, 0.0),
                 &mut clear,
             ),
             Some('K') => adjust_view(
-                &mut state.cam,
+                cam,
                 Vec3::default(),
                 Vec3::default(),
                 Vec3::new(0.0, -10.0, 0.0),
                 &mut clear,
             ),
             Some('l') => adjust_view(
-                &mut state.cam,
+                cam,
                 Vec3::default(),
                 Vec3::default(),
                 Vec3::new(1.0, 0.0, 0.0),
                 &mut clear,
             ),
             Some('L') => adjust_view(
-                &mut state.cam,
+                cam,
                 Vec3::default(),
                 Vec3::default(),
                 Vec3::new(10.0, 0.0, 0.0),
                 &mut clear,
             ),
+            // Bookmarks
+            Some(c @ '1'..='9') => bookmarks.restore(c as u32 - '0' as u32, cam, &mut clear),
             Some('?') => print_help(),
             // Catch others
             _ => (),
@@ -345,3 +436,9 @@ pub(super) fn process_keys(
 
     clear
 }
+
+/// Adjusts the display exposure by a number of stops
+fn adjust_exposure(winstate: &mut WinState, stops: FltPrim) {
+    winstate.display.exposure += stops;
+    println!("Exposure {:+.1} stops", winstate.display.exposure);
+}
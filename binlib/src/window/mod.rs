@@ -1,16 +1,45 @@
+use bookmarks::Bookmarks;
+use display::{Buffers, Display};
+use export::{print_cli_args, print_scene_snippet, save_snapshot, scene_name};
+use hud::draw_hud;
 use keys::{print_help, process_keys, setup_keys};
-use minifb::{Key, ScaleMode, Window, WindowOptions};
-use raytracer_lib::{float::*, hits::hittable::Hittable, triple::Colour};
-use std::{error::Error, time::Instant};
-
-use crate::MainParms;
+use minifb::{Key, KeyRepeat, ScaleMode, Window, WindowOptions};
+use mouse::{process_mouse, MouseState};
+use pick::pick;
+use raytracer_lib::{camera::Shading, float::*, hits::hittable::Hittable};
+use reload::{add_loaded_files, FileWatcher};
+use std::{
+    error::Error,
+    panic::{self, AssertUnwindSafe},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+use worker::{render_worker, RenderJob, RenderMsg};
+
+use crate::{parms::dump_camera_parameters, settings::Settings, MainParms};
 
 mod adjust;
+mod bookmarks;
+mod display;
+mod export;
+mod hud;
 mod keys;
+mod mouse;
+mod pick;
+mod reload;
+mod worker;
 
 struct WinState {
     move_delta_big: Flt,
     move_delta_small: Flt,
+    fly: bool,
+    autofocus: bool,
+    display: Display,
 }
 
 #[derive(Default)]
@@ -18,12 +47,54 @@ struct RenderState {
     frame_no: u64,
     started: Option<Instant>,
     fps: Flt,
+    /// Render time when stopped
+    elapsed: Duration,
+    /// Full resolution camera rays traced
+    camera_rays: u64,
 }
 
 impl RenderState {
     fn reset(&mut self) {
         self.frame_no = 0;
         self.started = Some(Instant::now());
+        self.elapsed = Duration::ZERO;
+        self.camera_rays = 0;
+    }
+
+    fn elapsed(&self) -> Duration {
+        match self.started {
+            Some(started) => started.elapsed(),
+            None => self.elapsed,
+        }
+    }
+
+    /// Returns the heads up display text
+    fn hud(&self, max_frame: u64, display: &Display) -> Vec<String> {
+        let elapsed = self.elapsed().as_secs_f64();
+
+        let rays_per_sec = if elapsed > 0.0 {
+            self.camera_rays as f64 / elapsed
+        } else {
+            0.0
+        };
+
+        let pass = if self.started.is_some() {
+            format!("Pass {} / {max_frame}", self.frame_no + 1)
+        } else {
+            "Finished".to_string()
+        };
+
+        vec![
+            format!("{pass}  {} spp", self.frame_no),
+            format!("{elapsed:.1}s  {:.2}M rays/s", rays_per_sec / 1_000_000.0),
+            format!(
+                "{}  Exposure {:+.1}  Tone {}  Gamma {}",
+                display.view.name(),
+                display.exposure,
+                display.tone_map.name(),
+                if display.gamma { "on" } else { "off" }
+            ),
+        ]
     }
 
     fn frame_finished(&mut self) {
@@ -41,11 +112,16 @@ impl RenderState {
     }
 
     fn stop(&mut self) {
+        self.elapsed = self.elapsed();
         self.started = None;
     }
 }
 
-pub(super) fn render_to_window(mut state: MainParms) -> Result<(), Box<dyn Error>> {
+pub(super) fn render_to_window(
+    mut state: MainParms,
+    settings: &Settings,
+    preview_shading: Shading,
+) -> Result<(), Box<dyn Error>> {
     // Print float type
     println!("Float type: {FLOAT_DESC} ({} bytes)", size_of::<FltPrim>());
 
@@ -53,6 +129,9 @@ pub(super) fn render_to_window(mut state: MainParms) -> Result<(), Box<dyn Error
     let mut winstate = WinState {
         move_delta_big: flt(100.0),
         move_delta_small: flt(10.0),
+        fly: false,
+        autofocus: false,
+        display: Display::default(),
     };
 
     // Main bounding box
@@ -96,11 +175,15 @@ pub(super) fn render_to_window(mut state: MainParms) -> Result<(), Box<dyn Error
     // Get image dimensions
     let (w, h) = state.cam.dimensions();
 
-    // Create buffer for the image (0RGB u32)
-    let mut output_buffer: Vec<u32> = vec![0; w as usize * h as usize];
+    // Create buffers for the displayed image and the window with the heads up display (0RGB u32)
+    let mut image_buffer: Vec<u32> = vec![0; w as usize * h as usize];
+    let mut window_buffer: Vec<u32> = vec![0; w as usize * h as usize];
+
+    // Rendered buffers
+    let mut buffers = Buffers::new(w, h);
 
     // Use samples per pixel as the max frame number
-    let max_frame = state.cam.samples_per_pixel();
+    let mut max_frame = state.cam.samples_per_pixel();
 
     // Set samples per pixel to 1
     state.cam.set_samples_per_pixel(1);
@@ -123,84 +206,272 @@ pub(super) fn render_to_window(mut state: MainParms) -> Result<(), Box<dyn Error
     // Set up key buffer
     let keys = setup_keys(&mut window);
 
+    // Mouse drag state
+    let mut mouse = MouseState::default();
+
+    // Load camera bookmarks
+    let mut bookmarks = Bookmarks::load(PathBuf::from(format!("{}.bookmarks", scene_name())));
+
     // State
     let mut render_state = RenderState::default();
     render_state.reset();
 
-    // Render the first frame
-    let mut frame = state.cam.render(&state.world, &*state.ambience, None);
-    render_state.frame_finished();
+    // Take the scene reload callback and watch the scene files, including any data files loaded
+    // while building the scene
+    let mut reload = state.reload.take();
+    add_loaded_files(&mut state.watch);
+    let mut watcher = FileWatcher::new(&state.watch);
 
-    while window.is_open() && !window.is_key_down(Key::Escape) {
-        if render_state.started.is_some() {
-            // Build output buffer from the renderer frame
-            let mut outelem = 0;
+    if reload.is_some() {
+        println!("Reloading the scene when its files change:");
 
-            for l in frame.iter() {
-                for c in l {
-                    let (r, g, b) = c.to_rgb(&state.gamma);
+        for path in &state.watch {
+            println!("  {}", path.display());
+        }
+    }
 
-                    output_buffer[outelem] = ((r as u32) << 16) + ((g as u32) << 8) + b as u32;
-                    outelem += 1;
+    // Has the camera been moved from the scene's camera?
+    let mut moved = false;
+
+    // Render generation. Passes for older generations are abandoned
+    let generation = AtomicU64::new(0);
+
+    loop {
+        // Set up the render worker
+        let (job_tx, job_rx) = mpsc::channel();
+        let (result_tx, result_rx) = mpsc::channel();
+
+        let world = &state.world;
+        let ambience = &*state.ambience;
+        let current = &generation;
+
+        let reload_scene = thread::scope(|scope| {
+            scope.spawn(move || render_worker(world, ambience, job_rx, result_tx, current));
+
+            // Own the channel ends here so the worker stops however the window loop ends
+            let (job_tx, result_rx) = (job_tx, result_rx);
+
+            // Render the first frame
+            job_tx.send(RenderJob {
+                generation: generation.load(Ordering::Relaxed),
+                cam: state.cam.clone(),
+                passes: max_frame,
+                preview_shading,
+            })?;
+
+            // Buffers need drawing to the window?
+            let mut redraw = false;
+
+            // Scene files changed?
+            let mut reload_scene = false;
+
+            while window.is_open() && !window.is_key_down(Key::Escape) {
+                let current_gen = generation.load(Ordering::Relaxed);
+
+                // Merge rendered tiles into the buffers
+                for msg in result_rx.try_iter() {
+                    match msg {
+                        RenderMsg::Tile {
+                            generation,
+                            columns,
+                            lines,
+                            block,
+                            shading,
+                            pixels,
+                        } if generation == current_gen => {
+                            if block == 1 && shading == Shading::Full {
+                                render_state.camera_rays += pixels.len() as u64;
+                            }
+
+                            buffers.merge_tile(columns, lines, block, shading, pixels);
+                            redraw = true;
+                        }
+                        RenderMsg::PassFinished { generation } if generation == current_gen => {
+                            // Increment frame number
+                            render_state.frame_finished();
+
+                            // Reached the end?
+                            if render_state.frame_no >= max_frame {
+                                render_state.stop();
+                                window.set_title(&format!(
+                                    "Finished ({:.2} fps) - ESC to exit",
+                                    render_state.fps
+                                ));
+                            }
+                        }
+                        // Abandoned pass
+                        _ => (),
+                    }
                 }
-            }
 
-            // Display the output buffer
-            window.set_title(&format!(
-                "Pass {} ({}%), {:.2} fps - ESC to exit",
-                render_state.frame_no,
-                (render_state.frame_no * 100) / max_frame,
-                render_state.fps
-            ));
-            window.update_with_buffer(&output_buffer, w as usize, h as usize)?;
-        } else {
-            // No buffer update
-            window.update();
-        }
+                if render_state.started.is_some() {
+                    window.set_title(&format!(
+                        "Pass {} ({}%), {:.2} fps - ESC to exit",
+                        render_state.frame_no + 1,
+                        (render_state.frame_no * 100) / max_frame,
+                        render_state.fps
+                    ));
+                }
+
+                // Apply the display transforms
+                if redraw {
+                    buffers.draw(&winstate.display, &state.gamma, &mut image_buffer);
+                    redraw = false;
+                }
+
+                // Display the image, with the heads up display if enabled
+                if winstate.display.hud {
+                    window_buffer.copy_from_slice(&image_buffer);
 
-        // Process key presses
-        if process_keys(&mut state, &winstate, &keys) {
-            // Print new camera parameters
-            state.dump_camera_parameters(false);
+                    draw_hud(
+                        &mut window_buffer,
+                        w as usize,
+                        h as usize,
+                        &render_state.hud(max_frame, &winstate.display),
+                    );
 
-            // Clear frame buffer
-            for fl in frame.iter_mut() {
-                for fc in fl.iter_mut() {
-                    *fc = Colour::default();
+                    window.update_with_buffer(&window_buffer, w as usize, h as usize)?;
+                } else {
+                    window.update_with_buffer(&image_buffer, w as usize, h as usize)?;
                 }
-            }
 
-            // Reset frame count and iterating flag
-            render_state.reset();
-        }
+                let display = winstate.display.clone();
+
+                // Process key presses
+                let mut changed = process_keys(&mut state.cam, &mut winstate, &bookmarks, &keys);
+
+                // Process function keys
+                for key in window.get_keys_pressed(KeyRepeat::No) {
+                    match key {
+                        Key::F1 => bookmarks.save(1, &state.cam),
+                        Key::F2 => bookmarks.save(2, &state.cam),
+                        Key::F3 => bookmarks.save(3, &state.cam),
+                        Key::F4 => bookmarks.save(4, &state.cam),
+                        Key::F5 => bookmarks.save(5, &state.cam),
+                        Key::F6 => bookmarks.save(6, &state.cam),
+                        Key::F7 => bookmarks.save(7, &state.cam),
+                        Key::F8 => bookmarks.save(8, &state.cam),
+                        Key::F9 => bookmarks.save(9, &state.cam),
+                        Key::F10 => {
+                            if let Err(e) =
+                                save_snapshot(&buffers.accum, &buffers.samples, w, &state.gamma)
+                            {
+                                println!("Failed to save the image ({e})");
+                            }
+                        }
+                        Key::F11 => print_scene_snippet(&state.cam),
+                        Key::F12 => print_cli_args(&state.cam),
+                        _ => (),
+                    }
+                }
+
+                // Process mouse movement
+                changed |= process_mouse(&mut state.cam, &winstate, &window, &mut mouse);
+
+                // Display settings changed?
+                if winstate.display != display {
+                    redraw = true;
+                }
+
+                // Pick the object under a click
+                if let Some(pos) = mouse.take_click() {
+                    pick(
+                        &mut state.cam,
+                        world,
+                        &window,
+                        pos,
+                        winstate.autofocus,
+                        &mut changed,
+                    );
+                }
+
+                if changed {
+                    moved = true;
+
+                    // Print new camera parameters
+                    dump_camera_parameters(&state.cam, false);
+
+                    // Abandon the current pass
+                    let next_gen = generation.fetch_add(1, Ordering::Relaxed) + 1;
 
-        if render_state.started.is_some() {
-            // Get the next frame
-            let next_frame = state.cam.render(&state.world, &*state.ambience, None);
-
-            // Merge with the current frame
-            for (fl, nl) in frame.iter_mut().zip(next_frame.into_iter()) {
-                for (fc, nc) in fl.iter_mut().zip(nl.into_iter()) {
-                    // Rolling average
-                    let cnt: Flt = flt(render_state.frame_no as FltPrim);
-                    *fc *= cnt;
-                    *fc += nc;
-                    *fc /= cnt + flt(1.0);
+                    // Clear accumulated samples
+                    buffers.clear();
+
+                    // Reset frame count and iterating flag
+                    render_state.reset();
+
+                    // Start rendering the new view
+                    job_tx.send(RenderJob {
+                        generation: next_gen,
+                        cam: state.cam.clone(),
+                        passes: max_frame,
+                        preview_shading,
+                    })?;
+                }
+
+                // Rebuild the scene if its files have changed
+                if reload.is_some() && watcher.changed() {
+                    reload_scene = true;
+                    break;
                 }
             }
 
-            // Increment frame number
-            render_state.frame_finished();
+            // Stop the render worker
+            generation.fetch_add(1, Ordering::Relaxed);
+            drop(job_tx);
+
+            Ok::<_, Box<dyn Error>>(reload_scene)
+        })?;
 
-            // Reached the end?
-            if render_state.frame_no >= max_frame {
-                render_state.stop();
-                window.set_title(&format!(
-                    "Finished ({:.2} fps) - ESC to exit",
-                    render_state.fps
-                ));
+        if !reload_scene {
+            break;
+        }
+
+        // Rebuild the scene
+        let cam = state.cam.clone();
+        state.cam.set_samples_per_pixel(max_frame);
+
+        if let Some(reload) = &mut reload {
+            // Loaders panic on bad files, which may just be part written
+            let result = panic::catch_unwind(AssertUnwindSafe(|| reload(&mut state)))
+                .unwrap_or_else(|_| Err("panicked".into()));
+
+            match result {
+                Ok(()) => {
+                    println!("Scene reloaded");
+
+                    // Apply the settings to the new camera
+                    if let Err(e) = settings.apply_camera(&mut state.cam) {
+                        println!("Settings not applied to the reloaded camera ({e})");
+                    }
+                    max_frame = state.cam.samples_per_pixel();
+
+                    if moved {
+                        // Keep the current camera
+                        state.cam = cam;
+                    } else {
+                        // Keep the window size
+                        state.cam.set_dimensions(w, h);
+                        state.cam.set_samples_per_pixel(1);
+
+                        dump_camera_parameters(&state.cam, false);
+                    }
+                }
+                Err(e) => {
+                    println!("Failed to reload the scene ({e})");
+                    state.cam = cam;
+                }
             }
         }
+
+        // Watch the files of the new scene
+        add_loaded_files(&mut state.watch);
+        watcher = FileWatcher::new(&state.watch);
+
+        // Restart rendering
+        generation.fetch_add(1, Ordering::Relaxed);
+        buffers.clear();
+        render_state.reset();
     }
 
     Ok(())
@@ -0,0 +1,122 @@
+use minifb::{MouseButton, MouseMode, Window};
+use raytracer_lib::{camera::Camera, float::*};
+
+use super::{
+    adjust::{dolly_view, look_around, orbit_view, pan_view},
+    WinState,
+};
+
+/// Degrees of rotation per pixel of mouse movement
+const DEGREES_PER_PIXEL: FltPrim = 0.25;
+
+/// Distance in pixels the mouse can move while a button is held and still be a click
+const CLICK_SLOP: f32 = 3.0;
+
+/// Mouse drag state
+#[derive(Default)]
+pub(super) struct MouseState {
+    /// Mouse position when a button was pressed
+    press_pos: Option<(f32, f32)>,
+    /// Mouse position when last processed while dragging
+    last_pos: Option<(f32, f32)>,
+    /// Position of a left click (press and release without dragging) to be processed
+    click: Option<(f32, f32)>,
+}
+
+impl MouseState {
+    /// Takes the window position of the last left click
+    pub(super) fn take_click(&mut self) -> Option<(f32, f32)> {
+        self.click.take()
+    }
+}
+
+pub(super) fn process_mouse(
+    cam: &mut Camera,
+    winstate: &WinState,
+    window: &Window,
+    mouse: &mut MouseState,
+) -> bool {
+    let mut clear = false;
+
+    // Scroll to dolly towards or away from the target
+    if let Some((_, scroll)) = window.get_scroll_wheel() {
+        if scroll != 0.0 {
+            dolly_view(
+                cam,
+                flt(scroll as FltPrim) * winstate.move_delta_small,
+                winstate.move_delta_small / 10.0,
+                &mut clear,
+            );
+        }
+    }
+
+    // Left button to orbit (or look around in fly mode), right or middle button to pan
+    let rotate = window.get_mouse_down(MouseButton::Left);
+    let pan =
+        window.get_mouse_down(MouseButton::Right) || window.get_mouse_down(MouseButton::Middle);
+
+    let pos = window.get_mouse_pos(MouseMode::Pass);
+
+    match (rotate || pan, pos) {
+        (true, Some(pos)) => match (mouse.press_pos, mouse.last_pos) {
+            (None, _) => mouse.press_pos = Some(pos),
+            (Some(press_pos), None) => {
+                // Start dragging once the mouse has moved far enough
+                if (pos.0 - press_pos.0).hypot(pos.1 - press_pos.1) > CLICK_SLOP {
+                    mouse.last_pos = Some(press_pos);
+                }
+            }
+            (Some(_), Some(last_pos)) => {
+                let dx = (pos.0 - last_pos.0) as FltPrim;
+                let dy = (pos.1 - last_pos.1) as FltPrim;
+
+                if dx != 0.0 || dy != 0.0 {
+                    if pan {
+                        // Move the scene with the pointer
+                        let scale = pan_scale(cam, window);
+
+                        pan_view(cam, -dx * scale, dy * scale, &mut clear);
+                    } else if winstate.fly {
+                        look_around(
+                            cam,
+                            -dx * DEGREES_PER_PIXEL,
+                            -dy * DEGREES_PER_PIXEL,
+                            &mut clear,
+                        );
+                    } else {
+                        orbit_view(
+                            cam,
+                            -dx * DEGREES_PER_PIXEL,
+                            -dy * DEGREES_PER_PIXEL,
+                            &mut clear,
+                        );
+                    }
+                }
+
+                mouse.last_pos = Some(pos);
+            }
+        },
+        (true, None) => (),
+        (false, _) => {
+            // Released without dragging?
+            if mouse.last_pos.is_none() && mouse.press_pos.is_some() {
+                mouse.click = mouse.press_pos;
+            }
+
+            mouse.press_pos = None;
+            mouse.last_pos = None;
+        }
+    }
+
+    clear
+}
+
+/// Returns the distance moved at the target per window pixel
+fn pan_scale(cam: &Camera, window: &Window) -> FltPrim {
+    let (look_from, look_at, _) = cam.view();
+    let (_, h) = window.get_size();
+
+    let distance = flt_prim(look_from.vec_to(&look_at).length());
+
+    2.0 * distance * (cam.vfov().to_radians() / 2.0).tan() / h as FltPrim
+}
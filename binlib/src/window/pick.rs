@@ -0,0 +1,64 @@
+use minifb::Window;
+use raytracer_lib::{
+    camera::Camera,
+    float::*,
+    hits::{
+        hittable::{Hittable, T_MIN},
+        hittable_list::HittableList,
+    },
+};
+
+/// Casts a ray through a window position and prints details of the object hit. If autofocus
+/// is on the focus distance is set to the depth of the hit
+pub(super) fn pick(
+    cam: &mut Camera,
+    world: &HittableList,
+    window: &Window,
+    pos: (f32, f32),
+    autofocus: bool,
+    clear: &mut bool,
+) {
+    // Map the window position to the image (the window may have been resized)
+    let (win_w, win_h) = window.get_size();
+    let (w, h) = cam.dimensions();
+
+    let x = pos.0 as FltPrim * w as FltPrim / win_w.max(1) as FltPrim;
+    let y = pos.1 as FltPrim * h as FltPrim / win_h.max(1) as FltPrim;
+
+    let ray = cam.image_ray(x, y);
+
+    let mut rng = rand::thread_rng();
+
+    let Some(hit) = world.hit(&mut rng, &ray, flt(T_MIN)..flt_max()) else {
+        println!("Pick ({x:.0}, {y:.0}): nothing hit");
+        return;
+    };
+
+    let distance = hit.t * ray.direction().length();
+
+    println!("Pick ({x:.0}, {y:.0}):");
+    println!("  Object     : {}", hit.object);
+    println!("  Material   : {}", hit.material.name());
+    println!("  Point      : {}", hit.p);
+    println!("  Normal     : {}", hit.normal);
+    println!("  Distance   : {distance:.4}");
+    println!("  Front face : {}", hit.front_face);
+
+    if autofocus {
+        // The plane of focus is perpendicular to the view direction
+        let (look_from, look_at, _) = cam.view();
+        let depth = flt_prim(
+            look_from
+                .vec_to(&hit.p)
+                .dot(&look_from.vec_to(&look_at).unit_vector()),
+        );
+
+        let (defocus_angle, focus_dist) = cam.focus();
+
+        if depth > 0.0 && depth != focus_dist {
+            cam.set_focus(defocus_angle, depth);
+            println!("Focus distance set to {depth:.4}");
+            *clear = true;
+        }
+    }
+}
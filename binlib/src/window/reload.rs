@@ -0,0 +1,69 @@
+use raytracer_lib::files::take_loaded;
+use std::{
+    fs::metadata,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime},
+};
+
+/// Time between checks of the watched files
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Watches files for changes by polling their modification times
+pub(super) struct FileWatcher {
+    /// Watched files and their last seen modification times (None if missing)
+    files: Vec<(PathBuf, Option<SystemTime>)>,
+    /// Time of the last check
+    last_check: Instant,
+}
+
+impl FileWatcher {
+    /// Starts watching a list of files
+    pub fn new(paths: &[PathBuf]) -> Self {
+        Self {
+            files: paths
+                .iter()
+                .map(|path| (path.clone(), modified(path)))
+                .collect(),
+            last_check: Instant::now(),
+        }
+    }
+
+    /// Returns true if any of the files has been modified, created or removed since the last
+    /// check. Files are checked at most every poll interval
+    pub fn changed(&mut self) -> bool {
+        if self.files.is_empty() || self.last_check.elapsed() < POLL_INTERVAL {
+            return false;
+        }
+
+        self.last_check = Instant::now();
+
+        let mut changed = false;
+
+        for (path, last_modified) in &mut self.files {
+            let modified = modified(path);
+
+            if modified != *last_modified {
+                println!("{} has changed", path.display());
+
+                *last_modified = modified;
+                changed = true;
+            }
+        }
+
+        changed
+    }
+}
+
+/// Adds the data files loaded while building the scene to a list of files to watch
+pub(super) fn add_loaded_files(watch: &mut Vec<PathBuf>) {
+    for path in take_loaded() {
+        if !watch.contains(&path) {
+            watch.push(path);
+        }
+    }
+}
+
+/// Returns the modification time of a file
+fn modified(path: &Path) -> Option<SystemTime> {
+    metadata(path).and_then(|meta| meta.modified()).ok()
+}
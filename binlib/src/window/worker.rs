@@ -0,0 +1,181 @@
+//! Background rendering for the interactive window
+
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    mpsc::{Receiver, Sender},
+};
+
+use rayon::prelude::*;
+use raytracer_lib::{
+    ambient::ambience::Ambience,
+    camera::{Camera, Shading},
+    hits::hittable_list::HittableList,
+    triple::Colour,
+};
+
+/// Width and height of a render tile in pixels
+const TILE_SIZE: u64 = 32;
+
+/// Block sizes of the low resolution previews rendered before the first full pass
+const PREVIEW_BLOCKS: [u64; 3] = [8, 4, 2];
+
+/// Shading modes of the auxiliary buffers rendered after the first full pass
+const AOV_SHADINGS: [Shading; 3] = [Shading::Normals, Shading::Albedo, Shading::Depth];
+
+/// Tile columns and lines (start, end)
+type Tile = ((u64, u64), (u64, u64));
+
+/// Render request for the worker
+pub(super) struct RenderJob {
+    /// Generation of the request. Passes for older generations are abandoned
+    pub generation: u64,
+    /// Camera to render with (one sample per pixel per pass)
+    pub cam: Camera,
+    /// Number of passes to render
+    pub passes: u64,
+    /// Shading mode for the low resolution previews
+    pub preview_shading: Shading,
+}
+
+/// Message from the worker
+pub(super) enum RenderMsg {
+    /// A tile of a pass has been rendered
+    Tile {
+        generation: u64,
+        columns: (u64, u64),
+        lines: (u64, u64),
+        /// Size of the block of pixels each colour covers (1 for a full resolution pass)
+        block: u64,
+        /// Shading mode the tile was rendered with
+        shading: Shading,
+        pixels: Vec<Colour>,
+    },
+    /// A full resolution pass has been completed
+    PassFinished { generation: u64 },
+}
+
+/// Render worker state
+struct Worker<'a, 'b> {
+    world: &'a HittableList<'b>,
+    ambience: &'a dyn Ambience,
+    results: Sender<RenderMsg>,
+    current: &'a AtomicU64,
+}
+
+/// Renders jobs as they arrive, streaming tiles back until the job sender is dropped. A job is
+/// abandoned as soon as the current generation moves on
+pub(super) fn render_worker(
+    world: &HittableList,
+    ambience: &dyn Ambience,
+    jobs: Receiver<RenderJob>,
+    results: Sender<RenderMsg>,
+    current: &AtomicU64,
+) {
+    let worker = Worker {
+        world,
+        ambience,
+        results,
+        current,
+    };
+
+    let mut pending = None;
+
+    loop {
+        // Wait for a job
+        let job = match pending.take() {
+            Some(job) => job,
+            None => match jobs.recv() {
+                Ok(job) => job,
+                Err(_) => return,
+            },
+        };
+
+        // Skip to the latest job
+        let job = jobs.try_iter().last().unwrap_or(job);
+
+        let (w, h) = job.cam.dimensions();
+
+        // Build the list of tiles
+        let tiles = (0..h)
+            .step_by(TILE_SIZE as usize)
+            .flat_map(|y| {
+                (0..w)
+                    .step_by(TILE_SIZE as usize)
+                    .map(move |x| ((x, (x + TILE_SIZE).min(w)), (y, (y + TILE_SIZE).min(h))))
+            })
+            .collect::<Vec<Tile>>();
+
+        // Render low resolution previews
+        let mut preview_cam = job.cam.clone();
+        preview_cam.set_shading(job.preview_shading);
+
+        for block in PREVIEW_BLOCKS {
+            worker.render_pass(&tiles, &preview_cam, block, job.generation);
+        }
+
+        // Render full resolution passes
+        for pass in 0..job.passes {
+            worker.render_pass(&tiles, &job.cam, 1, job.generation);
+
+            if !worker.is_current(job.generation) {
+                // Abandoned - start the next job if it has arrived
+                pending = jobs.try_iter().last();
+                break;
+            }
+
+            let finished = RenderMsg::PassFinished {
+                generation: job.generation,
+            };
+
+            if worker.results.send(finished).is_err() {
+                return;
+            }
+
+            // Render the auxiliary buffers after the first pass so the image shows first
+            if pass == 0 {
+                for shading in AOV_SHADINGS {
+                    let mut aov_cam = job.cam.clone();
+                    aov_cam.set_shading(shading);
+
+                    worker.render_pass(&tiles, &aov_cam, 1, job.generation);
+                }
+            }
+        }
+    }
+}
+
+impl<'a, 'b> Worker<'a, 'b> {
+    /// Returns true if a generation has not been abandoned
+    fn is_current(&self, generation: u64) -> bool {
+        self.current.load(Ordering::Relaxed) == generation
+    }
+
+    /// Renders the tiles of a pass with a given block size, stopping if abandoned
+    fn render_pass(&self, tiles: &[Tile], cam: &Camera, block: u64, generation: u64) {
+        tiles
+            .par_iter()
+            .for_each_with(self.results.clone(), |results, &(columns, lines)| {
+                if !self.is_current(generation) {
+                    return;
+                }
+
+                let pixels = cam.render_tile(
+                    self.world,
+                    self.ambience,
+                    columns.0..columns.1,
+                    lines.0..lines.1,
+                    block,
+                );
+
+                // Ignore failure, the window is closing
+                let _ = results.send(RenderMsg::Tile {
+                    generation,
+                    columns,
+                    lines,
+                    block,
+                    shading: cam.shading(),
+                    pixels,
+                });
+            });
+    }
+}
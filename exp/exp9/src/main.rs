@@ -7,32 +7,77 @@ use raytracer_lib::{
     ambient::ambient_light::AmbientLight,
     camera::Camera,
     float::*,
-    hits::{hittable::Hittable, hittable_list::HittableList},
-    materials::{diffuse_light::DiffuseLight, lambertian::Lambertian, material::Material},
+    hits::{
+        aabb::Aabb,
+        hittable::Hittable,
+        hittable_list::{HittableList, OwnedHittableList},
+    },
+    materials::{
+        diffuse_light::DiffuseLight,
+        lambertian::Lambertian,
+        material::{MatRef, OwnedMatRef},
+    },
     shapes::{boxcomp::BoxComp, quad::Quad, sphere::Sphere},
-    textures::image::Image,
+    textures::{image::Image, texture::TexRef},
     transforms::constant_medium::ConstantMedium,
     triple::{Colour, Point3, Vec3},
 };
 
 fn main() -> Result<(), Box<dyn Error>> {
+    // World
+    let (world, lantern_bbox) = build_world();
+
+    // Ambient light
+    let ambience = AmbientLight::new(Colour::default());
+
+    // Camera
+    let mut cam = Camera::new(800, 1.0, 10000, 40);
+
+    cam.set_view(
+        Point3::new(-8.0, 0.0, 8.0),
+        Point3::new(0.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+    );
+
+    cam.set_vfov(45.0);
+
+    // Call common bin main
+    let mut parms = MainParms::new_ambience(cam, world, ambience);
+
+    parms.set_main_bbox(lantern_bbox);
+
+    // Rebuild the world in the window when the lantern texture changes
+    parms.set_reload(Vec::new(), |parms| {
+        let (world, lantern_bbox) = build_world();
+
+        parms.world = world;
+        parms.set_main_bbox(lantern_bbox);
+
+        Ok(())
+    });
+
+    bin_main(parms)
+}
+
+/// Builds the world, returning it with the bounding box of the lantern
+fn build_world() -> (OwnedHittableList, Aabb) {
     // Textures
-    let lantern_tex = Image::new_from_file(&Path::new("lantern.png"));
+    let lantern_tex = Image::new_from_file(Path::new("lantern.png"));
 
     // Materials
     let light = DiffuseLight::new_with_colour(Colour::new(0.94, 0.73, 0.02));
-    let lantern_mat = Lambertian::new_with_texture(&lantern_tex);
+    let lantern_mat = MatRef::shared(Lambertian::new_with_texref(TexRef::boxed(lantern_tex)));
     let dummy = Lambertian::new_with_colour(Colour::new(0.0, 1.0, 0.0));
-    let black = Lambertian::new_with_colour(Colour::default());
+    let black = MatRef::shared(Lambertian::new_with_colour(Colour::default()));
 
     // World
     let mut world = HittableList::new();
 
     // Light
-    world.add(BoxComp::new(
+    world.add(BoxComp::new_with_matref(
         Point3::new(-1.999, -2.999, 1.999),
         Point3::new(1.999, 2.999, -1.999),
-        &light,
+        MatRef::boxed(light),
     ));
 
     let lantern = build_lantern(
@@ -47,41 +92,23 @@ fn main() -> Result<(), Box<dyn Error>> {
     world.add(lantern);
 
     // Environment white smoke
-    let env_smoke_boundary = Sphere::new(Point3::new(0.0, 0.0, 0.0), 100.0, &dummy);
+    let env_smoke_boundary =
+        Sphere::new_with_matref(Point3::new(0.0, 0.0, 0.0), 100.0, MatRef::boxed(dummy));
 
     let env_smoke =
         ConstantMedium::new_with_colour(env_smoke_boundary, 0.02, Colour::new(1.0, 1.0, 1.0));
 
     world.add(env_smoke);
 
-    // Ambient light
-    let ambience = AmbientLight::new(Colour::default());
-
-    // Camera
-    let mut cam = Camera::new(800, 1.0, 10000, 40);
-
-    cam.set_view(
-        Point3::new(-8.0, 0.0, 8.0),
-        Point3::new(0.0, 0.0, 0.0),
-        Vec3::new(0.0, 1.0, 0.0),
-    );
-
-    cam.set_vfov(45.0);
-
-    // Call common bin main
-    let mut parms = MainParms::new_ambience(cam, world, ambience);
-
-    parms.set_main_bbox(lantern_bbox);
-
-    bin_main(parms)
+    (world, lantern_bbox)
 }
 
-fn build_lantern<'a>(
+fn build_lantern(
     a: Point3,
     b: Point3,
-    material: &'a dyn Material,
-    end: &'a dyn Material,
-) -> HittableList<'a> {
+    material: &OwnedMatRef,
+    end: &OwnedMatRef,
+) -> OwnedHittableList {
     let mut sides = HittableList::new();
 
     // Construct the two opposite vertices with the minimum and maximum coordinates.
@@ -92,41 +119,41 @@ fn build_lantern<'a>(
     let dy = Vec3::new_flt(flt(0.0), max.y() - min.y(), flt(0.0));
     let dz = Vec3::new_flt(flt(0.0), flt(0.0), max.z() - min.z());
 
-    sides.add(Quad::new(
+    sides.add(Quad::new_with_matref(
         Point3::new_flt(min.x(), min.y(), max.z()),
         dx.clone(),
         dy.clone(),
-        material,
+        material.share().expect("Material not shared"),
     )); // front
-    sides.add(Quad::new(
+    sides.add(Quad::new_with_matref(
         Point3::new_flt(max.x(), min.y(), max.z()),
         -(&dz),
         dy.clone(),
-        material,
+        material.share().expect("Material not shared"),
     )); // right
-    sides.add(Quad::new(
+    sides.add(Quad::new_with_matref(
         Point3::new_flt(max.x(), min.y(), min.z()),
         -(&dx),
         dy.clone(),
-        material,
+        material.share().expect("Material not shared"),
     )); // back
-    sides.add(Quad::new(
+    sides.add(Quad::new_with_matref(
         Point3::new_flt(min.x(), min.y(), min.z()),
         dz.clone(),
         dy,
-        material,
+        material.share().expect("Material not shared"),
     )); // left
-    sides.add(Quad::new(
+    sides.add(Quad::new_with_matref(
         Point3::new_flt(min.x(), max.y(), max.z()),
         dx.clone(),
         -(&dz),
-        end,
+        end.share().expect("Material not shared"),
     )); // top
-    sides.add(Quad::new(
+    sides.add(Quad::new_with_matref(
         Point3::new_flt(min.x(), min.y(), min.z()),
         dx,
         dz,
-        end,
+        end.share().expect("Material not shared"),
     )); // bottom
 
     sides
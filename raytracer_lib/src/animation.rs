@@ -0,0 +1,222 @@
+//! Keyframe animation tracks
+
+use std::fmt::Debug;
+
+use crate::{
+    float::*,
+    triple::{Point3, Vec3},
+};
+
+/// Value that can be interpolated between keyframes
+pub trait Keyable: Clone + Debug + Send + Sync {
+    /// Returns the weighted sum of values (the weights sum to one)
+    fn weighted_sum(values: &[(Flt, &Self)]) -> Self;
+}
+
+impl Keyable for Flt {
+    fn weighted_sum(values: &[(Flt, &Self)]) -> Self {
+        values
+            .iter()
+            .fold(flt(0.0), |sum, (weight, value)| sum + *weight * **value)
+    }
+}
+
+impl Keyable for Vec3 {
+    fn weighted_sum(values: &[(Flt, &Self)]) -> Self {
+        Vec3::new_from_array([0, 1, 2].map(|axis| {
+            values
+                .iter()
+                .fold(flt(0.0), |sum, (weight, value)| sum + *weight * value[axis])
+        }))
+    }
+}
+
+impl Keyable for Point3 {
+    fn weighted_sum(values: &[(Flt, &Self)]) -> Self {
+        Point3::new_from_array([0, 1, 2].map(|axis| {
+            values
+                .iter()
+                .fold(flt(0.0), |sum, (weight, value)| sum + *weight * value[axis])
+        }))
+    }
+}
+
+/// Interpolation curve between keyframes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Interpolation {
+    /// Hold each key until the next
+    Step,
+    /// Straight line between keys
+    Linear,
+    /// Ease in and out of each key
+    Smooth,
+    /// Catmull-Rom spline through the keys
+    CatmullRom,
+}
+
+/// Keyframed value over time. Before the first key and after the last key the value is held
+#[derive(Debug, Clone)]
+pub struct Track<T: Keyable> {
+    keys: Vec<(Flt, T)>,
+    interpolation: Interpolation,
+}
+
+impl<T: Keyable> Track<T> {
+    /// Creates a new empty track with a given interpolation curve
+    pub fn new(interpolation: Interpolation) -> Self {
+        Self {
+            keys: Vec::new(),
+            interpolation,
+        }
+    }
+
+    /// Creates a new track holding a constant value
+    pub fn new_constant(value: T) -> Self {
+        let mut track = Self::new(Interpolation::Step);
+
+        track.add_key(0.0, value);
+
+        track
+    }
+
+    /// Adds a key at a given time, replacing any existing key at that time
+    pub fn add_key(&mut self, time: FltPrim, value: T) {
+        let time = flt(time);
+
+        match self
+            .keys
+            .binary_search_by(|(t, _)| t.partial_cmp(&time).expect("Invalid key time"))
+        {
+            Ok(pos) => self.keys[pos] = (time, value),
+            Err(pos) => self.keys.insert(pos, (time, value)),
+        }
+    }
+
+    /// Adds a key at a given time, returning the track
+    pub fn with_key(mut self, time: FltPrim, value: T) -> Self {
+        self.add_key(time, value);
+        self
+    }
+
+    /// Returns the key times
+    pub fn times(&self) -> impl Iterator<Item = Flt> + '_ {
+        self.keys.iter().map(|(time, _)| *time)
+    }
+
+    /// Returns true if the value changes over time
+    pub fn is_animated(&self) -> bool {
+        self.keys.len() > 1
+    }
+
+    /// Returns the value at a given time. Panics if the track has no keys
+    pub fn value_at(&self, time: Flt) -> T {
+        let last = self.keys.len().checked_sub(1).expect("Track has no keys");
+
+        // Index of the first key after the time
+        let next = self.keys.partition_point(|(t, _)| *t <= time);
+
+        if next == 0 {
+            return self.keys[0].1.clone();
+        }
+
+        if next > last {
+            return self.keys[last].1.clone();
+        }
+
+        let (t0, v0) = &self.keys[next - 1];
+        let (t1, v1) = &self.keys[next];
+
+        let s = (time - *t0) / (*t1 - *t0);
+
+        match self.interpolation {
+            Interpolation::Step => v0.clone(),
+            Interpolation::Linear => T::weighted_sum(&[(flt(1.0) - s, v0), (s, v1)]),
+            Interpolation::Smooth => {
+                let s = s * s * (flt(3.0) - flt(2.0) * s);
+
+                T::weighted_sum(&[(flt(1.0) - s, v0), (s, v1)])
+            }
+            Interpolation::CatmullRom => {
+                // Repeat the end keys where there are no neighbours
+                let before = &self.keys[next.saturating_sub(2)].1;
+                let after = &self.keys[(next + 1).min(last)].1;
+
+                let s2 = s * s;
+                let s3 = s2 * s;
+
+                T::weighted_sum(&[
+                    (flt(0.5) * (-s3 + flt(2.0) * s2 - s), before),
+                    (flt(0.5) * (flt(3.0) * s3 - flt(5.0) * s2 + flt(2.0)), v0),
+                    (flt(0.5) * (flt(-3.0) * s3 + flt(4.0) * s2 + s), v1),
+                    (flt(0.5) * (s3 - s2), after),
+                ])
+            }
+        }
+    }
+}
+
+/// Animated camera view parameters. Parameters without a track keep the camera's own values
+#[derive(Debug, Clone, Default)]
+pub struct CameraAnimation {
+    /// Point the camera is looking from
+    pub look_from: Option<Track<Point3>>,
+    /// Point the camera is looking at
+    pub look_at: Option<Track<Point3>>,
+    /// Camera-relative "up" direction
+    pub vup: Option<Track<Vec3>>,
+    /// Vertical field of view in degrees
+    pub vfov: Option<Track<Flt>>,
+    /// Defocus angle in degrees
+    pub defocus_angle: Option<Track<Flt>>,
+    /// Focus distance
+    pub focus_dist: Option<Track<Flt>>,
+}
+
+impl CameraAnimation {
+    /// Returns true if any of the parameters change over time
+    pub fn is_animated(&self) -> bool {
+        self.look_from.as_ref().is_some_and(Track::is_animated)
+            || self.look_at.as_ref().is_some_and(Track::is_animated)
+            || self.vup.as_ref().is_some_and(Track::is_animated)
+            || self.vfov.as_ref().is_some_and(Track::is_animated)
+            || self.defocus_angle.as_ref().is_some_and(Track::is_animated)
+            || self.focus_dist.as_ref().is_some_and(Track::is_animated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interpolation() {
+        let keys = [(0.0, 0.0), (1.0, 2.0), (3.0, 3.0), (4.0, 7.0)];
+
+        for interpolation in [
+            Interpolation::Step,
+            Interpolation::Linear,
+            Interpolation::Smooth,
+            Interpolation::CatmullRom,
+        ] {
+            let track = keys
+                .iter()
+                .fold(Track::new(interpolation), |track, &(t, v)| {
+                    track.with_key(t, flt(v))
+                });
+
+            // Passes through every key and holds outside them
+            for &(t, v) in &keys {
+                assert!((track.value_at(flt(t)) - v).abs() < 1e-12);
+            }
+
+            assert_eq!(track.value_at(flt(-1.0)), flt(0.0));
+            assert_eq!(track.value_at(flt(5.0)), flt(7.0));
+        }
+
+        let linear = Track::new(Interpolation::Linear)
+            .with_key(0.0, Vec3::new(0.0, 0.0, 0.0))
+            .with_key(2.0, Vec3::new(2.0, 4.0, -2.0));
+
+        assert_eq!(linear.value_at(flt(0.5)), Vec3::new(0.5, 1.0, -0.5));
+    }
+}
@@ -1,26 +1,114 @@
 //! Camera
 
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::{
+    ops::Range,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
 
 use rand::{rngs::ThreadRng, thread_rng, Rng};
 use rayon::prelude::*;
 
 use crate::{
     ambient::ambience::Ambience,
+    animation::CameraAnimation,
     float::*,
     hits::{
         hittable::{Hittable, T_MIN},
         hittable_list::HittableList,
     },
     ray::Ray,
+    spectrum::{spectrum_to_rgb, upsample, SampledSpectrum, Wavelengths, MAX_WAVELENGTHS},
     triple::{Colour, Point3, Vec3},
 };
 
 /// Render progress callback
 pub type CamProgressCb = Option<fn(l: u64, h: u64)>;
 
+/// Shading mode
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Shading {
+    /// Full path tracing
+    #[default]
+    Full,
+    /// Surface normal of the first hit as a colour
+    Normals,
+    /// Unlit surface colour of the first hit
+    Albedo,
+    /// Distance to the first hit in all components (zero if nothing is hit)
+    Depth,
+}
+
+/// Samples taken for each unfinished pixel in a progressive render pass
+const PASS_SAMPLES: u64 = 4;
+
+/// Samples taken for a pixel before its noise is trusted
+const MIN_NOISE_SAMPLES: u64 = 16;
+
+/// Luminance below which noise is measured relative to this level instead
+const NOISE_FLOOR: FltPrim = 0.01;
+
+/// Progressive render statistics
+#[derive(Debug, Clone, Default)]
+pub struct RenderStats {
+    /// Number of passes rendered
+    pub passes: u64,
+    /// Average samples per pixel
+    pub samples_per_pixel: FltPrim,
+    /// Fewest samples taken for a pixel
+    pub min_samples: u64,
+    /// Most samples taken for a pixel
+    pub max_samples: u64,
+    /// Mean relative noise of the pixels (standard error of the luminance)
+    pub noise: FltPrim,
+    /// Fraction of the pixels meeting the noise target
+    pub converged: FltPrim,
+}
+
+/// Accumulated samples for a pixel in a progressive render
+#[derive(Debug, Clone, Default)]
+struct PixelSamples {
+    /// Sum of the sample colours
+    sum: Colour,
+    /// Sum of the squared sample luminances
+    lum_sq_sum: Flt,
+    /// Number of samples
+    samples: u64,
+    /// Has the pixel finished?
+    done: bool,
+}
+
+impl PixelSamples {
+    /// Adds a sample
+    fn add(&mut self, colour: Colour) {
+        let lum = colour.luminance();
+
+        self.lum_sq_sum += lum * lum;
+        self.sum += colour;
+        self.samples += 1;
+    }
+
+    /// Returns the mean colour
+    fn colour(&self) -> Colour {
+        &self.sum / flt(self.samples.max(1) as FltPrim)
+    }
+
+    /// Returns the relative standard error of the mean luminance
+    fn noise(&self) -> Flt {
+        if self.samples < 2 {
+            return flt_max();
+        }
+
+        let n = flt(self.samples as FltPrim);
+        let mean = self.sum.luminance() / n;
+        let variance = (self.lum_sq_sum / n - mean * mean).max(flt(0.0)) * n / (n - 1.0);
+
+        (variance / n).sqrt() / mean.max(flt(NOISE_FLOOR))
+    }
+}
+
 /// Camera definition
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone)]
 pub struct Camera {
     /// Image width
     image_width: u64,
@@ -34,20 +122,10 @@ pub struct Camera {
     look_at: Point3,
     /// Camera-relative "up" direction
     vup: Vec3,
-    /// Camera frame basis vector u
-    u: Vec3,
-    /// Camera frame basis vector v
-    v: Vec3,
-    /// Camera frame basis vector w
-    w: Vec3,
     /// Vertical view angle (field of view)
     vfov: Flt,
-    /// Location of pixel (0,0,0)
-    pixel00_loc: Point3,
-    /// Offset to pixel to the right
-    pixel_delta_u: Vec3,
-    /// Offset to pixel below
-    pixel_delta_v: Vec3,
+    /// Viewport for the view parameters
+    viewport: Viewport,
     /// Count of random samples for each pixel
     samples_per_pixel: u64,
     /// Color scale factor for a sum of pixel samples
@@ -58,12 +136,41 @@ pub struct Camera {
     defocus_angle: Flt,
     /// Distance from camera look from point to plane of perfect focus
     focus_dist: Flt,
+    /// Time the shutter opens
+    shutter_open: Flt,
+    /// Time the shutter closes
+    shutter_close: Flt,
+    /// Animated view parameters
+    animation: Option<CameraAnimation>,
+    /// Do the view parameters change while the shutter is open?
+    animated: bool,
+    /// Number of wavelengths per camera path for spectral rendering (0 for RGB rendering)
+    spectral: usize,
+    /// Progressive render time limit
+    time_limit: Option<Duration>,
+    /// Progressive render target relative noise level
+    noise_target: Option<Flt>,
+    /// Shading mode
+    shading: Shading,
+}
+
+/// Camera frame and viewport derived from the view parameters
+#[derive(Debug, Default, Clone)]
+struct Viewport {
+    /// Point camera is looking from
+    look_from: Point3,
+    /// Location of pixel (0,0,0)
+    pixel00_loc: Point3,
+    /// Offset to pixel to the right
+    pixel_delta_u: Vec3,
+    /// Offset to pixel below
+    pixel_delta_v: Vec3,
+    /// Variation angle of rays through each pixel
+    defocus_angle: Flt,
     /// Defocus disk horizontal radius
     defocus_disk_u: Vec3,
     /// Defocus disk vertical radius
     defocus_disk_v: Vec3,
-    /// Time span
-    time_span: Flt,
 }
 
 impl Camera {
@@ -158,12 +265,30 @@ impl Camera {
         self.recalculate();
     }
 
-    /// Sets the render time span
+    /// Sets the render time span, with the shutter opening at time 0
     pub fn set_time_span(&mut self, time_span: FltPrim) {
-        // Values > 1.0 will mean bounding boxes for moving shapes are incorrect
-        assert!((0.0..=1.0).contains(&time_span));
+        self.set_shutter(0.0, time_span);
+    }
+
+    /// Sets the times the shutter opens and closes. A close time before the open time is
+    /// clamped to the open time
+    pub fn set_shutter(&mut self, open: FltPrim, close: FltPrim) {
+        self.shutter_open = flt(open);
+        self.shutter_close = flt(close.max(open));
 
-        self.time_span = flt(time_span);
+        self.recalculate();
+    }
+
+    /// Sets animated view parameters, which override the camera's own view parameters
+    pub fn set_animation(&mut self, animation: CameraAnimation) {
+        self.animation = Some(animation);
+
+        self.recalculate();
+    }
+
+    /// Sets the number of wavelengths carried by each camera path (0 for RGB rendering)
+    pub fn set_spectral(&mut self, wavelengths: usize) {
+        self.spectral = wavelengths.min(MAX_WAVELENGTHS);
     }
 
     /// Sets the samples per pixel
@@ -178,6 +303,24 @@ impl Camera {
         self.max_depth = max_depth;
     }
 
+    /// Sets the shading mode
+    pub fn set_shading(&mut self, shading: Shading) {
+        self.shading = shading;
+    }
+
+    /// Sets the time limit for a progressive render
+    pub fn set_time_limit(&mut self, time_limit: Duration) {
+        self.time_limit = Some(time_limit);
+    }
+
+    /// Sets the target relative noise level for a progressive render (e.g. 0.01 for 1%). Pixels
+    /// which don't reach the target stop at the samples per pixel
+    pub fn set_noise_target(&mut self, noise_target: FltPrim) {
+        assert!(noise_target > 0.0, "Noise target must be positive");
+
+        self.noise_target = Some(flt(noise_target));
+    }
+
     /// Gets the image width
     pub fn dimensions(&self) -> (u64, u64) {
         (self.image_width, self.image_height)
@@ -204,7 +347,22 @@ impl Camera {
 
     /// Gets the render time span
     pub fn time_span(&self) -> FltPrim {
-        flt_prim(self.time_span)
+        flt_prim(self.shutter_close - self.shutter_open)
+    }
+
+    /// Gets the times the shutter opens and closes
+    pub fn shutter(&self) -> (FltPrim, FltPrim) {
+        (flt_prim(self.shutter_open), flt_prim(self.shutter_close))
+    }
+
+    /// Gets the animated view parameters
+    pub fn animation(&self) -> Option<&CameraAnimation> {
+        self.animation.as_ref()
+    }
+
+    /// Gets the number of wavelengths carried by each camera path (0 for RGB rendering)
+    pub fn spectral(&self) -> usize {
+        self.spectral
     }
 
     /// Gets the samples per pixel
@@ -217,6 +375,26 @@ impl Camera {
         self.max_depth
     }
 
+    /// Gets the shading mode
+    pub fn shading(&self) -> Shading {
+        self.shading
+    }
+
+    /// Gets the time limit for a progressive render
+    pub fn time_limit(&self) -> Option<Duration> {
+        self.time_limit
+    }
+
+    /// Gets the target relative noise level for a progressive render
+    pub fn noise_target(&self) -> Option<FltPrim> {
+        self.noise_target.map(flt_prim)
+    }
+
+    /// Returns true if a time limit or noise target is set
+    pub fn is_progressive(&self) -> bool {
+        self.time_limit.is_some() || self.noise_target.is_some()
+    }
+
     /// Renders the scene
     pub fn render(
         &self,
@@ -243,7 +421,7 @@ impl Camera {
                                 let ray = self.get_ray(i, j, &mut rng);
 
                                 // Get the ray's colour
-                                Self::ray_colour(&mut rng, &ray, world, ambience, self.max_depth)
+                                self.sample_colour(&mut rng, ray, world, ambience)
                             })
                             .sum::<Colour>()
                             * self.pixel_samples_scale
@@ -260,74 +438,312 @@ impl Camera {
             .collect::<Vec<_>>()
     }
 
+    /// Renders a rectangle of the image, returning the pixel colours line by line. With a block
+    /// size over one, only the centre pixel of each block of block x block pixels is rendered
+    pub fn render_tile(
+        &self,
+        world: &HittableList,
+        ambience: &dyn Ambience,
+        columns: Range<u64>,
+        lines: Range<u64>,
+        block: u64,
+    ) -> Vec<Colour> {
+        // Get random number generator
+        let mut rng = thread_rng();
+
+        let block = block.max(1);
+
+        // Centre pixel of a block, within the rectangle
+        let centre = |start: u64, range: &Range<u64>| (start + block / 2).min(range.end - 1);
+
+        lines
+            .clone()
+            .step_by(block as usize)
+            .flat_map(|j| columns.clone().step_by(block as usize).map(move |i| (i, j)))
+            .map(|(i, j)| {
+                let (i, j) = (centre(i, &columns), centre(j, &lines));
+
+                // Calculate pixel colour
+                (0..self.samples_per_pixel)
+                    .map(|_| {
+                        // Construct a random ray
+                        let ray = self.get_ray(i, j, &mut rng);
+
+                        // Get the ray's colour
+                        self.sample_colour(&mut rng, ray, world, ambience)
+                    })
+                    .sum::<Colour>()
+                    * self.pixel_samples_scale
+            })
+            .collect()
+    }
+
+    /// Renders the scene progressively in passes. Rendering stops when the time limit is
+    /// reached or every pixel is finished. With a noise target a pixel is finished when it meets
+    /// the target or reaches the samples per pixel, which caps noisy pixels. Without a noise
+    /// target pixels are only finished by the samples per pixel when there is no time limit
+    pub fn render_progressive(
+        &self,
+        world: &HittableList,
+        ambience: &dyn Ambience,
+        progresscb: CamProgressCb,
+    ) -> (Vec<Vec<Colour>>, RenderStats) {
+        let start = Instant::now();
+
+        let total = self.image_width * self.image_height;
+
+        let mut pixels = vec![
+            vec![PixelSamples::default(); self.image_width as usize];
+            self.image_height as usize
+        ];
+
+        let mut passes = 0;
+        let mut pass_time = Duration::ZERO;
+
+        loop {
+            // Stop rather than start a pass which would overrun the time limit
+            if let Some(time_limit) = self.time_limit {
+                if passes > 0 && start.elapsed() + pass_time > time_limit {
+                    break;
+                }
+            }
+
+            let pass_start = Instant::now();
+
+            // For each scan line...
+            pixels.par_iter_mut().enumerate().for_each(|(j, line)| {
+                // Get random number generator
+                let mut rng = thread_rng();
+
+                // For each unfinished pixel...
+                for (i, pixel) in line.iter_mut().enumerate().filter(|(_, p)| !p.done) {
+                    for _ in 0..PASS_SAMPLES {
+                        // Construct a random ray
+                        let ray = self.get_ray(i as u64, j as u64, &mut rng);
+
+                        // Add the ray's colour
+                        pixel.add(self.sample_colour(&mut rng, ray, world, ambience));
+                    }
+
+                    pixel.done = match self.noise_target {
+                        Some(target) => {
+                            pixel.samples >= self.samples_per_pixel
+                                || (pixel.samples >= MIN_NOISE_SAMPLES && pixel.noise() < target)
+                        }
+                        None => {
+                            self.time_limit.is_none() && pixel.samples >= self.samples_per_pixel
+                        }
+                    };
+                }
+            });
+
+            passes += 1;
+            pass_time = pass_start.elapsed();
+
+            let done = pixels.iter().flatten().filter(|p| p.done).count() as u64;
+
+            // Report progress
+            if let Some(progresscb) = progresscb {
+                match self.time_limit {
+                    Some(time_limit) => progresscb(
+                        (start.elapsed().as_millis() as u64).min(time_limit.as_millis() as u64),
+                        (time_limit.as_millis() as u64).max(1),
+                    ),
+                    None => progresscb(done, total),
+                }
+            }
+
+            if done == total {
+                break;
+            }
+        }
+
+        // Gather statistics
+        let samples = pixels.iter().flatten().map(|p| p.samples);
+        let noise = pixels.iter().flatten().map(|p| flt_prim(p.noise()));
+
+        let stats = RenderStats {
+            passes,
+            samples_per_pixel: samples.clone().sum::<u64>() as FltPrim / total as FltPrim,
+            min_samples: samples.clone().min().unwrap_or(0),
+            max_samples: samples.max().unwrap_or(0),
+            noise: noise.sum::<FltPrim>() / total as FltPrim,
+            converged: match self.noise_target {
+                Some(target) => {
+                    pixels
+                        .iter()
+                        .flatten()
+                        .filter(|p| p.noise() < target)
+                        .count() as FltPrim
+                        / total as FltPrim
+                }
+                None => 0.0,
+            },
+        };
+
+        let image = pixels
+            .iter()
+            .map(|line| line.iter().map(PixelSamples::colour).collect())
+            .collect();
+
+        (image, stats)
+    }
+
     /// Recalculate camera parameters
     fn recalculate(&mut self) {
+        self.pixel_samples_scale = flt(1.0) / flt(self.samples_per_pixel as FltPrim);
+
+        self.animated = self.animation.as_ref().is_some_and(|animation| {
+            animation.is_animated() && self.shutter_close > self.shutter_open
+        });
+
+        self.viewport = self.viewport_at(self.shutter_open);
+    }
+
+    /// Calculates the viewport at a given time
+    fn viewport_at(&self, time: Flt) -> Viewport {
+        match &self.animation {
+            None => self.calc_viewport(
+                self.look_from.clone(),
+                &self.look_at,
+                &self.vup,
+                self.vfov,
+                self.defocus_angle,
+                self.focus_dist,
+            ),
+            Some(animation) => self.calc_viewport(
+                animation
+                    .look_from
+                    .as_ref()
+                    .map_or_else(|| self.look_from.clone(), |track| track.value_at(time)),
+                &animation
+                    .look_at
+                    .as_ref()
+                    .map_or_else(|| self.look_at.clone(), |track| track.value_at(time)),
+                &animation
+                    .vup
+                    .as_ref()
+                    .map_or_else(|| self.vup.clone(), |track| track.value_at(time)),
+                animation
+                    .vfov
+                    .as_ref()
+                    .map_or(self.vfov, |track| track.value_at(time)),
+                animation
+                    .defocus_angle
+                    .as_ref()
+                    .map_or(self.defocus_angle, |track| track.value_at(time)),
+                animation
+                    .focus_dist
+                    .as_ref()
+                    .map_or(self.focus_dist, |track| track.value_at(time)),
+            ),
+        }
+    }
+
+    /// Calculates the viewport for a set of view parameters
+    fn calc_viewport(
+        &self,
+        look_from: Point3,
+        look_at: &Point3,
+        vup: &Vec3,
+        vfov: Flt,
+        defocus_angle: Flt,
+        focus_dist: Flt,
+    ) -> Viewport {
         let f_image_width = flt(self.image_width as FltPrim);
         let f_image_height = flt(self.image_height as FltPrim);
 
-        self.pixel_samples_scale = flt(1.0) / flt(self.samples_per_pixel as FltPrim);
-
         // Calculate viewport dimensions
-        let theta = self.vfov.to_radians();
+        let theta = vfov.to_radians();
         let h = (theta / 2.0).tan();
-        let viewport_height = flt(2.0) * h * self.focus_dist;
+        let viewport_height = flt(2.0) * h * focus_dist;
         let viewport_width = viewport_height * (f_image_width / f_image_height);
 
         // Calculate the u,v,w unit basis vectors for the camera coordinate frame.
-        self.w = self.look_at.vec_to(&self.look_from).unit_vector();
-        self.u = self.vup.cross(&self.w).unit_vector();
-        self.v = self.w.cross(&self.u);
+        let w = look_at.vec_to(&look_from).unit_vector();
+        let u = vup.cross(&w).unit_vector();
+        let v = w.cross(&u);
 
         // Calculate the vectors across the horizontal and down the vertical viewport edges
-        let viewport_u = viewport_width * &self.u;
-        let viewport_v = viewport_height * -&self.v;
+        let viewport_u = viewport_width * &u;
+        let viewport_v = viewport_height * -&v;
 
         // Calculate the horizontal and vertical delta vectors from pixel to pixel
-        self.pixel_delta_u = &viewport_u / f_image_width;
-        self.pixel_delta_v = &viewport_v / f_image_height;
+        let pixel_delta_u = &viewport_u / f_image_width;
+        let pixel_delta_v = &viewport_v / f_image_height;
 
         // Calculate the location of the upper left pixel
-        let viewport_upper_left = &self.look_from
-            - (self.focus_dist * &self.w)
-            - viewport_u / flt(2.0)
-            - viewport_v / flt(2.0);
-        self.pixel00_loc =
-            viewport_upper_left + flt(0.5) * (&self.pixel_delta_u + &self.pixel_delta_v);
+        let viewport_upper_left =
+            &look_from - (focus_dist * &w) - viewport_u / flt(2.0) - viewport_v / flt(2.0);
+        let pixel00_loc = viewport_upper_left + flt(0.5) * (&pixel_delta_u + &pixel_delta_v);
 
         // Calculate the camera defocus disk basis vectors
-        let defocus_radius = self.focus_dist * (self.defocus_angle / 2.0).to_radians().tan();
-        self.defocus_disk_u = &self.u * defocus_radius;
-        self.defocus_disk_v = &self.v * defocus_radius;
+        let defocus_radius = focus_dist * (defocus_angle / 2.0).to_radians().tan();
+
+        Viewport {
+            look_from,
+            pixel00_loc,
+            pixel_delta_u,
+            pixel_delta_v,
+            defocus_angle,
+            defocus_disk_u: &u * defocus_radius,
+            defocus_disk_v: &v * defocus_radius,
+        }
+    }
+
+    /// Returns a ray from the centre of the lens through a point on the image (in pixels from
+    /// the top left corner) at the time the shutter opens
+    pub fn image_ray(&self, x: FltPrim, y: FltPrim) -> Ray {
+        let viewport = &self.viewport;
+
+        let point = &viewport.pixel00_loc
+            + (flt(x - 0.5) * &viewport.pixel_delta_u)
+            + (flt(y - 0.5) * &viewport.pixel_delta_v);
+
+        Ray::new(
+            viewport.look_from.clone(),
+            viewport.look_from.vec_to(&point),
+            self.shutter_open,
+        )
     }
 
     /// Construct a camera ray originating from the defocus disk and directed
     /// at a randomly sampled point around the pixel location i, j
     fn get_ray(&self, i: u64, j: u64, rng: &mut ThreadRng) -> Ray {
+        // Ray time
+        let time = if self.shutter_close > self.shutter_open {
+            flt(rng.gen_range(flt_prim(self.shutter_open)..flt_prim(self.shutter_close)))
+        } else {
+            self.shutter_open
+        };
+
+        // Animated view parameters are evaluated at the ray time
+        let animated;
+        let viewport = if self.animated {
+            animated = self.viewport_at(time);
+            &animated
+        } else {
+            &self.viewport
+        };
+
         // Calculate random offset in the pixel square
         let offset = self.sample_square(rng);
 
         // Calculate the point in the viewport to sample
-        let pixel_sample = &self.pixel00_loc
-            + ((flt(i as FltPrim) + offset.x()) * &self.pixel_delta_u)
-            + ((flt(j as FltPrim) + offset.y()) * &self.pixel_delta_v);
+        let pixel_sample = &viewport.pixel00_loc
+            + ((flt(i as FltPrim) + offset.x()) * &viewport.pixel_delta_u)
+            + ((flt(j as FltPrim) + offset.y()) * &viewport.pixel_delta_v);
 
         // Ray origin
-        let ray_origin = if self.defocus_angle <= 0.0 {
-            self.look_from.clone()
+        let ray_origin = if viewport.defocus_angle <= 0.0 {
+            viewport.look_from.clone()
         } else {
-            self.defocus_disk_sample(rng)
+            self.defocus_disk_sample(rng, viewport)
         };
 
         // Ray direction
         let ray_direction = ray_origin.vec_to(&pixel_sample);
 
-        // Ray time
-        let time = flt(if self.time_span > 0.0 {
-            rng.gen_range(0.0..(self.time_span.into()))
-        } else {
-            0.0
-        });
-
         Ray::new(ray_origin, ray_direction, time)
     }
 
@@ -337,9 +753,128 @@ impl Camera {
     }
 
     /// Returns a random point in the camera defocus disk
-    fn defocus_disk_sample(&self, rng: &mut ThreadRng) -> Point3 {
+    fn defocus_disk_sample(&self, rng: &mut ThreadRng, viewport: &Viewport) -> Point3 {
         let p = Vec3::new_random_in_unit_disk(rng);
-        &self.look_from + ((p.x() * &self.defocus_disk_u) + (p.y() * &self.defocus_disk_v))
+        &viewport.look_from
+            + ((p.x() * &viewport.defocus_disk_u) + (p.y() * &viewport.defocus_disk_v))
+    }
+
+    /// Returns the colour of a camera ray, tracing a set of wavelengths if spectral
+    fn sample_colour(
+        &self,
+        rng: &mut ThreadRng,
+        mut ray: Ray,
+        world: &HittableList,
+        ambience: &dyn Ambience,
+    ) -> Colour {
+        if self.shading != Shading::Full {
+            self.preview_colour(rng, &ray, world, ambience)
+        } else if self.spectral == 0 {
+            Self::ray_colour(rng, &ray, world, ambience, self.max_depth)
+        } else {
+            let wavelengths = Wavelengths::new_random(rng, self.spectral);
+            ray.set_wavelengths(Some(wavelengths.clone()));
+
+            let values = Self::ray_spectrum(rng, &ray, world, ambience, self.max_depth);
+
+            spectrum_to_rgb(&values, &wavelengths)
+        }
+    }
+
+    /// Returns the colour of a camera ray for a cheap shading mode, from the first hit only
+    fn preview_colour(
+        &self,
+        rng: &mut ThreadRng,
+        ray: &Ray,
+        world: &HittableList,
+        ambience: &dyn Ambience,
+    ) -> Colour {
+        match world.hit(rng, ray, flt(T_MIN)..flt_max()) {
+            None if self.shading == Shading::Depth => Colour::default(),
+            None => ambience.value(ray),
+            Some(hit) => match self.shading {
+                Shading::Normals => Colour::new_from_array(
+                    [0, 1, 2].map(|axis| flt(0.5) * (hit.normal[axis] + flt(1.0))),
+                ),
+                Shading::Depth => Colour::new_from_array([hit.t * ray.direction().length(); 3]),
+                Shading::Albedo | Shading::Full => {
+                    let (mut albedo, emitted, _) = hit.material.scatter(rng, ray, &hit);
+
+                    if let Some(emitted) = emitted {
+                        albedo += emitted
+                    }
+
+                    albedo
+                }
+            },
+        }
+    }
+
+    /// Returns the spectral values of a given ray at the wavelengths it carries
+    fn ray_spectrum(
+        rng: &mut ThreadRng,
+        ray: &Ray,
+        world: &HittableList,
+        ambience: &dyn Ambience,
+        max_depth: u64,
+    ) -> SampledSpectrum {
+        let cur_depth = ray.depth();
+
+        if cur_depth >= max_depth {
+            // Reached maximum ray bounces - return black
+            return [flt(0.0); MAX_WAVELENGTHS];
+        }
+
+        let wavelengths = ray.wavelengths().expect("No wavelengths on spectral ray");
+
+        match world.hit(rng, ray, flt(T_MIN)..flt_max()) {
+            None => {
+                // Ray hit nothing - return background colour
+                upsample(&ambience.value(ray), wavelengths)
+            }
+            Some(hit) => {
+                // Ray hit an object
+                let (attenuation, emitted, next_ray) = hit.material.scatter(rng, ray, &hit);
+
+                let mut result = upsample(&attenuation, wavelengths);
+
+                // Is there a next ray?
+                if let Some(mut next_ray) = next_ray {
+                    // Carry the wavelengths on unless the material has terminated some
+                    if next_ray.wavelengths().is_none() {
+                        next_ray.set_wavelengths(Some(wavelengths.clone()));
+                    }
+
+                    next_ray.set_depth(cur_depth + 1);
+
+                    let next_count = next_ray.wavelengths().map_or(0, |w| w.count());
+                    let next = Self::ray_spectrum(rng, &next_ray, world, ambience, max_depth);
+
+                    for (i, (r, n)) in result.iter_mut().zip(next).enumerate() {
+                        if i < next_count {
+                            *r *= n;
+                        } else {
+                            *r = flt(0.0);
+                        }
+                    }
+
+                    // Terminated wavelengths are accounted for by weighting the hero
+                    if next_count < wavelengths.count() {
+                        result[0] *= flt(wavelengths.count() as FltPrim / next_count as FltPrim);
+                    }
+                }
+
+                // Any colour emitted?
+                if let Some(emitted) = emitted {
+                    // Yes - add it on
+                    for (r, e) in result.iter_mut().zip(upsample(&emitted, wavelengths)) {
+                        *r += e;
+                    }
+                }
+
+                result
+            }
+        }
     }
 
     /// Returns the colour of a geven ray
@@ -386,3 +921,38 @@ impl Camera {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        ambient::ambient_light::AmbientLight, materials::lambertian::Lambertian,
+        shapes::sphere::Sphere,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_noise_target_sample_cap() {
+        let material = Lambertian::new_with_colour(Colour::new(0.5, 0.5, 0.5));
+
+        let mut world = HittableList::new();
+        world.add(Sphere::new(Point3::new(0.0, 0.0, 0.0), 1.0, &material));
+
+        let ambience = AmbientLight::new(Colour::new(1.0, 1.0, 1.0));
+
+        let mut cam = Camera::new(8, 1.0, 20, 10);
+        cam.set_view(
+            Point3::new(0.0, 0.0, 5.0),
+            Point3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        );
+
+        // The sphere's pixels can't reach this target so stop at the samples per pixel
+        cam.set_noise_target(1e-12);
+
+        let (_, stats) = cam.render_progressive(&world, &ambience, None);
+
+        assert_eq!(stats.max_samples, 20);
+        assert!(stats.converged < 1.0);
+    }
+}
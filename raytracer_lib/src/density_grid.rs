@@ -0,0 +1,158 @@
+//! 3D density grid for heterogeneous media
+
+use std::{fs, path::Path};
+
+use crate::{files::find_file, float::*, perlin::PerlinNoise, triple::Point3};
+
+/// Grid of density values covering the unit cube, sampled with trilinear interpolation
+#[derive(Debug)]
+pub struct DensityGrid {
+    dims: [usize; 3],
+    data: Vec<f32>,
+    max: Flt,
+}
+
+impl DensityGrid {
+    /// Creates a new density grid from values with x varying fastest, then y, then z
+    pub fn new(dims: [usize; 3], data: Vec<f32>) -> Self {
+        assert!(
+            dims.iter().all(|&d| d >= 2),
+            "Density grid must have at least two points on each axis"
+        );
+        assert_eq!(
+            data.len(),
+            dims[0] * dims[1] * dims[2],
+            "Density grid data does not match dimensions"
+        );
+
+        let max = data.iter().fold(0.0f32, |max, &d| max.max(d));
+
+        Self {
+            dims,
+            data,
+            max: flt(max as FltPrim),
+        }
+    }
+
+    /// Creates a new density grid from a raw file of little endian 32 bit floats with x varying
+    /// fastest, then y, then z
+    pub fn new_from_file(file: &Path, dims: [usize; 3]) -> Self {
+        let file_path = find_file(file, "volumes");
+
+        let bytes = fs::read(file_path).expect("Unable to read density grid");
+
+        let data = bytes
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+
+        Self::new(dims, data)
+    }
+
+    /// Creates a new density grid from perlin noise turbulence
+    pub fn new_from_turbulence(dims: [usize; 3], scale: FltPrim, depth: usize) -> Self {
+        let perlin = PerlinNoise::new();
+
+        Self::new_from_fn(dims, |p| {
+            flt_prim(perlin.turbulence(&(flt(scale) * p), depth)) as f32
+        })
+    }
+
+    /// Creates a new density grid by evaluating a function at each grid point (unit cube coordinates)
+    pub fn new_from_fn(dims: [usize; 3], f: impl Fn(&Point3) -> f32) -> Self {
+        let mut data = Vec::with_capacity(dims[0] * dims[1] * dims[2]);
+
+        for z in 0..dims[2] {
+            for y in 0..dims[1] {
+                for x in 0..dims[0] {
+                    let p = Point3::new(
+                        x as FltPrim / (dims[0] - 1) as FltPrim,
+                        y as FltPrim / (dims[1] - 1) as FltPrim,
+                        z as FltPrim / (dims[2] - 1) as FltPrim,
+                    );
+
+                    data.push(f(&p).max(0.0));
+                }
+            }
+        }
+
+        Self::new(dims, data)
+    }
+
+    /// Returns the grid dimensions
+    pub fn dims(&self) -> [usize; 3] {
+        self.dims
+    }
+
+    /// Returns the maximum density in the grid
+    pub fn max(&self) -> Flt {
+        self.max
+    }
+
+    /// Returns the interpolated density at a point in the unit cube (zero outside)
+    pub fn density(&self, p: &Point3) -> Flt {
+        let coords = [p.x(), p.y(), p.z()];
+
+        if coords.iter().any(|&c| !(0.0..=1.0).contains(&flt_prim(c))) {
+            return flt(0.0);
+        }
+
+        // Grid cell and position within it on each axis
+        let mut cell = [0usize; 3];
+        let mut frac = [flt(0.0); 3];
+
+        for axis in 0..3 {
+            let pos = coords[axis] * flt((self.dims[axis] - 1) as FltPrim);
+            let i = (flt_prim(pos.floor()) as usize).min(self.dims[axis] - 2);
+
+            cell[axis] = i;
+            frac[axis] = pos - flt(i as FltPrim);
+        }
+
+        let mut result = flt(0.0);
+
+        for dz in 0..2 {
+            for dy in 0..2 {
+                for dx in 0..2 {
+                    let weight = Self::weight(frac[0], dx)
+                        * Self::weight(frac[1], dy)
+                        * Self::weight(frac[2], dz);
+
+                    result += weight * self.value(cell[0] + dx, cell[1] + dy, cell[2] + dz);
+                }
+            }
+        }
+
+        result
+    }
+
+    fn value(&self, x: usize, y: usize, z: usize) -> Flt {
+        flt(self.data[(z * self.dims[1] + y) * self.dims[0] + x] as FltPrim)
+    }
+
+    fn weight(frac: Flt, offset: usize) -> Flt {
+        if offset == 0 {
+            flt(1.0) - frac
+        } else {
+            frac
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trilinear() {
+        // Density equal to x + 2y + 4z at the corners
+        let grid = DensityGrid::new([2, 2, 2], vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0]);
+
+        assert_eq!(grid.max(), 7.0);
+        assert_eq!(grid.density(&Point3::new(0.0, 0.0, 0.0)), 0.0);
+        assert_eq!(grid.density(&Point3::new(1.0, 1.0, 1.0)), 7.0);
+        assert_eq!(grid.density(&Point3::new(0.5, 0.5, 0.5)), 3.5);
+        assert_eq!(grid.density(&Point3::new(0.25, 0.5, 0.0)), 1.25);
+        assert_eq!(grid.density(&Point3::new(1.5, 0.5, 0.5)), 0.0);
+    }
+}
@@ -0,0 +1,59 @@
+//! Data files loaded by scenes
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+/// Files loaded since the last call to `take_loaded`
+static LOADED: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// Finds a data file to load, records it as loaded and returns its path. A file which doesn't
+/// exist relative to the current directory is looked for in a data directory (eg "images") in
+/// the current directory or any of its parents. Panics if the file can't be found
+pub(crate) fn find_file(file: &Path, data_dir: &str) -> PathBuf {
+    let path = if file.exists() {
+        PathBuf::from(file)
+    } else {
+        find_in_parents(file, data_dir).expect("Unable to find file")
+    };
+
+    record_loaded(&path);
+
+    path
+}
+
+/// Looks for a file in a data directory in the current directory or one of its parents
+fn find_in_parents(file: &Path, data_dir: &str) -> Option<PathBuf> {
+    for parent in 0..8 {
+        let mut buf = PathBuf::new();
+
+        for _ in 0..parent {
+            buf.push("..")
+        }
+
+        buf.push(data_dir);
+        buf.push(file);
+
+        if buf.is_file() {
+            return Some(buf);
+        }
+    }
+
+    None
+}
+
+/// Records a data file loaded while building a scene
+fn record_loaded(path: &Path) {
+    let mut loaded = LOADED.lock().expect("Loaded file list poisoned");
+
+    if !loaded.iter().any(|loaded| loaded == path) {
+        loaded.push(path.to_path_buf());
+    }
+}
+
+/// Returns the data files (images, height maps, density grids) loaded since the last call, so
+/// that they can be watched for changes
+pub fn take_loaded() -> Vec<PathBuf> {
+    std::mem::take(&mut *LOADED.lock().expect("Loaded file list poisoned"))
+}
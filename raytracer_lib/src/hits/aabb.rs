@@ -71,6 +71,11 @@ impl Aabb {
 
     /// Tests if the bounding box is hit by a ray
     pub fn hit(&self, ray: &Ray, t_range: &Range<Flt>) -> bool {
+        self.clip(ray, t_range).is_some()
+    }
+
+    /// Returns the part of a ray range that lies inside the bounding box
+    pub fn clip(&self, ray: &Ray, t_range: &Range<Flt>) -> Option<Range<Flt>> {
         let mut start = t_range.start;
         let mut end = t_range.end;
 
@@ -89,11 +94,11 @@ impl Aabb {
             end = end.min(t0.max(t1));
 
             if end <= start {
-                return false;
+                return None;
             }
         }
 
-        true
+        Some(start..end)
     }
 
     /// Returns the longest axis index
@@ -117,7 +122,7 @@ impl Aabb {
 
     fn pad_to_minimums(&mut self) {
         // Adjust the AABB so that no side is narrower than some delta, padding if necessary.
-        for i in 0..2 {
+        for i in 0..3 {
             if self.ranges[i].end - self.ranges[i].start < Self::DELTA {
                 let half = Self::DELTA / 2.0;
 
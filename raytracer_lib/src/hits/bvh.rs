@@ -16,16 +16,40 @@ use super::{hittable::HittableRef, hittable_list::HittableList};
 #[derive(Debug)]
 pub struct BvhNode<'a> {
     bbox: Aabb,
-    left: HittableRef<'a>,
+    /// Left subtree (None if all objects are unbounded)
+    left: Option<HittableRef<'a>>,
     right: Option<HittableRef<'a>>,
+    /// Objects with no bounding box, always tested (root node only)
+    unbounded: Vec<HittableRef<'a>>,
 }
 
 impl<'a> BvhNode<'a> {
-    /// Creates a new BVH from a hittable list
+    /// Creates a new BVH from a hittable list. Unbounded objects are kept out of the tree
     pub fn new(hittable_list: HittableList<'a>) -> Self {
-        let objects = hittable_list.into_objects();
+        let (objects, unbounded): (Vec<_>, Vec<_>) = hittable_list
+            .into_objects()
+            .into_iter()
+            .partition(|o| o.bounded());
 
-        Self::new_from_vec(objects)
+        if objects.is_empty() {
+            // No tree - use the first object's box as the list does
+            return Self {
+                bbox: unbounded
+                    .first()
+                    .expect("No objects for BvhNode")
+                    .bounding_box()
+                    .clone(),
+                left: None,
+                right: None,
+                unbounded,
+            };
+        }
+
+        let mut node = Self::new_from_vec(objects);
+
+        node.unbounded = unbounded;
+
+        node
     }
 
     fn new_from_vec(mut objects: Vec<HittableRef<'a>>) -> Self {
@@ -40,7 +64,7 @@ impl<'a> BvhNode<'a> {
                     Some(next.clone())
                 }
             })
-            .expect("No objects for BvhNode");
+            .expect("No bounded objects for BvhNode");
 
         // Calculate longest axis
         let axis = bbox.longest_axis();
@@ -51,12 +75,12 @@ impl<'a> BvhNode<'a> {
             0 => panic!("Zero length hittable vec"),
             1 => {
                 let object = objects.pop().unwrap();
-                (object, None)
+                (Some(object), None)
             }
             2 => {
                 let object1 = objects.pop().unwrap();
                 let object0 = objects.pop().unwrap();
-                (object0, Some(object1))
+                (Some(object0), Some(object1))
             }
             _ => {
                 // Sort objects in to order by start point on the chosen axis
@@ -69,13 +93,18 @@ impl<'a> BvhNode<'a> {
                 let split = objects.split_off(mid);
 
                 (
-                    HittableRef::boxed(BvhNode::new_from_vec(objects)),
+                    Some(HittableRef::boxed(BvhNode::new_from_vec(objects))),
                     Some(HittableRef::boxed(BvhNode::new_from_vec(split))),
                 )
             }
         };
 
-        Self { bbox, left, right }
+        Self {
+            bbox,
+            left,
+            right,
+            unbounded: Vec::new(),
+        }
     }
 
     fn box_compare(a: &dyn Hittable<'a>, b: &dyn Hittable<'a>, axis: usize) -> Ordering {
@@ -87,17 +116,19 @@ impl<'a> BvhNode<'a> {
             .partial_cmp(&b_axis_interval.start)
             .expect("Invalid float in sort")
     }
-}
 
-impl<'a> Hittable<'a> for BvhNode<'a> {
-    fn hit(&self, rng: &mut ThreadRng, ray: &Ray, t_range: Range<Flt>) -> Option<Hit> {
+    /// Tests the bounded objects in the tree
+    fn hit_tree(&self, rng: &mut ThreadRng, ray: &Ray, t_range: Range<Flt>) -> Option<Hit> {
+        // Any bounded objects?
+        let left = self.left.as_ref()?;
+
         // Any hit at all?
         if !self.bbox.hit(ray, &t_range) {
             return None;
         }
 
         // Check for left hit
-        match self.left.hit(rng, ray, t_range.clone()) {
+        match left.hit(rng, ray, t_range.clone()) {
             None => {
                 // No left hit - check right
                 if let Some(right) = &self.right {
@@ -121,8 +152,128 @@ impl<'a> Hittable<'a> for BvhNode<'a> {
             }
         }
     }
+}
+
+impl<'a> Hittable<'a> for BvhNode<'a> {
+    fn hit(&self, rng: &mut ThreadRng, ray: &Ray, t_range: Range<Flt>) -> Option<Hit> {
+        if !self.unbounded.is_empty() {
+            // Test the unbounded objects first and narrow the range to the closest hit
+            let mut closest = t_range.end;
+            let mut closest_hit = None;
+
+            for obj in &self.unbounded {
+                if let Some(hit) = obj.hit(rng, ray, t_range.start..closest) {
+                    closest = hit.t;
+                    closest_hit = Some(hit);
+                }
+            }
+
+            return match self.hit_tree(rng, ray, t_range.start..closest) {
+                None => closest_hit,
+                hit => hit,
+            };
+        }
+
+        self.hit_tree(rng, ray, t_range)
+    }
 
     fn bounding_box(&self) -> &Aabb {
         &self.bbox
     }
+
+    fn bounded(&self) -> bool {
+        self.unbounded.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::thread_rng;
+
+    use crate::{
+        hits::hittable::T_MIN,
+        materials::lambertian::Lambertian,
+        shapes::{plane::Plane, sphere::Sphere},
+        triple::{Colour, Point3, Vec3},
+    };
+
+    use super::*;
+
+    fn ray_down() -> Ray {
+        Ray::new(
+            Point3::new(0.0, 10.0, 0.0),
+            Vec3::new(0.0, -1.0, 0.0),
+            flt(0.0),
+        )
+    }
+
+    #[test]
+    fn test_unbounded_only() {
+        let material = Lambertian::new_with_colour(Colour::new(0.5, 0.5, 0.5));
+
+        let mut world = HittableList::new();
+        world.add(Plane::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            &material,
+        ));
+
+        let bvh = BvhNode::new(world);
+        assert!(!bvh.bounded());
+
+        let hit = bvh.hit(&mut thread_rng(), &ray_down(), flt(T_MIN)..flt_max());
+        assert_eq!(hit.map(|hit| hit.t), Some(flt(10.0)));
+    }
+
+    #[test]
+    fn test_bounded_and_unbounded() {
+        let material = Lambertian::new_with_colour(Colour::new(0.5, 0.5, 0.5));
+
+        let mut world = HittableList::new();
+        world.add(Plane::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            &material,
+        ));
+        world.add(Sphere::new(Point3::new(0.0, 2.0, 0.0), 1.0, &material));
+        world.add(Sphere::new(Point3::new(5.0, 2.0, 0.0), 1.0, &material));
+
+        let bvh = BvhNode::new(world);
+
+        // Sphere in front of the plane
+        let hit = bvh.hit(&mut thread_rng(), &ray_down(), flt(T_MIN)..flt_max());
+        assert_eq!(hit.map(|hit| hit.t), Some(flt(7.0)));
+
+        // Plane in front of the sphere
+        let hit = bvh.hit(&mut thread_rng(), &ray_down(), flt(9.5)..flt_max());
+        assert_eq!(hit.map(|hit| hit.t), Some(flt(10.0)));
+    }
+
+    #[test]
+    fn test_leaf_names() {
+        let material = Lambertian::new_with_colour(Colour::new(0.5, 0.5, 0.5));
+
+        let mut world = HittableList::new();
+        world.add(Plane::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            &material,
+        ));
+        world.add(Sphere::new(Point3::new(0.0, 2.0, 0.0), 1.0, &material));
+
+        let bvh = BvhNode::new(world);
+        assert_eq!(bvh.name(), "BvhNode");
+
+        // Names come from the leaf hit, not the tree
+        let hit = bvh
+            .hit(&mut thread_rng(), &ray_down(), flt(T_MIN)..flt_max())
+            .expect("No hit");
+        assert_eq!(hit.object, "Sphere");
+        assert_eq!(hit.material.name(), "Lambertian");
+
+        let hit = bvh
+            .hit(&mut thread_rng(), &ray_down(), flt(9.5)..flt_max())
+            .expect("No hit");
+        assert_eq!(hit.object, "Plane");
+    }
 }
@@ -8,10 +8,13 @@ use crate::{
 };
 
 /// Intersection details
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Hit<'a> {
     /// The point of intersecton
     pub p: Point3,
+    /// The point of intersection in the object's own space, as passed to `Material::hit`.
+    /// This is not changed by transforms
+    pub object_p: Point3,
     /// The normal vector at point of intersection
     pub normal: Vec3,
     /// The distance to the intersection
@@ -22,12 +25,19 @@ pub struct Hit<'a> {
     pub v: Flt,
     /// Object front face hit
     pub front_face: bool,
+    /// Surface tangent in the direction of increasing u (zero if not provided by the shape)
+    pub dpdu: Vec3,
+    /// Surface tangent in the direction of increasing v (zero if not provided by the shape)
+    pub dpdv: Vec3,
     /// The material of the object at intersection
     pub material: &'a dyn Material,
+    /// The type name of the object hit
+    pub object: &'static str,
 }
 
 impl<'a> Hit<'a> {
     /// Creates a new hit detail object
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         p: Point3,
         t: Flt,
@@ -36,6 +46,7 @@ impl<'a> Hit<'a> {
         ray: &Ray,
         outward_normal: &Vec3,
         material: &'a dyn Material,
+        object: &'static str,
     ) -> Self {
         let front_face = ray.direction().dot(outward_normal) < 0.0;
 
@@ -46,13 +57,58 @@ impl<'a> Hit<'a> {
         };
 
         Self {
+            object_p: p.clone(),
             p,
             normal,
             t,
             u,
             v,
             front_face,
+            dpdu: Vec3::default(),
+            dpdv: Vec3::default(),
             material,
+            object,
         }
     }
+
+    /// Sets the surface tangents at the point of intersection
+    pub fn set_tangents(&mut self, dpdu: Vec3, dpdv: Vec3) {
+        self.dpdu = dpdu;
+        self.dpdv = dpdv;
+    }
+
+    /// Returns the outward facing normal at the point of intersection
+    pub fn outward_normal(&self) -> Vec3 {
+        if self.front_face {
+            self.normal.clone()
+        } else {
+            -&self.normal
+        }
+    }
+
+    /// Returns an orthonormal tangent and bitangent for the outward normal, derived from
+    /// the surface tangents if available or an arbitrary basis otherwise
+    pub fn tangent_frame(&self) -> (Vec3, Vec3) {
+        let normal = self.outward_normal();
+
+        // Project dpdu on to the tangent plane
+        let tangent = &self.dpdu - normal.dot(&self.dpdu) * &normal;
+
+        let tangent = if tangent.near_zero() {
+            // Pick an axis not parallel to the normal
+            let axis = if normal.x().abs() > 0.9 {
+                Vec3::new(0.0, 1.0, 0.0)
+            } else {
+                Vec3::new(1.0, 0.0, 0.0)
+            };
+
+            axis.cross(&normal).unit_vector()
+        } else {
+            tangent.unit_vector()
+        };
+
+        let bitangent = normal.cross(&tangent);
+
+        (tangent, bitangent)
+    }
 }
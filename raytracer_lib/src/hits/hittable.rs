@@ -1,8 +1,10 @@
 //! Hittable trait
 
 use std::{
+    any,
     fmt::Debug,
     ops::{Deref, Range},
+    sync::Arc,
 };
 
 use rand::rngs::ThreadRng;
@@ -20,17 +22,65 @@ pub trait Hittable<'a>: Debug + Send + Sync {
     /// Tests whether the object intersects a given ray
     fn hit(&self, rng: &mut ThreadRng, ray: &Ray, t_range: Range<Flt>) -> Option<Hit>;
 
+    /// Returns all intersections with a given ray in order of distance. For closed objects the
+    /// front face flag of each hit shows whether the ray is entering or leaving the object
+    fn hits(&self, rng: &mut ThreadRng, ray: &Ray, t_range: Range<Flt>) -> Vec<Hit> {
+        let mut hits = Vec::new();
+        let mut start = t_range.start;
+
+        // Repeatedly look for the next hit beyond the last one
+        while let Some(hit) = self.hit(rng, ray, start..t_range.end) {
+            // Step relative to the distance so the step isn't lost to rounding when far away
+            let next = hit.t + flt(T_MIN) * hit.t.abs().max(flt(1.0));
+
+            hits.push(hit);
+
+            if next <= start {
+                break;
+            }
+
+            start = next;
+        }
+
+        hits
+    }
+
     /// Returns the bounding box of the object
     fn bounding_box(&self) -> &Aabb;
+
+    /// Returns false if the object has no finite extent (eg an infinite plane), in which case
+    /// the bounding box should not be used to cull rays
+    fn bounded(&self) -> bool {
+        true
+    }
+
+    /// Returns the name of the object type
+    fn name(&self) -> &'static str {
+        type_name::<Self>()
+    }
+}
+
+/// Returns the name of a type without its module path or generic parameters
+pub fn type_name<T: ?Sized>() -> &'static str {
+    let name = any::type_name::<T>();
+    let name = name.split('<').next().unwrap_or(name);
+
+    name.rsplit("::").next().unwrap_or(name)
 }
 
-/// Reference to a hittable object, either borrowed or owned
+/// Hittable object reference with no borrows (owned, shared or 'static), which can be
+/// returned from functions and sent to other threads
+pub type OwnedHittableRef = HittableRef<'static>;
+
+/// Reference to a hittable object, either borrowed, owned or shared
 #[derive(Debug)]
 pub enum HittableRef<'a> {
     /// A borrowed object
     Borrow(&'a dyn Hittable<'a>),
     /// An owned object
     Box(Box<dyn Hittable<'a> + 'a>),
+    /// A shared object
+    Arc(Arc<dyn Hittable<'a> + 'a>),
 }
 
 impl<'a> HittableRef<'a> {
@@ -39,11 +89,34 @@ impl<'a> HittableRef<'a> {
         Self::Box(Box::new(hittable))
     }
 
+    /// Creates a new shared hittable reference
+    pub fn shared(hittable: impl Hittable<'a> + 'a) -> Self {
+        Self::Arc(Arc::new(hittable))
+    }
+
+    /// Converts an owned hittable reference to a shared one so that it can be shared
+    pub fn into_shared(self) -> Self {
+        match self {
+            Self::Box(boxed) => Self::Arc(Arc::from(boxed)),
+            other => other,
+        }
+    }
+
+    /// Creates another reference to the same object if it is borrowed or shared
+    pub fn share(&self) -> Option<Self> {
+        match self {
+            Self::Borrow(refer) => Some(Self::Borrow(*refer)),
+            Self::Box(_) => None,
+            Self::Arc(shared) => Some(Self::Arc(shared.clone())),
+        }
+    }
+
     /// Gets a reference to the hittable
     pub fn get_ref(&'a self) -> &dyn Hittable {
         match self {
             Self::Borrow(refer) => *refer,
             Self::Box(boxed) => boxed.as_ref(),
+            Self::Arc(shared) => shared.as_ref(),
         }
     }
 }
@@ -55,6 +128,7 @@ impl<'a> Deref for HittableRef<'a> {
         match self {
             HittableRef::Borrow(refer) => *refer,
             HittableRef::Box(boxed) => boxed.as_ref(),
+            HittableRef::Arc(shared) => shared.as_ref(),
         }
     }
 }
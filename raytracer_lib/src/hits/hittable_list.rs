@@ -17,14 +17,20 @@ use super::hittable::HittableRef;
 pub struct HittableList<'a> {
     objects: Vec<HittableRef<'a>>,
     bbox: Option<Aabb>,
+    unbounded: bool,
 }
 
+/// Hittable object list with no borrowed objects, which can be returned from functions and
+/// sent to other threads
+pub type OwnedHittableList = HittableList<'static>;
+
 impl<'a> HittableList<'a> {
     /// Create a new empty hittable list
     pub fn new() -> Self {
         Self {
             objects: Vec::new(),
             bbox: None,
+            unbounded: false,
         }
     }
 
@@ -32,6 +38,7 @@ impl<'a> HittableList<'a> {
     pub fn clear(&mut self) {
         self.objects.clear();
         self.bbox = None;
+        self.unbounded = false;
     }
 
     /// Adds an object to the hittable list
@@ -39,11 +46,16 @@ impl<'a> HittableList<'a> {
     where
         T: Hittable<'a> + 'a,
     {
-        self.bbox = Some(if let Some(bbox) = &self.bbox {
-            Aabb::new_from_bbox(bbox, hittable.bounding_box())
+        // Unbounded objects don't contribute to the bounding box
+        if hittable.bounded() {
+            self.bbox = Some(if let Some(bbox) = &self.bbox {
+                Aabb::new_from_bbox(bbox, hittable.bounding_box())
+            } else {
+                hittable.bounding_box().clone()
+            });
         } else {
-            hittable.bounding_box().clone()
-        });
+            self.unbounded = true;
+        }
 
         self.objects.push(HittableRef::boxed(hittable));
     }
@@ -57,6 +69,7 @@ impl<'a> HittableList<'a> {
     pub fn into_objects(mut self) -> Vec<HittableRef<'a>> {
         let vec = mem::take(&mut self.objects);
         self.bbox = None;
+        self.unbounded = false;
         vec
     }
 }
@@ -80,6 +93,85 @@ impl<'a> Hittable<'a> for HittableList<'a> {
     }
 
     fn bounding_box(&self) -> &Aabb {
-        self.bbox.as_ref().expect("No objects in hittable list")
+        // Fall back to the first object's box if all objects are unbounded
+        self.bbox
+            .as_ref()
+            .or_else(|| self.objects.first().map(|o| o.bounding_box()))
+            .expect("No objects in hittable list")
+    }
+
+    fn bounded(&self) -> bool {
+        !self.unbounded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use rand::thread_rng;
+
+    use crate::{
+        hits::{bvh::BvhNode, hittable::T_MIN},
+        materials::{
+            lambertian::Lambertian,
+            material::{MatRef, OwnedMatRef},
+        },
+        shapes::sphere::Sphere,
+        textures::{
+            checker::Checker,
+            solid::Solid,
+            texture::{OwnedTexRef, TexRef},
+        },
+        triple::{Colour, Point3, Vec3},
+    };
+
+    use super::*;
+
+    /// Builds a scene in a function with shared materials and textures
+    fn build_scene() -> OwnedHittableList {
+        let texture: OwnedTexRef = TexRef::shared(Checker::new_with_texref(
+            1.0,
+            TexRef::boxed(Solid::new(Colour::new(0.2, 0.2, 0.2))),
+            TexRef::boxed(Solid::new(Colour::new(0.8, 0.8, 0.8))),
+        ));
+
+        let material: OwnedMatRef = MatRef::shared(Lambertian::new_with_texref(texture));
+
+        let mut world = HittableList::new();
+
+        for x in 0..4 {
+            world.add(Sphere::new_with_matref(
+                Point3::new(x as FltPrim * 3.0, 0.0, 0.0),
+                1.0,
+                material.share().expect("Material not shared"),
+            ));
+        }
+
+        let mut scene = HittableList::new();
+        scene.add(BvhNode::new(world));
+
+        scene
+    }
+
+    #[test]
+    fn test_owned_scene() {
+        let scene = build_scene();
+
+        let hit_t = thread::spawn(move || {
+            let ray = Ray::new(
+                Point3::new(6.0, 10.0, 0.0),
+                Vec3::new(0.0, -1.0, 0.0),
+                flt(0.0),
+            );
+
+            scene
+                .hit(&mut thread_rng(), &ray, flt(T_MIN)..flt_max())
+                .map(|hit| hit.t)
+        })
+        .join()
+        .expect("Render thread panicked");
+
+        assert_eq!(hit_t, Some(flt(9.0)));
     }
 }
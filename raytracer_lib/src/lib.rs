@@ -3,14 +3,19 @@
 #![warn(missing_docs)]
 
 pub mod ambient;
+pub mod animation;
 pub mod camera;
+pub mod density_grid;
+pub mod files;
 pub mod float;
 pub mod gamma;
 pub mod hits;
 pub mod materials;
 pub mod perlin;
+pub mod poly;
 pub mod ray;
 pub mod shapes;
+pub mod spectrum;
 pub mod textures;
 pub mod transforms;
 pub mod triple;
@@ -0,0 +1,141 @@
+//! Bump and normal mapping wrapper material
+
+use rand::rngs::ThreadRng;
+
+use crate::{
+    float::*,
+    hits::hit::Hit,
+    ray::Ray,
+    textures::texture::{TexRef, Texture},
+    triple::{Point3, Vec3},
+};
+
+use super::material::{MatRef, Material, Scattered};
+
+/// Texture coordinate step used to estimate height map derivatives
+const DELTA: FltPrim = 0.001;
+
+/// Interpretation of the bump texture
+#[derive(Debug, Clone)]
+pub enum BumpMap {
+    /// Scalar height map scaled by a given factor
+    Height(Flt),
+    /// Tangent space normal map (rgb 0..1 mapped to xyz -1..1)
+    Normal,
+}
+
+/// Bump material details
+#[derive(Debug)]
+pub struct Bump<'a> {
+    /// How to interpret the texture
+    map: BumpMap,
+    /// Height or normal texture
+    texture: TexRef<'a>,
+    /// Material to delegate to with the perturbed normal
+    inner: MatRef<'a>,
+}
+
+impl<'a> Bump<'a> {
+    /// Create a new bump mapped material from a height texture
+    pub fn new_with_height_texture(
+        scale: FltPrim,
+        texture: &'a dyn Texture,
+        inner: &'a dyn Material,
+    ) -> Self {
+        Self::new_with_texref(
+            BumpMap::Height(flt(scale)),
+            TexRef::Borrow(texture),
+            MatRef::Borrow(inner),
+        )
+    }
+
+    /// Create a new normal mapped material from a tangent space normal texture
+    pub fn new_with_normal_texture(texture: &'a dyn Texture, inner: &'a dyn Material) -> Self {
+        Self::new_with_texref(
+            BumpMap::Normal,
+            TexRef::Borrow(texture),
+            MatRef::Borrow(inner),
+        )
+    }
+
+    /// Create a new bump or normal mapped material with texture and material references
+    pub fn new_with_texref(map: BumpMap, texture: TexRef<'a>, inner: MatRef<'a>) -> Self {
+        Self {
+            map,
+            texture,
+            inner,
+        }
+    }
+
+    /// Calculates the perturbed outward normal at the hit point
+    fn shading_normal(&self, hit: &Hit) -> Vec3 {
+        let normal = hit.outward_normal();
+        let (tangent, bitangent) = hit.tangent_frame();
+
+        match &self.map {
+            BumpMap::Height(scale) => {
+                // Fall back to the tangent frame if the shape does not provide derivatives
+                let (dpdu, dpdv) = if hit.dpdu.near_zero() || hit.dpdv.near_zero() {
+                    (tangent, bitangent)
+                } else {
+                    (hit.dpdu.clone(), hit.dpdv.clone())
+                };
+
+                let delta = flt(DELTA);
+
+                // Estimate the height derivatives by forward differences
+                let height = |u: Flt, v: Flt, p: &Point3| *scale * self.texture.scalar(u, v, p);
+
+                let h = height(hit.u, hit.v, &hit.p);
+                let h_u = height(hit.u + delta, hit.v, &(&hit.p + (&dpdu * delta)));
+                let h_v = height(hit.u, hit.v + delta, &(&hit.p + (&dpdv * delta)));
+
+                let dhdu = (h_u - h) / delta;
+                let dhdv = (h_v - h) / delta;
+
+                // Displaced surface derivatives
+                let dpdu = dpdu + (dhdu * &normal);
+                let dpdv = dpdv + (dhdv * &normal);
+
+                let perturbed = dpdu.cross(&dpdv).unit_vector();
+
+                // Keep on the same side as the geometric normal
+                if perturbed.dot(&normal) < 0.0 {
+                    -perturbed
+                } else {
+                    perturbed
+                }
+            }
+            BumpMap::Normal => {
+                let value = self.texture.value(hit.u, hit.v, &hit.p);
+
+                let x = flt(2.0) * value[0] - flt(1.0);
+                let y = flt(2.0) * value[1] - flt(1.0);
+                let z = flt(2.0) * value[2] - flt(1.0);
+
+                ((x * tangent) + (y * bitangent) + (z * normal)).unit_vector()
+            }
+        }
+    }
+}
+
+impl<'a> Material for Bump<'a> {
+    fn hit(&self, rng: &mut ThreadRng, u: Flt, v: Flt, p: &Point3) -> bool {
+        self.inner.hit(rng, u, v, p)
+    }
+
+    fn scatter(&self, rng: &mut ThreadRng, ray: &Ray, hit: &Hit) -> Scattered {
+        let perturbed = self.shading_normal(hit);
+
+        // Replace the normal, keeping it facing the incoming ray
+        let mut shading_hit = hit.clone();
+
+        shading_hit.normal = if hit.front_face {
+            perturbed
+        } else {
+            -perturbed
+        };
+
+        self.inner.scatter(rng, ray, &shading_hit)
+    }
+}
@@ -0,0 +1,116 @@
+//! Dielectric clearcoat over a base material
+
+use rand::{rngs::ThreadRng, Rng};
+
+use crate::{
+    float::*,
+    hits::hit::Hit,
+    ray::Ray,
+    triple::{Colour, Point3, Vec3},
+};
+
+use super::material::{MatRef, Material, Scattered};
+
+/// Coated material details
+#[derive(Debug)]
+pub struct Coated<'a> {
+    /// The material underneath the coat
+    base: MatRef<'a>,
+    /// Reflectance at normal incidence
+    r0_sq: Flt,
+    /// Roughness of the coat surface
+    roughness: Flt,
+    /// Coat absorption colour, applied on the way in and out
+    tint: Colour,
+}
+
+impl<'a> Coated<'a> {
+    /// Create a new clear coat with a given refractive index over a base material
+    pub fn new(refraction_index: FltPrim, base: &'a dyn Material) -> Self {
+        Self::new_with_matref(refraction_index, MatRef::Borrow(base))
+    }
+
+    /// Create a new clear coat with a given refractive index over a base material reference
+    pub fn new_with_matref(refraction_index: FltPrim, base: MatRef<'a>) -> Self {
+        let refraction_index = flt(refraction_index);
+        let r0 = (flt(1.0) - refraction_index) / (flt(1.0) + refraction_index);
+
+        Self {
+            base,
+            r0_sq: r0 * r0,
+            roughness: flt(0.0),
+            tint: Colour::new_white(),
+        }
+    }
+
+    /// Sets the roughness of the coat surface (0 = mirror, 1 = fully rough)
+    pub fn set_roughness(&mut self, roughness: FltPrim) {
+        self.roughness = flt(roughness.clamp(0.0, 1.0));
+    }
+
+    /// Sets the coat absorption colour
+    pub fn set_tint(&mut self, tint: Colour) {
+        self.tint = tint;
+    }
+
+    fn reflectance(&self, cosine: Flt) -> Flt {
+        // Use Schlick's approximation for reflectance.
+        self.r0_sq + ((flt(1.0) - self.r0_sq) * (flt(1.0) - cosine).powf(flt(5.0)))
+    }
+}
+
+impl<'a> Material for Coated<'a> {
+    fn hit(&self, rng: &mut ThreadRng, u: Flt, v: Flt, p: &Point3) -> bool {
+        self.base.hit(rng, u, v, p)
+    }
+
+    fn scatter(&self, rng: &mut ThreadRng, ray: &Ray, hit: &Hit) -> Scattered {
+        // The coat is only on the outside of the object
+        if !hit.front_face {
+            return self.base.scatter(rng, ray, hit);
+        }
+
+        let unit_direction = ray.direction().unit_vector();
+        let cos_theta = (-unit_direction.dot(&hit.normal)).min(flt(1.0));
+
+        if self.reflectance(cos_theta) > rng.gen_range(0.0..1.0) {
+            // Reflected by the coat
+            let mut reflected = unit_direction.reflect(&hit.normal);
+
+            if self.roughness != 0.0 {
+                reflected = reflected + (self.roughness * Vec3::new_random_unit_vector(rng));
+            }
+
+            if reflected.dot(&hit.normal) > 0.0 {
+                let scattered = Ray::new(hit.p.clone(), reflected, ray.time());
+                (Colour::new_white(), None, Some(scattered))
+            } else {
+                (Colour::default(), None, None)
+            }
+        } else {
+            // Transmitted through the coat to the base material
+            let (attenuation, emitted, scattered) = self.base.scatter(rng, ray, hit);
+
+            // Light leaving the base is partly reflected back in by the coat. The way in is
+            // accounted for by choosing between reflection and transmission above
+            let transmitted = match &scattered {
+                Some(scattered) => {
+                    let cos_out = scattered.direction().unit_vector().dot(&hit.normal);
+
+                    if cos_out > 0.0 {
+                        flt(1.0) - self.reflectance(cos_out.min(flt(1.0)))
+                    } else {
+                        flt(1.0)
+                    }
+                }
+                None => flt(1.0),
+            };
+
+            (
+                attenuation * &self.tint * &self.tint * transmitted,
+                emitted.map(|emitted| emitted * &self.tint),
+                scattered,
+            )
+        }
+    }
+}
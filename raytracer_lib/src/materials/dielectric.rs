@@ -2,10 +2,37 @@
 
 use rand::{rngs::ThreadRng, Rng};
 
-use crate::{float::*, hits::hit::Hit, ray::Ray, triple::Colour};
+use crate::{float::*, hits::hit::Hit, ray::Ray, spectrum::LAMBDA_D, triple::Colour};
 
 use super::material::{Material, Scattered};
 
+/// Variation of refractive index with wavelength
+#[derive(Debug, Clone)]
+pub enum Dispersion {
+    /// Cauchy's equation n = A + B / λ² (λ in micrometres)
+    Cauchy(FltPrim, FltPrim),
+    /// Sellmeier equation n² = 1 + Σ Bᵢλ² / (λ² - Cᵢ) (λ in micrometres, Cᵢ in micrometres²)
+    Sellmeier([FltPrim; 3], [FltPrim; 3]),
+}
+
+impl Dispersion {
+    /// Returns the refractive index at a given wavelength in nanometres
+    pub fn refraction_index(&self, lambda: FltPrim) -> FltPrim {
+        let lambda_um = lambda / 1000.0;
+        let lambda_sq = lambda_um * lambda_um;
+
+        match self {
+            Dispersion::Cauchy(a, b) => a + b / lambda_sq,
+            Dispersion::Sellmeier(b, c) => (1.0
+                + b.iter()
+                    .zip(c.iter())
+                    .map(|(b, c)| b * lambda_sq / (lambda_sq - c))
+                    .sum::<FltPrim>())
+            .sqrt(),
+        }
+    }
+}
+
 /// Dielectric material details
 #[derive(Debug)]
 pub struct Dielectric {
@@ -13,6 +40,7 @@ pub struct Dielectric {
     inv_refraction_index: Flt,
     r0_sq: Flt,
     inv_r0_sq: Flt,
+    dispersion: Option<Dispersion>,
 }
 
 impl Dielectric {
@@ -21,19 +49,40 @@ impl Dielectric {
         let refraction_index = flt(refraction_index);
         let inv_refraction_index = refraction_index.recip();
 
-        let r0_sq = |v| -> Flt {
-            let r0 = (flt(1.0) - v) / (flt(1.0) + v);
-            r0 * r0
-        };
-
         Self {
             refraction_index,
             inv_refraction_index,
-            r0_sq: r0_sq(refraction_index),
-            inv_r0_sq: r0_sq(inv_refraction_index),
+            r0_sq: Self::r0_sq(refraction_index),
+            inv_r0_sq: Self::r0_sq(inv_refraction_index),
+            dispersion: None,
         }
     }
 
+    /// Create a new dispersive dielectric using Cauchy's equation (λ in micrometres)
+    pub fn new_cauchy(a: FltPrim, b: FltPrim) -> Self {
+        Self::new_with_dispersion(Dispersion::Cauchy(a, b))
+    }
+
+    /// Create a new dispersive dielectric using the Sellmeier equation (λ in micrometres)
+    pub fn new_sellmeier(b: [FltPrim; 3], c: [FltPrim; 3]) -> Self {
+        Self::new_with_dispersion(Dispersion::Sellmeier(b, c))
+    }
+
+    /// Create a new dispersive dielectric. The refractive index at the sodium D line is
+    /// used when not rendering spectrally
+    pub fn new_with_dispersion(dispersion: Dispersion) -> Self {
+        let mut result = Self::new(dispersion.refraction_index(LAMBDA_D));
+
+        result.dispersion = Some(dispersion);
+
+        result
+    }
+
+    fn r0_sq(refraction_index: Flt) -> Flt {
+        let r0 = (flt(1.0) - refraction_index) / (flt(1.0) + refraction_index);
+        r0 * r0
+    }
+
     fn reflectance(&self, cosine: Flt, r0_sq: Flt) -> Flt {
         // Use Schlick's approximation for reflectance.
         r0_sq + ((flt(1.0) - r0_sq) * (flt(1.0) - cosine).powf(flt(5.0)))
@@ -42,12 +91,36 @@ impl Dielectric {
 
 impl Material for Dielectric {
     fn scatter(&self, rng: &mut ThreadRng, ray: &Ray, hit: &Hit) -> Scattered {
-        let (ri, r0_sq) = if hit.front_face {
-            // Front face hit
-            (self.inv_refraction_index, self.inv_r0_sq)
-        } else {
-            // Back face hit
-            (self.refraction_index, self.r0_sq)
+        // Dispersion when tracing wavelengths - only the hero wavelength can be followed
+        let dispersed = match (&self.dispersion, ray.wavelengths()) {
+            (Some(dispersion), Some(wavelengths)) => {
+                let refraction_index =
+                    flt(dispersion.refraction_index(flt_prim(wavelengths.hero())));
+
+                Some((refraction_index, wavelengths.hero_only()))
+            }
+            _ => None,
+        };
+
+        let (ri, r0_sq) = match &dispersed {
+            Some((refraction_index, _)) => {
+                let ri = if hit.front_face {
+                    refraction_index.recip()
+                } else {
+                    *refraction_index
+                };
+
+                (ri, Self::r0_sq(ri))
+            }
+            None => {
+                if hit.front_face {
+                    // Front face hit
+                    (self.inv_refraction_index, self.inv_r0_sq)
+                } else {
+                    // Back face hit
+                    (self.refraction_index, self.r0_sq)
+                }
+            }
         };
 
         let unit_direction = ray.direction().unit_vector();
@@ -66,7 +139,11 @@ impl Material for Dielectric {
                 unit_direction.refract(&hit.normal, ri)
             };
 
-        let scattered = Ray::new(hit.p.clone(), direction, ray.time());
+        let mut scattered = Ray::new(hit.p.clone(), direction, ray.time());
+
+        if let Some((_, wavelengths)) = dispersed {
+            scattered.set_wavelengths(Some(wavelengths));
+        }
 
         (Colour::new_white(), None, Some(scattered))
     }
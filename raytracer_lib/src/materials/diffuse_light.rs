@@ -32,7 +32,8 @@ impl<'a> DiffuseLight<'a> {
         Self::new_with_texref(TexRef::Borrow(texture))
     }
 
-    fn new_with_texref(texture: TexRef<'a>) -> Self {
+    /// Create a new diffuse light with a given texture reference
+    pub fn new_with_texref(texture: TexRef<'a>) -> Self {
         Self { texture }
     }
 }
@@ -1,11 +1,11 @@
 //! Materials
 
 use rand::rngs::ThreadRng;
-use std::{fmt::Debug, ops::Deref};
+use std::{fmt::Debug, ops::Deref, sync::Arc};
 
 use crate::{
     float::*,
-    hits::hit::Hit,
+    hits::{hit::Hit, hittable::type_name},
     ray::Ray,
     triple::{Colour, Point3},
 };
@@ -22,15 +22,25 @@ pub trait Material: Debug + Send + Sync {
 
     /// Returns details of scattered light
     fn scatter(&self, rng: &mut ThreadRng, ray: &Ray, hit: &Hit) -> Scattered;
+
+    /// Returns the name of the material type
+    fn name(&self) -> &'static str {
+        type_name::<Self>()
+    }
 }
 
-/// A material reference, borrowed or owned
+/// A material reference with no borrows (owned, shared or 'static)
+pub type OwnedMatRef = MatRef<'static>;
+
+/// A material reference, borrowed, owned or shared
 #[derive(Debug)]
 pub enum MatRef<'a> {
     /// A borrowed material
     Borrow(&'a dyn Material),
     /// An owned material
     Box(Box<dyn Material + 'a>),
+    /// A shared material
+    Arc(Arc<dyn Material + 'a>),
 }
 
 impl<'a> MatRef<'a> {
@@ -39,11 +49,34 @@ impl<'a> MatRef<'a> {
         Self::Box(Box::new(material))
     }
 
+    /// Create a new shared material reference
+    pub fn shared(material: impl Material + 'a) -> Self {
+        Self::Arc(Arc::new(material))
+    }
+
+    /// Converts an owned material reference to a shared one so that it can be shared
+    pub fn into_shared(self) -> Self {
+        match self {
+            Self::Box(boxed) => Self::Arc(Arc::from(boxed)),
+            other => other,
+        }
+    }
+
+    /// Creates another reference to the same material if it is borrowed or shared
+    pub fn share(&self) -> Option<Self> {
+        match self {
+            Self::Borrow(refer) => Some(Self::Borrow(*refer)),
+            Self::Box(_) => None,
+            Self::Arc(shared) => Some(Self::Arc(shared.clone())),
+        }
+    }
+
     /// Gets a reference to the material
     pub fn get_ref(&'a self) -> &dyn Material {
         match self {
             Self::Borrow(refer) => *refer,
             Self::Box(boxed) => boxed.as_ref(),
+            Self::Arc(shared) => shared.as_ref(),
         }
     }
 }
@@ -55,6 +88,7 @@ impl<'a> Deref for MatRef<'a> {
         match self {
             MatRef::Borrow(refer) => *refer,
             MatRef::Box(boxed) => boxed.as_ref(),
+            MatRef::Arc(shared) => shared.as_ref(),
         }
     }
 }
@@ -0,0 +1,136 @@
+//! Mix of two materials
+
+use rand::rngs::ThreadRng;
+
+use crate::{
+    float::*,
+    hits::hit::Hit,
+    ray::Ray,
+    textures::{
+        solid::Solid,
+        texture::{TexRef, Texture},
+    },
+    triple::{Colour, Point3},
+};
+
+use super::material::{MatRef, Material, Scattered};
+
+/// Mix material details
+#[derive(Debug)]
+pub struct Mix<'a> {
+    /// Material used where the mask is 0
+    a: MatRef<'a>,
+    /// Material used where the mask is 1
+    b: MatRef<'a>,
+    /// Probability of selecting material b
+    mask: TexRef<'a>,
+}
+
+impl<'a> Mix<'a> {
+    /// Create a new mix of two materials with a constant mix factor (0 = a, 1 = b)
+    pub fn new(factor: FltPrim, a: &'a dyn Material, b: &'a dyn Material) -> Self {
+        Self::new_with_matref(
+            TexRef::boxed(Solid::new(Colour::new_grey(factor))),
+            MatRef::Borrow(a),
+            MatRef::Borrow(b),
+        )
+    }
+
+    /// Create a new mix of two materials using a texture mask (black = a, white = b)
+    pub fn new_with_texture(
+        mask: &'a dyn Texture,
+        a: &'a dyn Material,
+        b: &'a dyn Material,
+    ) -> Self {
+        Self::new_with_matref(TexRef::Borrow(mask), MatRef::Borrow(a), MatRef::Borrow(b))
+    }
+
+    /// Create a new mix of two material references using a texture mask reference
+    pub fn new_with_matref(mask: TexRef<'a>, a: MatRef<'a>, b: MatRef<'a>) -> Self {
+        Self { a, b, mask }
+    }
+
+    /// Selects one of the materials weighted by the mask. The choice is made from a hash of the
+    /// hit coordinates rather than a random number so that the hit test and the scatter for the
+    /// same hit select the same material. The point must be in object space as it is for the
+    /// hit test
+    fn select(&self, u: Flt, v: Flt, p: &Point3) -> &MatRef<'a> {
+        let factor = self.mask.scalar(u, v, p);
+
+        if factor > selector(u, v, p) {
+            &self.b
+        } else {
+            &self.a
+        }
+    }
+}
+
+/// Returns a number in 0..1 which varies randomly with the texture coordinates and point
+fn selector(u: Flt, v: Flt, p: &Point3) -> FltPrim {
+    let hash = [u, v, p.x(), p.y(), p.z()]
+        .into_iter()
+        .fold(0u64, |hash, x| {
+            mix_bits(hash ^ flt_prim(x).to_bits() as u64)
+        });
+
+    // Use the top 24 bits so the result is exact in f32 and below 1
+    (hash >> 40) as FltPrim / (1u64 << 24) as FltPrim
+}
+
+/// Mixes the bits of a 64 bit integer (splitmix64 finaliser)
+fn mix_bits(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+    x ^ (x >> 31)
+}
+
+impl<'a> Material for Mix<'a> {
+    fn hit(&self, rng: &mut ThreadRng, u: Flt, v: Flt, p: &Point3) -> bool {
+        self.select(u, v, p).hit(rng, u, v, p)
+    }
+
+    fn scatter(&self, rng: &mut ThreadRng, ray: &Ray, hit: &Hit) -> Scattered {
+        self.select(hit.u, hit.v, &hit.object_p)
+            .scatter(rng, ray, hit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ptr;
+
+    use rand::{thread_rng, Rng};
+
+    use crate::materials::lambertian::Lambertian;
+
+    use super::*;
+
+    #[test]
+    fn test_selection() {
+        let a = Lambertian::new_with_colour(Colour::new(0.0, 0.0, 0.0));
+        let b = Lambertian::new_with_colour(Colour::new(1.0, 1.0, 1.0));
+        let mix = Mix::new(0.3, &a, &b);
+
+        let mut rng = thread_rng();
+        let samples = 20000;
+        let mut selected_b = 0;
+
+        for _ in 0..samples {
+            let u = flt(rng.gen_range(0.0..1.0));
+            let v = flt(rng.gen_range(0.0..1.0));
+            let p = Point3::new_random_clamped(&mut rng, 0.0, 10.0);
+
+            let selected = mix.select(u, v, &p);
+
+            // The same hit always selects the same material
+            assert!(ptr::eq(selected, mix.select(u, v, &p)));
+
+            if ptr::eq(selected, &mix.b) {
+                selected_b += 1;
+            }
+        }
+
+        let fraction = selected_b as FltPrim / samples as FltPrim;
+        assert!((fraction - 0.3).abs() < 0.02, "{fraction}");
+    }
+}
@@ -1,5 +1,7 @@
 //! Materials
 
+pub mod bump;
+pub mod coated;
 pub mod dielectric;
 pub mod diffuse;
 pub mod diffuse_light;
@@ -8,5 +10,9 @@ pub mod isotropic;
 pub mod lambertian;
 pub mod material;
 pub mod metal;
+pub mod mix;
 pub mod normal;
+pub mod phase;
 pub mod polar_light;
+pub mod principled;
+pub mod thin_film;
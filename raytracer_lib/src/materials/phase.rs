@@ -0,0 +1,185 @@
+//! Phase function material for participating media
+
+use rand::{rngs::ThreadRng, Rng};
+
+use crate::{
+    float::*,
+    hits::hit::Hit,
+    ray::Ray,
+    textures::{
+        solid::Solid,
+        texture::{TexRef, Texture},
+    },
+    triple::{Colour, Point3, Vec3},
+};
+
+use super::material::{Material, Scattered};
+
+/// Distribution of scattered directions relative to the incoming direction
+#[derive(Debug, Clone)]
+pub enum PhaseFunction {
+    /// Uniform scattering in all directions
+    Isotropic,
+    /// Henyey-Greenstein with asymmetry parameter g (-1 backward .. 0 isotropic .. 1 forward)
+    HenyeyGreenstein(Flt),
+    /// Blend of two Henyey-Greenstein lobes with the weight of the first lobe (0..1)
+    DoubleHenyeyGreenstein(Flt, Flt, Flt),
+    /// Rayleigh scattering by particles much smaller than the wavelength (clear sky)
+    Rayleigh,
+}
+
+impl PhaseFunction {
+    /// Create a new Henyey-Greenstein phase function
+    pub fn henyey_greenstein(g: FltPrim) -> Self {
+        Self::HenyeyGreenstein(flt(g.clamp(-0.99, 0.99)))
+    }
+
+    /// Create a new double lobe Henyey-Greenstein phase function (typically one forward and
+    /// one backward lobe) with the weight of the first lobe
+    pub fn double_henyey_greenstein(g1: FltPrim, g2: FltPrim, weight: FltPrim) -> Self {
+        Self::DoubleHenyeyGreenstein(
+            flt(g1.clamp(-0.99, 0.99)),
+            flt(g2.clamp(-0.99, 0.99)),
+            flt(weight.clamp(0.0, 1.0)),
+        )
+    }
+
+    /// Samples a scattered direction for a given unit incoming direction
+    pub fn sample(&self, rng: &mut ThreadRng, direction: &Vec3) -> Vec3 {
+        let cos_theta = match self {
+            PhaseFunction::Isotropic => return Vec3::new_random_unit_vector(rng),
+            PhaseFunction::HenyeyGreenstein(g) => Self::sample_hg_cos(rng, *g),
+            PhaseFunction::DoubleHenyeyGreenstein(g1, g2, weight) => {
+                // Choose a lobe by its weight
+                if *weight > rng.gen_range(0.0..1.0) {
+                    Self::sample_hg_cos(rng, *g1)
+                } else {
+                    Self::sample_hg_cos(rng, *g2)
+                }
+            }
+            PhaseFunction::Rayleigh => Self::sample_rayleigh_cos(rng),
+        };
+
+        Self::direction_from_cos(rng, direction, cos_theta)
+    }
+
+    /// Samples the cosine of the angle between the incoming and scattered directions
+    fn sample_hg_cos(rng: &mut ThreadRng, g: Flt) -> Flt {
+        let xi = flt(rng.gen_range(0.0..1.0));
+
+        if g.abs() < 1e-3 {
+            return flt(1.0) - flt(2.0) * xi;
+        }
+
+        let g_sq = g * g;
+        let term = (flt(1.0) - g_sq) / (flt(1.0) - g + flt(2.0) * g * xi);
+
+        clamp(
+            (flt(1.0) + g_sq - term * term) / (flt(2.0) * g),
+            flt(-1.0),
+            flt(1.0),
+        )
+    }
+
+    /// Samples the cosine of the scattering angle for Rayleigh scattering, p(cos) ∝ 1 + cos²,
+    /// by inverting the cumulative distribution
+    fn sample_rayleigh_cos(rng: &mut ThreadRng) -> Flt {
+        let q = flt(2.0) * (flt(2.0) * flt(rng.gen_range(0.0..1.0)) - flt(1.0));
+        let a = (q + (q * q + flt(1.0)).sqrt()).cbrt();
+
+        clamp(a - a.recip(), flt(-1.0), flt(1.0))
+    }
+
+    /// Builds a direction at a given angle to a unit direction with a random azimuth
+    fn direction_from_cos(rng: &mut ThreadRng, direction: &Vec3, cos_theta: Flt) -> Vec3 {
+        let sin_theta = (flt(1.0) - cos_theta * cos_theta).max(flt(0.0)).sqrt();
+        let phi = flt(2.0 * PI) * flt(rng.gen_range(0.0..1.0));
+
+        // Orthonormal basis around the incoming direction
+        let axis = if direction.x().abs() > 0.9 {
+            Vec3::new(0.0, 1.0, 0.0)
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+
+        let tangent = axis.cross(direction).unit_vector();
+        let bitangent = direction.cross(&tangent);
+
+        (sin_theta * phi.cos() * tangent)
+            + (sin_theta * phi.sin() * bitangent)
+            + (cos_theta * direction)
+    }
+}
+
+/// Phase function material details
+#[derive(Debug)]
+pub struct Phase<'a> {
+    function: PhaseFunction,
+    texture: TexRef<'a>,
+}
+
+impl<'a> Phase<'a> {
+    /// Create new phase function material with a given colour
+    pub fn new_with_colour(function: PhaseFunction, albedo: Colour) -> Self {
+        Self::new_with_texref(function, TexRef::boxed(Solid::new(albedo)))
+    }
+
+    /// Create new phase function material with a given texture
+    pub fn new_with_texture(function: PhaseFunction, texture: &'a dyn Texture) -> Self {
+        Self::new_with_texref(function, TexRef::Borrow(texture))
+    }
+
+    /// Create new phase function material with a given texture reference
+    pub fn new_with_texref(function: PhaseFunction, texture: TexRef<'a>) -> Self {
+        Self { function, texture }
+    }
+}
+
+impl<'a> Material for Phase<'a> {
+    fn hit(&self, rng: &mut ThreadRng, u: Flt, v: Flt, p: &Point3) -> bool {
+        self.texture.hit(rng, u, v, p)
+    }
+
+    fn scatter(&self, rng: &mut ThreadRng, ray: &Ray, hit: &Hit) -> Scattered {
+        let direction = self.function.sample(rng, &ray.direction().unit_vector());
+
+        let scattered = Ray::new(hit.p.clone(), direction, ray.time());
+
+        (
+            self.texture.value(hit.u, hit.v, &hit.p),
+            None,
+            Some(scattered),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::thread_rng;
+
+    use super::*;
+
+    fn mean_cos(function: &PhaseFunction) -> FltPrim {
+        let mut rng = thread_rng();
+        let direction = Vec3::new(0.0, 0.0, 1.0);
+        let samples = 20000;
+
+        let sum: Flt = (0..samples)
+            .map(|_| function.sample(&mut rng, &direction).dot(&direction))
+            .sum();
+
+        flt_prim(sum) / samples as FltPrim
+    }
+
+    #[test]
+    fn test_mean_cosine() {
+        // The mean cosine of Henyey-Greenstein is g, Rayleigh is symmetric
+        assert!((mean_cos(&PhaseFunction::henyey_greenstein(0.6)) - 0.6).abs() < 0.02);
+        assert!((mean_cos(&PhaseFunction::henyey_greenstein(-0.3)) + 0.3).abs() < 0.02);
+        assert!(
+            (mean_cos(&PhaseFunction::double_henyey_greenstein(0.8, -0.4, 0.75)) - 0.5).abs()
+                < 0.02
+        );
+        assert!(mean_cos(&PhaseFunction::Rayleigh).abs() < 0.02);
+    }
+}
@@ -33,7 +33,8 @@ impl<'a> PolarLight<'a> {
         Self::new_with_texref(angle, TexRef::Borrow(texture))
     }
 
-    fn new_with_texref(angle: FltPrim, texture: TexRef<'a>) -> Self {
+    /// Create a new polar light with a given texture reference
+    pub fn new_with_texref(angle: FltPrim, texture: TexRef<'a>) -> Self {
         Self {
             cos_angle: flt(angle).to_radians().cos(),
             texture,
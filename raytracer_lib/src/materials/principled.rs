@@ -0,0 +1,315 @@
+//! Principled (Disney style) material
+
+use rand::{rngs::ThreadRng, Rng};
+
+use crate::{
+    float::*,
+    hits::hit::Hit,
+    ray::Ray,
+    textures::{
+        solid::Solid,
+        texture::{TexRef, Texture},
+    },
+    triple::{Colour, Point3, Vec3},
+};
+
+use super::material::{Material, Scattered};
+
+/// Roughness of the clearcoat layer
+const CLEARCOAT_ROUGHNESS: FltPrim = 0.05;
+
+/// Principled material details
+#[derive(Debug)]
+pub struct Principled<'a> {
+    /// Base (albedo) colour
+    base_colour: TexRef<'a>,
+    /// Metallic factor (0 = dielectric, 1 = metal)
+    metallic: TexRef<'a>,
+    /// Surface roughness (0 = mirror, 1 = fully rough)
+    roughness: TexRef<'a>,
+    /// Dielectric specular reflectance (0.5 = 4%)
+    specular: TexRef<'a>,
+    /// Amount of base colour tint in the dielectric specular reflection
+    specular_tint: TexRef<'a>,
+    /// Clearcoat layer strength
+    clearcoat: TexRef<'a>,
+    /// Sheen strength (grazing retro-reflection for cloth)
+    sheen: TexRef<'a>,
+    /// Transmission factor (0 = opaque, 1 = fully transmissive)
+    transmission: TexRef<'a>,
+    /// Refractive index used for transmission
+    refraction_index: Flt,
+}
+
+/// Generates value, texture and texture reference setters for a parameter
+macro_rules! param_setters {
+    ($field:ident, $set:ident, $set_texture:ident, $set_texref:ident, $desc:literal) => {
+        #[doc = concat!("Sets the ", $desc)]
+        pub fn $set(&mut self, value: FltPrim) {
+            self.$set_texref(TexRef::boxed(Solid::new(Colour::new_grey(value))));
+        }
+
+        #[doc = concat!("Sets the ", $desc, " from a texture")]
+        pub fn $set_texture(&mut self, texture: &'a dyn Texture) {
+            self.$set_texref(TexRef::Borrow(texture));
+        }
+
+        #[doc = concat!("Sets the ", $desc, " from a texture reference")]
+        pub fn $set_texref(&mut self, texture: TexRef<'a>) {
+            self.$field = texture;
+        }
+    };
+}
+
+impl<'a> Principled<'a> {
+    /// Create a new principled material with a given base colour
+    pub fn new_with_colour(base_colour: Colour) -> Self {
+        Self::new_with_texref(TexRef::boxed(Solid::new(base_colour)))
+    }
+
+    /// Create a new principled material with a given base colour texture
+    pub fn new_with_texture(base_colour: &'a dyn Texture) -> Self {
+        Self::new_with_texref(TexRef::Borrow(base_colour))
+    }
+
+    /// Create a new principled material with a given base colour texture reference
+    pub fn new_with_texref(base_colour: TexRef<'a>) -> Self {
+        let grey = |level| TexRef::boxed(Solid::new(Colour::new_grey(level)));
+
+        Self {
+            base_colour,
+            metallic: grey(0.0),
+            roughness: grey(0.5),
+            specular: grey(0.5),
+            specular_tint: grey(0.0),
+            clearcoat: grey(0.0),
+            sheen: grey(0.0),
+            transmission: grey(0.0),
+            refraction_index: flt(1.5),
+        }
+    }
+
+    param_setters!(
+        metallic,
+        set_metallic,
+        set_metallic_texture,
+        set_metallic_texref,
+        "metallic factor"
+    );
+
+    param_setters!(
+        roughness,
+        set_roughness,
+        set_roughness_texture,
+        set_roughness_texref,
+        "roughness"
+    );
+
+    param_setters!(
+        specular,
+        set_specular,
+        set_specular_texture,
+        set_specular_texref,
+        "dielectric specular level"
+    );
+
+    param_setters!(
+        specular_tint,
+        set_specular_tint,
+        set_specular_tint_texture,
+        set_specular_tint_texref,
+        "specular tint"
+    );
+
+    param_setters!(
+        clearcoat,
+        set_clearcoat,
+        set_clearcoat_texture,
+        set_clearcoat_texref,
+        "clearcoat strength"
+    );
+
+    param_setters!(
+        sheen,
+        set_sheen,
+        set_sheen_texture,
+        set_sheen_texref,
+        "sheen"
+    );
+
+    param_setters!(
+        transmission,
+        set_transmission,
+        set_transmission_texture,
+        set_transmission_texref,
+        "transmission factor"
+    );
+
+    /// Sets the refractive index used for transmission
+    pub fn set_refraction_index(&mut self, refraction_index: FltPrim) {
+        self.refraction_index = flt(refraction_index);
+    }
+
+    /// Returns a scalar parameter value clamped to 0..1
+    fn param(texture: &TexRef, hit: &Hit) -> Flt {
+        clamp(texture.scalar(hit.u, hit.v, &hit.p), flt(0.0), flt(1.0))
+    }
+
+    /// Schlick's approximation of the Fresnel factor
+    fn schlick(cosine: Flt) -> Flt {
+        (flt(1.0) - cosine).powf(flt(5.0))
+    }
+
+    /// Reflects a unit direction about the normal, perturbed by roughness
+    fn glossy_reflect(
+        rng: &mut ThreadRng,
+        unit_direction: &Vec3,
+        normal: &Vec3,
+        roughness: Flt,
+    ) -> Vec3 {
+        let reflected = unit_direction.reflect(normal);
+
+        if roughness > 0.0 {
+            reflected + (roughness * roughness * Vec3::new_random_unit_vector(rng))
+        } else {
+            reflected
+        }
+    }
+
+    /// Returns a scattered ray if the direction is on the expected side of the surface
+    fn scattered(
+        ray: &Ray,
+        p: &Point3,
+        direction: Vec3,
+        normal: &Vec3,
+        outward: bool,
+    ) -> Option<Ray> {
+        if (direction.dot(normal) > 0.0) == outward {
+            Some(Ray::new(p.clone(), direction, ray.time()))
+        } else {
+            None
+        }
+    }
+}
+
+impl<'a> Material for Principled<'a> {
+    fn hit(&self, rng: &mut ThreadRng, u: Flt, v: Flt, p: &Point3) -> bool {
+        self.base_colour.hit(rng, u, v, p)
+    }
+
+    fn scatter(&self, rng: &mut ThreadRng, ray: &Ray, hit: &Hit) -> Scattered {
+        let base_colour = self.base_colour.value(hit.u, hit.v, &hit.p);
+        let metallic = Self::param(&self.metallic, hit);
+        let roughness = Self::param(&self.roughness, hit);
+        let clearcoat = Self::param(&self.clearcoat, hit);
+        let transmission = Self::param(&self.transmission, hit);
+
+        let unit_direction = ray.direction().unit_vector();
+        let cos_theta = (-unit_direction.dot(&hit.normal)).min(flt(1.0));
+        let fresnel = Self::schlick(cos_theta);
+
+        // Clearcoat layer - reflect with probability of its Fresnel weight, otherwise continue to the base
+        let clearcoat_f = clearcoat * (flt(0.04) + flt(0.96) * fresnel);
+
+        if clearcoat_f > rng.gen_range(0.0..1.0) {
+            let direction =
+                Self::glossy_reflect(rng, &unit_direction, &hit.normal, flt(CLEARCOAT_ROUGHNESS));
+
+            return match Self::scattered(ray, &hit.p, direction, &hit.normal, true) {
+                Some(scattered) => (Colour::new_white(), None, Some(scattered)),
+                None => (Colour::default(), None, None),
+            };
+        }
+
+        // Metallic lobe - coloured specular reflection
+        if metallic > rng.gen_range(0.0..1.0) {
+            let direction = Self::glossy_reflect(rng, &unit_direction, &hit.normal, roughness);
+            let attenuation = base_colour.lerp(&Colour::new_white(), fresnel);
+
+            return match Self::scattered(ray, &hit.p, direction, &hit.normal, true) {
+                Some(scattered) => (attenuation, None, Some(scattered)),
+                None => (Colour::default(), None, None),
+            };
+        }
+
+        // Transmission lobe - rough tinted glass
+        if transmission > rng.gen_range(0.0..1.0) {
+            let ri = if hit.front_face {
+                self.refraction_index.recip()
+            } else {
+                self.refraction_index
+            };
+
+            let r0 = (flt(1.0) - ri) / (flt(1.0) + ri);
+            let r0_sq = r0 * r0;
+
+            let sin_theta = (flt(1.0) - cos_theta * cos_theta).sqrt();
+            let cannot_refract = ri * sin_theta > 1.0;
+
+            if cannot_refract || r0_sq + (flt(1.0) - r0_sq) * fresnel > rng.gen_range(0.0..1.0) {
+                let direction = Self::glossy_reflect(rng, &unit_direction, &hit.normal, roughness);
+
+                return match Self::scattered(ray, &hit.p, direction, &hit.normal, true) {
+                    Some(scattered) => (Colour::new_white(), None, Some(scattered)),
+                    None => (Colour::default(), None, None),
+                };
+            }
+
+            let mut direction = unit_direction.refract(&hit.normal, ri);
+
+            if roughness > 0.0 {
+                direction = direction.unit_vector()
+                    + (roughness * roughness * Vec3::new_random_unit_vector(rng));
+            }
+
+            return match Self::scattered(ray, &hit.p, direction, &hit.normal, false) {
+                Some(scattered) => (base_colour, None, Some(scattered)),
+                None => (Colour::default(), None, None),
+            };
+        }
+
+        // Dielectric base - specular reflection or diffuse with sheen
+        let specular = Self::param(&self.specular, hit);
+        let specular_tint = Self::param(&self.specular_tint, hit);
+        let sheen = Self::param(&self.sheen, hit);
+
+        let luminance = base_colour.luminance();
+
+        let tint = if luminance > 0.0 {
+            &base_colour / luminance
+        } else {
+            Colour::new_white()
+        };
+
+        let specular_f0 = Colour::new_white().lerp(&tint, specular_tint) * (flt(0.08) * specular);
+        let specular_f = specular_f0.lerp(&Colour::new_white(), fresnel);
+        let specular_prob = clamp(specular_f.luminance(), flt(0.0), flt(1.0));
+
+        if specular_prob > rng.gen_range(0.0..1.0) {
+            let direction = Self::glossy_reflect(rng, &unit_direction, &hit.normal, roughness);
+
+            return match Self::scattered(ray, &hit.p, direction, &hit.normal, true) {
+                Some(scattered) => (specular_f / specular_prob, None, Some(scattered)),
+                None => (Colour::default(), None, None),
+            };
+        }
+
+        let mut scatter_direction = &hit.normal + Vec3::new_random_unit_vector(rng);
+
+        // Catch degenerate scatter direction
+        if scatter_direction.near_zero() {
+            scatter_direction = hit.normal.clone();
+        }
+
+        // Sheen is strongest at grazing angles of the outgoing direction
+        let cos_out = scatter_direction.unit_vector().dot(&hit.normal);
+        let sheen_colour =
+            Colour::new_white().lerp(&tint, flt(0.5)) * (sheen * Self::schlick(cos_out));
+
+        let attenuation = base_colour + sheen_colour;
+
+        let scattered = Ray::new(hit.p.clone(), scatter_direction, ray.time());
+
+        (attenuation, None, Some(scattered))
+    }
+}
@@ -0,0 +1,263 @@
+//! Thin film interference material (soap bubbles, coated optics)
+
+use rand::{rngs::ThreadRng, Rng};
+
+use crate::{
+    float::*,
+    hits::hit::Hit,
+    ray::Ray,
+    spectrum::{rgb_to_spectrum, Wavelengths},
+    textures::texture::{TexRef, Texture},
+    triple::{Colour, Vec3},
+};
+
+use super::material::{Material, Scattered};
+
+/// Representative wavelengths (nm) for the red, green and blue channels
+const RGB_WAVELENGTHS: [FltPrim; 3] = [650.0, 532.0, 450.0];
+
+/// Material underneath the film
+#[derive(Debug, Clone)]
+pub enum ThinFilmBase {
+    /// Dielectric with a given refractive index (1.0 for a free standing film such as a soap bubble)
+    Dielectric(Flt),
+    /// Metal with a given albedo and fuzz factor
+    Metal(Colour, Flt),
+}
+
+/// Thin film details
+#[derive(Debug)]
+pub struct ThinFilm<'a> {
+    /// Material underneath the film
+    base: ThinFilmBase,
+    /// Refractive index of the film
+    film_ior: Flt,
+    /// Film thickness in nanometres
+    thickness: Flt,
+    /// Optional texture scaling the thickness (0..1)
+    thickness_texture: Option<TexRef<'a>>,
+}
+
+impl<'a> ThinFilm<'a> {
+    /// Create a new thin film over a dielectric with given film thickness (nm) and film refractive index
+    pub fn new_dielectric(
+        refraction_index: FltPrim,
+        thickness: FltPrim,
+        film_ior: FltPrim,
+    ) -> Self {
+        Self::new_with_base(
+            ThinFilmBase::Dielectric(flt(refraction_index)),
+            thickness,
+            film_ior,
+        )
+    }
+
+    /// Create a new thin film over a metal with given film thickness (nm) and film refractive index
+    pub fn new_metal(albedo: Colour, fuzz: FltPrim, thickness: FltPrim, film_ior: FltPrim) -> Self {
+        Self::new_with_base(
+            ThinFilmBase::Metal(albedo, flt(fuzz.clamp(0.0, 1.0))),
+            thickness,
+            film_ior,
+        )
+    }
+
+    /// Create a new thin film over a given base with film thickness (nm) and film refractive index
+    pub fn new_with_base(base: ThinFilmBase, thickness: FltPrim, film_ior: FltPrim) -> Self {
+        Self {
+            base,
+            film_ior: flt(film_ior),
+            thickness: flt(thickness),
+            thickness_texture: None,
+        }
+    }
+
+    /// Sets a texture to scale the film thickness
+    pub fn set_thickness_texture(&mut self, texture: &'a dyn Texture) {
+        self.set_thickness_texref(TexRef::Borrow(texture));
+    }
+
+    /// Sets a texture reference to scale the film thickness
+    pub fn set_thickness_texref(&mut self, texture: TexRef<'a>) {
+        self.thickness_texture = Some(texture);
+    }
+
+    /// Returns the film thickness at the hit point
+    fn thickness(&self, hit: &Hit) -> Flt {
+        match &self.thickness_texture {
+            Some(texture) => self.thickness * texture.scalar(hit.u, hit.v, &hit.p),
+            None => self.thickness,
+        }
+    }
+
+    /// Calculates the reflectance of the film for each colour channel, or as a grey value at
+    /// the hero wavelength when tracing wavelengths.
+    /// n1 is the refractive index of the incident medium and n3 that of the medium behind the
+    /// film, or None for a metal with the given albedo
+    fn reflectance(
+        &self,
+        cos1: Flt,
+        n1: Flt,
+        n3: Option<Flt>,
+        metal: &Colour,
+        thickness: Flt,
+        wavelengths: Option<&Wavelengths>,
+    ) -> Colour {
+        let n2 = self.film_ior;
+
+        // Snell's law into the film
+        let sin1_sq = flt(1.0) - cos1 * cos1;
+        let sin2_sq = (n1 / n2) * (n1 / n2) * sin1_sq;
+
+        if sin2_sq >= 1.0 {
+            // Total internal reflection at the film
+            return Colour::new_white();
+        }
+
+        let cos2 = (flt(1.0) - sin2_sq).sqrt();
+
+        // Fresnel amplitude coefficients at the top of the film
+        let r12_s = (n1 * cos1 - n2 * cos2) / (n1 * cos1 + n2 * cos2);
+        let r12_p = (n2 * cos1 - n1 * cos2) / (n2 * cos1 + n1 * cos2);
+
+        // Fresnel amplitude coefficients at the bottom of the film, None for a metal
+        let r23 = match n3 {
+            Some(n3) => {
+                let sin3_sq = (n1 / n3) * (n1 / n3) * sin1_sq;
+
+                if sin3_sq >= 1.0 {
+                    // Total internal reflection below the film
+                    return Colour::new_white();
+                }
+
+                let cos3 = (flt(1.0) - sin3_sq).sqrt();
+
+                Some((
+                    (n2 * cos2 - n3 * cos3) / (n2 * cos2 + n3 * cos3),
+                    (n3 * cos2 - n2 * cos3) / (n3 * cos2 + n2 * cos3),
+                ))
+            }
+            None => None,
+        };
+
+        let airy = |r12: Flt, r23: Flt, delta: Flt| -> Flt {
+            let cross = flt(2.0) * r12 * r23 * delta.cos();
+
+            (r12 * r12 + r23 * r23 + cross) / (flt(1.0) + r12 * r12 * r23 * r23 + cross)
+        };
+
+        // Reflectance at a given wavelength with a given metal albedo
+        let at = |wavelength: Flt, albedo: Flt| -> Flt {
+            // Approximate a metal as a perfect conductor with a phase shift of pi
+            let (r23_s, r23_p) = r23.unwrap_or_else(|| {
+                let r = -albedo.max(flt(0.0)).sqrt();
+                (r, r)
+            });
+
+            // Phase difference between the two reflected waves
+            let delta = flt(4.0 * PI) * n2 * thickness * cos2 / wavelength;
+
+            // Average of the s and p polarisations
+            (airy(r12_s, r23_s, delta) + airy(r12_p, r23_p, delta)) / flt(2.0)
+        };
+
+        match wavelengths {
+            Some(wavelengths) => {
+                let hero = wavelengths.hero();
+                let r = at(hero, rgb_to_spectrum(metal, hero));
+
+                Colour::new_flt(r, r, r)
+            }
+            None => {
+                let mut result = [flt(0.0); 3];
+
+                for (c, &wavelength) in RGB_WAVELENGTHS.iter().enumerate() {
+                    result[c] = at(flt(wavelength), metal[c]);
+                }
+
+                Colour::new_from_array(result)
+            }
+        }
+    }
+}
+
+impl<'a> Material for ThinFilm<'a> {
+    fn scatter(&self, rng: &mut ThreadRng, ray: &Ray, hit: &Hit) -> Scattered {
+        let unit_direction = ray.direction().unit_vector();
+        let cos_theta = (-unit_direction.dot(&hit.normal)).min(flt(1.0));
+        let thickness = self.thickness(hit);
+
+        // Interference is wavelength dependent, so only the hero wavelength can be followed
+        let wavelengths = ray.wavelengths();
+
+        match &self.base {
+            ThinFilmBase::Dielectric(refraction_index) => {
+                let (n1, n3) = if hit.front_face {
+                    (flt(1.0), *refraction_index)
+                } else {
+                    (*refraction_index, flt(1.0))
+                };
+
+                let reflectance = self.reflectance(
+                    cos_theta,
+                    n1,
+                    Some(n3),
+                    &Colour::default(),
+                    thickness,
+                    wavelengths,
+                );
+
+                // Choose reflection or refraction by the mean reflectance and weight each channel
+                let reflect_prob = clamp(
+                    (reflectance[0] + reflectance[1] + reflectance[2]) / flt(3.0),
+                    flt(0.0),
+                    flt(1.0),
+                );
+
+                if reflect_prob > rng.gen_range(0.0..1.0) {
+                    let mut scattered = Ray::new(
+                        hit.p.clone(),
+                        unit_direction.reflect(&hit.normal),
+                        ray.time(),
+                    );
+
+                    scattered.set_wavelengths(wavelengths.map(Wavelengths::hero_only));
+
+                    (reflectance / reflect_prob, None, Some(scattered))
+                } else {
+                    let mut scattered = Ray::new(
+                        hit.p.clone(),
+                        unit_direction.refract(&hit.normal, n1 / n3),
+                        ray.time(),
+                    );
+
+                    scattered.set_wavelengths(wavelengths.map(Wavelengths::hero_only));
+
+                    let transmittance =
+                        (Colour::new_white() - reflectance) / (flt(1.0) - reflect_prob);
+
+                    (transmittance, None, Some(scattered))
+                }
+            }
+            ThinFilmBase::Metal(albedo, fuzz) => {
+                let reflectance =
+                    self.reflectance(cos_theta, flt(1.0), None, albedo, thickness, wavelengths);
+
+                let mut reflected = unit_direction.reflect(&hit.normal);
+
+                if *fuzz != 0.0 {
+                    reflected = reflected + (*fuzz * Vec3::new_random_unit_vector(rng));
+                }
+
+                let mut scattered = Ray::new(hit.p.clone(), reflected, ray.time());
+
+                scattered.set_wavelengths(wavelengths.map(Wavelengths::hero_only));
+
+                if scattered.direction().dot(&hit.normal) > 0.0 {
+                    (reflectance, None, Some(scattered))
+                } else {
+                    (Colour::default(), None, None)
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,142 @@
+//! Polynomial root finding
+
+use std::ops::Range;
+
+use crate::float::*;
+
+/// Maximum number of bisection steps when refining a root
+const MAX_ITERATIONS: usize = 100;
+
+/// Evaluates a polynomial. coeffs[i] is the coefficient of x^i
+pub fn evaluate(coeffs: &[FltPrim], x: FltPrim) -> FltPrim {
+    coeffs.iter().rev().fold(0.0, |acc, &c| acc * x + c)
+}
+
+/// Finds the real roots of a polynomial within a range in ascending order. coeffs[i] is the
+/// coefficient of x^i. Roots where the polynomial touches zero without changing sign may be
+/// missed
+pub fn roots_in_range(coeffs: &[FltPrim], range: Range<FltPrim>) -> Vec<FltPrim> {
+    // Ignore zero leading coefficients
+    let degree = match coeffs.iter().rposition(|&c| c != 0.0) {
+        Some(degree) => degree,
+        None => return Vec::new(),
+    };
+
+    let coeffs = &coeffs[..=degree];
+
+    match degree {
+        0 => Vec::new(),
+        1 => {
+            let root = -coeffs[0] / coeffs[1];
+
+            if range.contains(&root) {
+                vec![root]
+            } else {
+                Vec::new()
+            }
+        }
+        _ => {
+            // The polynomial is monotonic between the roots of its derivative
+            let derivative: Vec<FltPrim> = coeffs
+                .iter()
+                .enumerate()
+                .skip(1)
+                .map(|(i, &c)| i as FltPrim * c)
+                .collect();
+
+            let mut bounds = vec![range.start];
+            bounds.extend(roots_in_range(&derivative, range.clone()));
+            bounds.push(range.end);
+
+            let mut roots = Vec::new();
+
+            for pair in bounds.windows(2) {
+                if let Some(root) = bisect(coeffs, pair[0], pair[1]) {
+                    // Avoid reporting a root on an interval boundary twice
+                    if roots.last() != Some(&root) {
+                        roots.push(root);
+                    }
+                }
+            }
+
+            roots
+        }
+    }
+}
+
+/// Finds a root of a polynomial that is monotonic over an interval
+fn bisect(coeffs: &[FltPrim], mut lo: FltPrim, mut hi: FltPrim) -> Option<FltPrim> {
+    let f_lo = evaluate(coeffs, lo);
+    let f_hi = evaluate(coeffs, hi);
+
+    if f_lo == 0.0 {
+        return Some(lo);
+    }
+
+    if f_hi == 0.0 {
+        return Some(hi);
+    }
+
+    if (f_lo < 0.0) == (f_hi < 0.0) {
+        // No sign change
+        return None;
+    }
+
+    let lo_negative = f_lo < 0.0;
+
+    for _ in 0..MAX_ITERATIONS {
+        let mid = 0.5 * (lo + hi);
+
+        if mid <= lo || mid >= hi {
+            // Reached the float precision
+            break;
+        }
+
+        let f_mid = evaluate(coeffs, mid);
+
+        if f_mid == 0.0 {
+            return Some(mid);
+        }
+
+        if (f_mid < 0.0) == lo_negative {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+
+    Some(0.5 * (lo + hi))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_roots(found: Vec<FltPrim>, expected: &[FltPrim]) {
+        assert_eq!(found.len(), expected.len(), "found {found:?}");
+
+        for (f, e) in found.iter().zip(expected) {
+            assert!(
+                (f - e).abs() < 1e-5,
+                "found {found:?}, expected {expected:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_quadratic() {
+        // (x - 1)(x - 3)
+        assert_roots(roots_in_range(&[3.0, -4.0, 1.0], -10.0..10.0), &[1.0, 3.0]);
+        assert_roots(roots_in_range(&[3.0, -4.0, 1.0], 2.0..10.0), &[3.0]);
+        assert_roots(roots_in_range(&[1.0, 0.0, 1.0], -10.0..10.0), &[]);
+    }
+
+    #[test]
+    fn test_quartic() {
+        // (x + 2)(x - 0.5)(x - 1)(x - 4) = x⁴ - 3.5x³ - 4.5x² + 11x - 4
+        let coeffs = [-4.0, 11.0, -4.5, -3.5, 1.0];
+
+        assert_roots(roots_in_range(&coeffs, -10.0..10.0), &[-2.0, 0.5, 1.0, 4.0]);
+        assert_roots(roots_in_range(&coeffs, 0.75..10.0), &[1.0, 4.0]);
+    }
+}
@@ -4,6 +4,7 @@ use std::fmt::Display;
 
 use crate::{
     float::*,
+    spectrum::Wavelengths,
     triple::{Point3, Vec3},
 };
 
@@ -20,6 +21,8 @@ pub struct Ray {
     time: Flt,
     /// The ray depth
     depth: u64,
+    /// The wavelengths carried by the ray (spectral rendering only)
+    wavelengths: Option<Wavelengths>,
 }
 
 impl Ray {
@@ -33,9 +36,21 @@ impl Ray {
             inv_dir,
             time,
             depth: 0,
+            wavelengths: None,
         }
     }
 
+    /// Create a new ray with given origin and direction, keeping the time, depth and
+    /// wavelengths of an existing ray (used for transforming rays to object space)
+    pub fn new_from(ray: &Ray, orig: Point3, dir: Vec3) -> Self {
+        let mut result = Self::new(orig, dir, ray.time);
+
+        result.depth = ray.depth;
+        result.wavelengths = ray.wavelengths.clone();
+
+        result
+    }
+
     /// Returns the ray origin
     pub fn origin(&self) -> &Point3 {
         &self.orig
@@ -56,6 +71,12 @@ impl Ray {
         self.time
     }
 
+    /// Returns the ray time clamped to 0..1, over which shapes with a start and end position
+    /// move. Outside this range they hold their start or end position
+    pub fn motion_time(&self) -> Flt {
+        clamp(self.time, flt(0.0), flt(1.0))
+    }
+
     /// Returns the ray position at given distance
     pub fn at(&self, t: Flt) -> Point3 {
         &self.orig + (&self.dir * t)
@@ -70,6 +91,16 @@ impl Ray {
     pub fn set_depth(&mut self, depth: u64) {
         self.depth = depth;
     }
+
+    /// Returns the wavelengths carried by the ray (spectral rendering only)
+    pub fn wavelengths(&self) -> Option<&Wavelengths> {
+        self.wavelengths.as_ref()
+    }
+
+    /// Sets the wavelengths carried by the ray
+    pub fn set_wavelengths(&mut self, wavelengths: Option<Wavelengths>) {
+        self.wavelengths = wavelengths;
+    }
 }
 
 impl Display for Ray {
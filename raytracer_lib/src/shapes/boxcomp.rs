@@ -7,7 +7,7 @@ use rand::rngs::ThreadRng;
 use crate::{
     float::*,
     hits::{aabb::Aabb, hit::Hit, hittable::Hittable, hittable_list::HittableList},
-    materials::material::Material,
+    materials::material::{MatRef, Material},
     ray::Ray,
     triple::{Point3, Vec3},
 };
@@ -23,6 +23,15 @@ pub struct BoxComp<'a> {
 impl<'a> BoxComp<'a> {
     /// Returns the 3D box (six sides) that contains the two opposite vertices a & b.
     pub fn new(a: Point3, b: Point3, material: &'a dyn Material) -> Self {
+        Self::new_with_matref(a, b, MatRef::Borrow(material))
+    }
+
+    /// Returns the 3D box (six sides) that contains the two opposite vertices a & b. An owned
+    /// material reference is converted to a shared one for the sides.
+    pub fn new_with_matref(a: Point3, b: Point3, material: MatRef<'a>) -> Self {
+        let material = material.into_shared();
+        let side_material = || material.share().expect("Material reference is not shared");
+
         let mut sides = HittableList::new();
 
         // Construct the two opposite vertices with the minimum and maximum coordinates.
@@ -33,41 +42,41 @@ impl<'a> BoxComp<'a> {
         let dy = Vec3::new_flt(flt(0.0), max.y() - min.y(), flt(0.0));
         let dz = Vec3::new_flt(flt(0.0), flt(0.0), max.z() - min.z());
 
-        sides.add(Quad::new(
+        sides.add(Quad::new_with_matref(
             Point3::new_flt(min.x(), min.y(), max.z()),
             dx.clone(),
             dy.clone(),
-            material,
+            side_material(),
         )); // front
-        sides.add(Quad::new(
+        sides.add(Quad::new_with_matref(
             Point3::new_flt(max.x(), min.y(), max.z()),
             -(&dz),
             dy.clone(),
-            material,
+            side_material(),
         )); // right
-        sides.add(Quad::new(
+        sides.add(Quad::new_with_matref(
             Point3::new_flt(max.x(), min.y(), min.z()),
             -(&dx),
             dy.clone(),
-            material,
+            side_material(),
         )); // back
-        sides.add(Quad::new(
+        sides.add(Quad::new_with_matref(
             Point3::new_flt(min.x(), min.y(), min.z()),
             dz.clone(),
             dy,
-            material,
+            side_material(),
         )); // left
-        sides.add(Quad::new(
+        sides.add(Quad::new_with_matref(
             Point3::new_flt(min.x(), max.y(), max.z()),
             dx.clone(),
             -(&dz),
-            material,
+            side_material(),
         )); // top
-        sides.add(Quad::new(
+        sides.add(Quad::new_with_matref(
             Point3::new_flt(min.x(), min.y(), min.z()),
             dx,
             dz,
-            material,
+            side_material(),
         )); // bottom
 
         Self { sides }
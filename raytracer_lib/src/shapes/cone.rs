@@ -0,0 +1,236 @@
+//! Cone shape
+
+use std::ops::Range;
+
+use rand::rngs::ThreadRng;
+
+use crate::{
+    float::*,
+    hits::{aabb::Aabb, hit::Hit, hittable::Hittable},
+    materials::material::{MatRef, Material},
+    ray::Ray,
+    triple::{Point3, Vec3},
+};
+
+use super::frame::{azimuth, disk_tangents, Frame, LocalHit};
+
+/// Cone details
+#[derive(Debug)]
+pub struct Cone<'a> {
+    /// Centre of the base at time 0
+    base0: Point3,
+    /// Is moving?
+    moving: bool,
+    /// Movement per time unit
+    movement: Vec3,
+    /// Frame with z along the axis
+    frame: Frame,
+    /// Height from the base to the apex
+    height: Flt,
+    /// Base radius
+    radius: Flt,
+    /// Is the base closed?
+    capped: bool,
+    /// Material to use
+    material: MatRef<'a>,
+    /// Bounding box
+    bbox: Aabb,
+}
+
+impl<'a> Cone<'a> {
+    /// Creates a new capped cone from the centre of the base, an axis vector from the base
+    /// to the apex and a base radius. Material object
+    pub fn new(base: Point3, axis: Vec3, radius: FltPrim, material: &'a dyn Material) -> Self {
+        Self::new_moving(base.clone(), base, axis, radius, material)
+    }
+
+    /// Creates a new capped cone from the centre of the base, an axis vector from the base
+    /// to the apex and a base radius. Material reference
+    pub fn new_with_matref(
+        base: Point3,
+        axis: Vec3,
+        radius: FltPrim,
+        material: MatRef<'a>,
+    ) -> Self {
+        Self::new_moving_with_matref(base.clone(), base, axis, radius, material)
+    }
+
+    /// Creates a new moving capped cone with given base centres, axis vector and base radius.
+    /// Material object
+    pub fn new_moving(
+        base0: Point3,
+        base1: Point3,
+        axis: Vec3,
+        radius: FltPrim,
+        material: &'a dyn Material,
+    ) -> Self {
+        Self::new_moving_with_matref(base0, base1, axis, radius, MatRef::Borrow(material))
+    }
+
+    /// Creates a new moving capped cone with given base centres, axis vector and base radius.
+    /// Material reference
+    pub fn new_moving_with_matref(
+        base0: Point3,
+        base1: Point3,
+        axis: Vec3,
+        radius: FltPrim,
+        material: MatRef<'a>,
+    ) -> Self {
+        let movement = base0.vec_to(&base1);
+        let moving = movement.length() > 0.0;
+
+        let frame = Frame::new(&axis);
+        let radius = flt(radius);
+
+        let bbox = Aabb::new_from_bbox(
+            &Self::calc_bbox(&base0, &axis, &frame, radius),
+            &Self::calc_bbox(&base1, &axis, &frame, radius),
+        );
+
+        Self {
+            base0,
+            moving,
+            movement,
+            frame,
+            height: axis.length(),
+            radius,
+            capped: true,
+            material,
+            bbox,
+        }
+    }
+
+    /// Sets whether the base of the cone is closed
+    pub fn set_capped(&mut self, capped: bool) {
+        self.capped = capped;
+    }
+
+    fn calc_bbox(base: &Point3, axis: &Vec3, frame: &Frame, radius: Flt) -> Aabb {
+        let extent = frame.disk_extent(radius);
+        let apex = base + axis;
+
+        let base_box = Aabb::new_from_points(&(base - &extent), &(base + &extent));
+        let apex_box = Aabb::new_from_points(&apex, &apex);
+
+        Aabb::new_from_bbox(&base_box, &apex_box)
+    }
+
+    fn position_at_time(&self, time: Flt) -> Point3 {
+        if self.moving {
+            &self.base0 + (time * &self.movement)
+        } else {
+            self.base0.clone()
+        }
+    }
+
+    /// Finds the nearest intersection in the local frame
+    fn intersect(&self, origin: &Vec3, direction: &Vec3, t_range: &Range<Flt>) -> Option<LocalHit> {
+        let mut nearest = None;
+        let mut closest = t_range.end;
+
+        // Curved side: x² + y² = k²(h - z)²
+        let k = self.radius / self.height;
+        let k_sq = k * k;
+        let w = self.height - origin.z();
+
+        let a = direction.x() * direction.x() + direction.y() * direction.y()
+            - k_sq * direction.z() * direction.z();
+        let h = origin.x() * direction.x() + origin.y() * direction.y() + k_sq * w * direction.z();
+        let c = origin.x() * origin.x() + origin.y() * origin.y() - k_sq * w * w;
+
+        let roots = if a.abs() > 1e-12 {
+            let discriminant = h * h - a * c;
+
+            if discriminant >= 0.0 {
+                let sqrtd = discriminant.sqrt();
+                vec![(-h - sqrtd) / a, (-h + sqrtd) / a]
+            } else {
+                Vec::new()
+            }
+        } else if h.abs() > 1e-12 {
+            // Ray parallel to the side
+            vec![-c / (flt(2.0) * h)]
+        } else {
+            Vec::new()
+        };
+
+        for t in roots {
+            if t < t_range.start || t >= closest {
+                continue;
+            }
+
+            let local = origin + (t * direction);
+
+            if local.z() < 0.0 || local.z() > self.height {
+                continue;
+            }
+
+            let normal = Vec3::new_flt(local.x(), local.y(), k_sq * (self.height - local.z()));
+
+            let normal = if normal.near_zero() {
+                // At the apex
+                Vec3::new(0.0, 0.0, 1.0)
+            } else {
+                normal.unit_vector()
+            };
+
+            let v = local.z() / self.height;
+            let u = azimuth(&local);
+            let (sin_phi, cos_phi) = (flt(2.0 * PI) * u).sin_cos();
+
+            closest = t;
+            nearest = Some(LocalHit {
+                t,
+                normal,
+                u,
+                v,
+                dpdu: Vec3::new_flt(-local.y(), local.x(), flt(0.0)) * flt(2.0 * PI),
+                dpdv: Vec3::new_flt(-self.radius * cos_phi, -self.radius * sin_phi, self.height),
+            });
+        }
+
+        // Base
+        if self.capped && direction.z().abs() > 1e-8 {
+            let t = -origin.z() / direction.z();
+
+            if t >= t_range.start && t < closest {
+                let local = origin + (t * direction);
+                let rho = (local.x() * local.x() + local.y() * local.y()).sqrt();
+
+                if rho <= self.radius {
+                    let (dpdu, dpdv) = disk_tangents(&local, self.radius);
+
+                    nearest = Some(LocalHit {
+                        t,
+                        normal: Vec3::new(0.0, 0.0, -1.0),
+                        u: azimuth(&local),
+                        v: rho / self.radius,
+                        dpdu,
+                        dpdv,
+                    });
+                }
+            }
+        }
+
+        nearest
+    }
+}
+
+impl<'a> Hittable<'a> for Cone<'a> {
+    fn hit(&self, rng: &mut ThreadRng, ray: &Ray, t_range: Range<Flt>) -> Option<Hit> {
+        let base = self.position_at_time(ray.motion_time());
+
+        // Work in the cone's frame
+        let origin = self.frame.to_local(&base.vec_to(ray.origin()));
+        let direction = self.frame.to_local(ray.direction());
+
+        let local_hit = self.intersect(&origin, &direction, &t_range)?;
+
+        self.frame
+            .to_world_hit(rng, ray, local_hit, self.material.get_ref(), self.name())
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        &self.bbox
+    }
+}
@@ -0,0 +1,249 @@
+//! Constructive solid geometry
+
+use std::ops::Range;
+
+use rand::rngs::ThreadRng;
+
+use crate::{
+    float::*,
+    hits::{
+        aabb::Aabb,
+        hit::Hit,
+        hittable::{Hittable, HittableRef},
+    },
+    ray::Ray,
+};
+
+/// Constructive solid geometry operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsgOp {
+    /// Inside either object
+    Union,
+    /// Inside both objects
+    Intersection,
+    /// Inside the first object but not the second
+    Difference,
+}
+
+impl CsgOp {
+    fn inside(&self, in_a: bool, in_b: bool) -> bool {
+        match self {
+            CsgOp::Union => in_a || in_b,
+            CsgOp::Intersection => in_a && in_b,
+            CsgOp::Difference => in_a && !in_b,
+        }
+    }
+}
+
+/// Constructive solid geometry details
+///
+/// Both objects must be closed so that the intersections along a ray alternate between
+/// entering and leaving them.
+#[derive(Debug)]
+pub struct Csg<'a> {
+    op: CsgOp,
+    a: HittableRef<'a>,
+    b: HittableRef<'a>,
+    bbox: Aabb,
+}
+
+impl<'a> Csg<'a> {
+    /// Creates the union of two objects
+    pub fn new_union(a: impl Hittable<'a> + 'a, b: impl Hittable<'a> + 'a) -> Self {
+        Self::new(CsgOp::Union, a, b)
+    }
+
+    /// Creates the intersection of two objects
+    pub fn new_intersection(a: impl Hittable<'a> + 'a, b: impl Hittable<'a> + 'a) -> Self {
+        Self::new(CsgOp::Intersection, a, b)
+    }
+
+    /// Creates the difference of two objects (a with b cut away)
+    pub fn new_difference(a: impl Hittable<'a> + 'a, b: impl Hittable<'a> + 'a) -> Self {
+        Self::new(CsgOp::Difference, a, b)
+    }
+
+    /// Creates a new operation on two objects
+    pub fn new(op: CsgOp, a: impl Hittable<'a> + 'a, b: impl Hittable<'a> + 'a) -> Self {
+        Self::new_with_hittable_ref(op, HittableRef::boxed(a), HittableRef::boxed(b))
+    }
+
+    /// Creates a new operation on two object references
+    pub fn new_with_hittable_ref(op: CsgOp, a: HittableRef<'a>, b: HittableRef<'a>) -> Self {
+        let bbox = match op {
+            CsgOp::Union => Aabb::new_from_bbox(a.bounding_box(), b.bounding_box()),
+            CsgOp::Intersection => {
+                let (a_box, b_box) = (a.bounding_box(), b.bounding_box());
+
+                // Overlap of the two boxes
+                let range = |axis: usize| {
+                    let start = a_box.ranges[axis].start.max(b_box.ranges[axis].start);
+                    let end = a_box.ranges[axis].end.min(b_box.ranges[axis].end);
+
+                    start..end.max(start)
+                };
+
+                Aabb::new_from_ranges(range(0), range(1), range(2))
+            }
+            CsgOp::Difference => a.bounding_box().clone(),
+        };
+
+        Self { op, a, b, bbox }
+    }
+}
+
+impl<'a> Hittable<'a> for Csg<'a> {
+    fn hit(&self, rng: &mut ThreadRng, ray: &Ray, t_range: Range<Flt>) -> Option<Hit> {
+        self.hits(rng, ray, t_range).into_iter().next()
+    }
+
+    fn hits(&self, rng: &mut ThreadRng, ray: &Ray, t_range: Range<Flt>) -> Vec<Hit> {
+        // Find all boundary crossings beyond the start of the range
+        let a_hits = self.a.hits(rng, ray, t_range.start..flt_max());
+        let b_hits = self.b.hits(rng, ray, t_range.start..flt_max());
+
+        // If the first crossing leaves an object then the ray starts inside it
+        let mut in_a = a_hits.first().is_some_and(|hit| !hit.front_face);
+        let mut in_b = b_hits.first().is_some_and(|hit| !hit.front_face);
+
+        let mut inside = self.op.inside(in_a, in_b);
+
+        // Merge the crossings in order of distance
+        let mut events: Vec<(Hit, bool)> = a_hits
+            .into_iter()
+            .map(|hit| (hit, true))
+            .chain(b_hits.into_iter().map(|hit| (hit, false)))
+            .collect();
+
+        events.sort_by(|(h1, _), (h2, _)| h1.t.partial_cmp(&h2.t).expect("Invalid float in sort"));
+
+        let mut result = Vec::new();
+
+        for (mut hit, from_a) in events {
+            if hit.t >= t_range.end {
+                break;
+            }
+
+            if from_a {
+                in_a = hit.front_face;
+            } else {
+                in_b = hit.front_face;
+            }
+
+            let now_inside = self.op.inside(in_a, in_b);
+
+            if now_inside != inside {
+                // Boundary of the combined object. The normal still faces the ray, but the
+                // outward side may be flipped (eg the inside of b for a difference)
+                hit.front_face = now_inside;
+                inside = now_inside;
+
+                result.push(hit);
+            }
+        }
+
+        result
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        &self.bbox
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::thread_rng;
+
+    use crate::{
+        hits::hittable::T_MIN,
+        materials::lambertian::Lambertian,
+        shapes::{quad::Quad, sphere::Sphere},
+        triple::{Colour, Point3, Vec3},
+    };
+
+    use super::*;
+
+    /// Returns the distances and front face flags of the crossings of a ray along the x axis
+    /// through the CSG of two overlapping spheres centred at x = 0 and x = 1
+    fn crossings(op: CsgOp, origin_x: FltPrim) -> Vec<(FltPrim, bool)> {
+        let material = Lambertian::new_with_colour(Colour::new(0.5, 0.5, 0.5));
+
+        let csg = Csg::new(
+            op,
+            Sphere::new(Point3::new(0.0, 0.0, 0.0), 1.0, &material),
+            Sphere::new(Point3::new(1.0, 0.0, 0.0), 1.0, &material),
+        );
+
+        let ray = Ray::new(
+            Point3::new(origin_x, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            flt(0.0),
+        );
+
+        csg.hits(&mut thread_rng(), &ray, flt(T_MIN)..flt_max())
+            .into_iter()
+            .map(|hit| (flt_prim(hit.t), hit.front_face))
+            .collect()
+    }
+
+    fn assert_crossings(actual: Vec<(FltPrim, bool)>, expected: &[(FltPrim, bool)]) {
+        assert_eq!(actual.len(), expected.len(), "{actual:?}");
+
+        for ((t, front), (exp_t, exp_front)) in actual.iter().zip(expected) {
+            assert!((t - exp_t).abs() < 1e-4, "{actual:?}");
+            assert_eq!(front, exp_front, "{actual:?}");
+        }
+    }
+
+    #[test]
+    fn test_union() {
+        assert_crossings(crossings(CsgOp::Union, -5.0), &[(4.0, true), (7.0, false)]);
+    }
+
+    #[test]
+    fn test_intersection() {
+        assert_crossings(
+            crossings(CsgOp::Intersection, -5.0),
+            &[(5.0, true), (6.0, false)],
+        );
+    }
+
+    #[test]
+    fn test_difference() {
+        assert_crossings(
+            crossings(CsgOp::Difference, -5.0),
+            &[(4.0, true), (5.0, false)],
+        );
+    }
+
+    #[test]
+    fn test_start_inside() {
+        // Starting inside both spheres
+        assert_crossings(crossings(CsgOp::Union, 0.5), &[(1.5, false)]);
+        assert_crossings(crossings(CsgOp::Intersection, 0.5), &[(0.5, false)]);
+        assert_crossings(crossings(CsgOp::Difference, 0.5), &[]);
+    }
+
+    #[test]
+    fn test_distant_hits() {
+        let material = Lambertian::new_with_colour(Colour::new(0.5, 0.5, 0.5));
+
+        // Quad far enough away that a fixed step is lost to rounding with 32 bit floats
+        let quad = Quad::new(
+            Point3::new(100000.0, -1.0, -1.0),
+            Vec3::new(0.0, 2.0, 0.0),
+            Vec3::new(0.0, 0.0, 2.0),
+            &material,
+        );
+
+        let ray = Ray::new(
+            Point3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            flt(0.0),
+        );
+
+        let hits = quad.hits(&mut thread_rng(), &ray, flt(T_MIN)..flt_max());
+
+        assert_eq!(hits.len(), 1);
+    }
+}
@@ -0,0 +1,218 @@
+//! Cylinder shape
+
+use std::ops::Range;
+
+use rand::rngs::ThreadRng;
+
+use crate::{
+    float::*,
+    hits::{aabb::Aabb, hit::Hit, hittable::Hittable},
+    materials::material::{MatRef, Material},
+    ray::Ray,
+    triple::{Point3, Vec3},
+};
+
+use super::frame::{azimuth, disk_tangents, Frame, LocalHit};
+
+/// Cylinder details
+#[derive(Debug)]
+pub struct Cylinder<'a> {
+    /// Centre of the base at time 0
+    base0: Point3,
+    /// Is moving?
+    moving: bool,
+    /// Movement per time unit
+    movement: Vec3,
+    /// Frame with z along the axis
+    frame: Frame,
+    /// Height along the axis
+    height: Flt,
+    /// Radius
+    radius: Flt,
+    /// Are the ends closed?
+    capped: bool,
+    /// Material to use
+    material: MatRef<'a>,
+    /// Bounding box
+    bbox: Aabb,
+}
+
+impl<'a> Cylinder<'a> {
+    /// Creates a new capped cylinder from the centre of the base, an axis vector from the base
+    /// to the top and a radius. Material object
+    pub fn new(base: Point3, axis: Vec3, radius: FltPrim, material: &'a dyn Material) -> Self {
+        Self::new_moving(base.clone(), base, axis, radius, material)
+    }
+
+    /// Creates a new capped cylinder from the centre of the base, an axis vector from the base
+    /// to the top and a radius. Material reference
+    pub fn new_with_matref(
+        base: Point3,
+        axis: Vec3,
+        radius: FltPrim,
+        material: MatRef<'a>,
+    ) -> Self {
+        Self::new_moving_with_matref(base.clone(), base, axis, radius, material)
+    }
+
+    /// Creates a new moving capped cylinder with given base centres, axis vector and radius.
+    /// Material object
+    pub fn new_moving(
+        base0: Point3,
+        base1: Point3,
+        axis: Vec3,
+        radius: FltPrim,
+        material: &'a dyn Material,
+    ) -> Self {
+        Self::new_moving_with_matref(base0, base1, axis, radius, MatRef::Borrow(material))
+    }
+
+    /// Creates a new moving capped cylinder with given base centres, axis vector and radius.
+    /// Material reference
+    pub fn new_moving_with_matref(
+        base0: Point3,
+        base1: Point3,
+        axis: Vec3,
+        radius: FltPrim,
+        material: MatRef<'a>,
+    ) -> Self {
+        let movement = base0.vec_to(&base1);
+        let moving = movement.length() > 0.0;
+
+        let frame = Frame::new(&axis);
+        let radius = flt(radius);
+
+        let bbox = Aabb::new_from_bbox(
+            &Self::calc_bbox(&base0, &axis, &frame, radius),
+            &Self::calc_bbox(&base1, &axis, &frame, radius),
+        );
+
+        Self {
+            base0,
+            moving,
+            movement,
+            frame,
+            height: axis.length(),
+            radius,
+            capped: true,
+            material,
+            bbox,
+        }
+    }
+
+    /// Sets whether the ends of the cylinder are closed
+    pub fn set_capped(&mut self, capped: bool) {
+        self.capped = capped;
+    }
+
+    fn calc_bbox(base: &Point3, axis: &Vec3, frame: &Frame, radius: Flt) -> Aabb {
+        let extent = frame.disk_extent(radius);
+        let top = base + axis;
+
+        let bottom_box = Aabb::new_from_points(&(base - &extent), &(base + &extent));
+        let top_box = Aabb::new_from_points(&(&top - &extent), &(&top + &extent));
+
+        Aabb::new_from_bbox(&bottom_box, &top_box)
+    }
+
+    fn position_at_time(&self, time: Flt) -> Point3 {
+        if self.moving {
+            &self.base0 + (time * &self.movement)
+        } else {
+            self.base0.clone()
+        }
+    }
+
+    /// Finds the nearest intersection in the local frame
+    fn intersect(&self, origin: &Vec3, direction: &Vec3, t_range: &Range<Flt>) -> Option<LocalHit> {
+        let mut nearest = None;
+        let mut closest = t_range.end;
+
+        // Curved side
+        let a = direction.x() * direction.x() + direction.y() * direction.y();
+
+        if a > 1e-12 {
+            let h = origin.x() * direction.x() + origin.y() * direction.y();
+            let c = origin.x() * origin.x() + origin.y() * origin.y() - self.radius * self.radius;
+
+            let discriminant = h * h - a * c;
+
+            if discriminant >= 0.0 {
+                let sqrtd = discriminant.sqrt();
+
+                for t in [(-h - sqrtd) / a, (-h + sqrtd) / a] {
+                    if t < t_range.start || t >= closest {
+                        continue;
+                    }
+
+                    let local = origin + (t * direction);
+
+                    if local.z() < 0.0 || local.z() > self.height {
+                        continue;
+                    }
+
+                    closest = t;
+                    nearest = Some(LocalHit {
+                        t,
+                        normal: Vec3::new_flt(local.x(), local.y(), flt(0.0)) / self.radius,
+                        u: azimuth(&local),
+                        v: local.z() / self.height,
+                        dpdu: Vec3::new_flt(-local.y(), local.x(), flt(0.0)) * flt(2.0 * PI),
+                        dpdv: Vec3::new_flt(flt(0.0), flt(0.0), self.height),
+                    });
+                }
+            }
+        }
+
+        // End caps
+        if self.capped && direction.z().abs() > 1e-8 {
+            for (z, normal_z) in [(flt(0.0), flt(-1.0)), (self.height, flt(1.0))] {
+                let t = (z - origin.z()) / direction.z();
+
+                if t < t_range.start || t >= closest {
+                    continue;
+                }
+
+                let local = origin + (t * direction);
+                let rho = (local.x() * local.x() + local.y() * local.y()).sqrt();
+
+                if rho > self.radius {
+                    continue;
+                }
+
+                let (dpdu, dpdv) = disk_tangents(&local, self.radius);
+
+                closest = t;
+                nearest = Some(LocalHit {
+                    t,
+                    normal: Vec3::new_flt(flt(0.0), flt(0.0), normal_z),
+                    u: azimuth(&local),
+                    v: rho / self.radius,
+                    dpdu,
+                    dpdv,
+                });
+            }
+        }
+
+        nearest
+    }
+}
+
+impl<'a> Hittable<'a> for Cylinder<'a> {
+    fn hit(&self, rng: &mut ThreadRng, ray: &Ray, t_range: Range<Flt>) -> Option<Hit> {
+        let base = self.position_at_time(ray.motion_time());
+
+        // Work in the cylinder's frame
+        let origin = self.frame.to_local(&base.vec_to(ray.origin()));
+        let direction = self.frame.to_local(ray.direction());
+
+        let local_hit = self.intersect(&origin, &direction, &t_range)?;
+
+        self.frame
+            .to_world_hit(rng, ray, local_hit, self.material.get_ref(), self.name())
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        &self.bbox
+    }
+}
@@ -0,0 +1,146 @@
+//! Disk shape
+
+use std::ops::Range;
+
+use rand::rngs::ThreadRng;
+
+use crate::{
+    float::*,
+    hits::{aabb::Aabb, hit::Hit, hittable::Hittable},
+    materials::material::{MatRef, Material},
+    ray::Ray,
+    triple::{Point3, Vec3},
+};
+
+use super::frame::{azimuth, disk_tangents, Frame, LocalHit};
+
+/// Disk details
+#[derive(Debug)]
+pub struct Disk<'a> {
+    /// Centre at time 0
+    center0: Point3,
+    /// Is moving?
+    moving: bool,
+    /// Movement per time unit
+    movement: Vec3,
+    /// Frame with z along the normal
+    frame: Frame,
+    /// Radius
+    radius: Flt,
+    /// Material to use
+    material: MatRef<'a>,
+    /// Bounding box
+    bbox: Aabb,
+}
+
+impl<'a> Disk<'a> {
+    /// Creates a new disk with a given centre, normal and radius. Material object
+    pub fn new(center: Point3, normal: Vec3, radius: FltPrim, material: &'a dyn Material) -> Self {
+        Self::new_moving(center.clone(), center, normal, radius, material)
+    }
+
+    /// Creates a new disk with a given centre, normal and radius. Material reference
+    pub fn new_with_matref(
+        center: Point3,
+        normal: Vec3,
+        radius: FltPrim,
+        material: MatRef<'a>,
+    ) -> Self {
+        Self::new_moving_with_matref(center.clone(), center, normal, radius, material)
+    }
+
+    /// Creates a new moving disk with given centres, normal and radius. Material object
+    pub fn new_moving(
+        center0: Point3,
+        center1: Point3,
+        normal: Vec3,
+        radius: FltPrim,
+        material: &'a dyn Material,
+    ) -> Self {
+        Self::new_moving_with_matref(center0, center1, normal, radius, MatRef::Borrow(material))
+    }
+
+    /// Creates a new moving disk with given centres, normal and radius. Material reference
+    pub fn new_moving_with_matref(
+        center0: Point3,
+        center1: Point3,
+        normal: Vec3,
+        radius: FltPrim,
+        material: MatRef<'a>,
+    ) -> Self {
+        let movement = center0.vec_to(&center1);
+        let moving = movement.length() > 0.0;
+
+        let frame = Frame::new(&normal);
+        let radius = flt(radius);
+
+        let extent = frame.disk_extent(radius);
+
+        let box1 = Aabb::new_from_points(&(&center0 - &extent), &(&center0 + &extent));
+        let box2 = Aabb::new_from_points(&(&center1 - &extent), &(&center1 + &extent));
+
+        Self {
+            center0,
+            moving,
+            movement,
+            frame,
+            radius,
+            material,
+            bbox: Aabb::new_from_bbox(&box1, &box2),
+        }
+    }
+
+    fn position_at_time(&self, time: Flt) -> Point3 {
+        if self.moving {
+            &self.center0 + (time * &self.movement)
+        } else {
+            self.center0.clone()
+        }
+    }
+}
+
+impl<'a> Hittable<'a> for Disk<'a> {
+    fn hit(&self, rng: &mut ThreadRng, ray: &Ray, t_range: Range<Flt>) -> Option<Hit> {
+        let center = self.position_at_time(ray.motion_time());
+
+        // Work in the disk's frame
+        let origin = self.frame.to_local(&center.vec_to(ray.origin()));
+        let direction = self.frame.to_local(ray.direction());
+
+        // No hit if the ray is parallel to the disk
+        if direction.z().abs() < 1e-8 {
+            return None;
+        }
+
+        let t = -origin.z() / direction.z();
+
+        if !t_range.contains(&t) {
+            return None;
+        }
+
+        let local = &origin + (t * &direction);
+        let rho = (local.x() * local.x() + local.y() * local.y()).sqrt();
+
+        if rho > self.radius {
+            return None;
+        }
+
+        let (dpdu, dpdv) = disk_tangents(&local, self.radius);
+
+        let local_hit = LocalHit {
+            t,
+            normal: Vec3::new(0.0, 0.0, 1.0),
+            u: azimuth(&local),
+            v: rho / self.radius,
+            dpdu,
+            dpdv,
+        };
+
+        self.frame
+            .to_world_hit(rng, ray, local_hit, self.material.get_ref(), self.name())
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        &self.bbox
+    }
+}
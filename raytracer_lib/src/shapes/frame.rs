@@ -0,0 +1,132 @@
+//! Local coordinate frame for shapes defined around an axis
+
+use rand::rngs::ThreadRng;
+
+use crate::{float::*, hits::hit::Hit, materials::material::Material, ray::Ray, triple::Vec3};
+
+/// Orthonormal basis with the z axis along a given direction
+#[derive(Debug, Clone)]
+pub struct Frame {
+    x: Vec3,
+    y: Vec3,
+    z: Vec3,
+}
+
+impl Frame {
+    /// Creates a new frame with the z axis along a given direction
+    pub fn new(axis: &Vec3) -> Self {
+        let z = axis.unit_vector();
+
+        // Pick an axis not parallel to z
+        let other = if z.x().abs() > 0.9 {
+            Vec3::new(0.0, 1.0, 0.0)
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+
+        let x = other.cross(&z).unit_vector();
+        let y = z.cross(&x);
+
+        Self { x, y, z }
+    }
+
+    /// Returns the z axis of the frame
+    pub fn axis(&self) -> &Vec3 {
+        &self.z
+    }
+
+    /// Converts a world space vector to the frame
+    pub fn to_local(&self, v: &Vec3) -> Vec3 {
+        Vec3::new_flt(v.dot(&self.x), v.dot(&self.y), v.dot(&self.z))
+    }
+
+    /// Converts a vector in the frame to world space
+    pub fn to_world(&self, v: &Vec3) -> Vec3 {
+        (v.x() * &self.x) + (v.y() * &self.y) + (v.z() * &self.z)
+    }
+
+    /// Returns the half widths along each world axis of a disk of a given radius perpendicular
+    /// to the frame axis
+    pub fn disk_extent(&self, radius: Flt) -> Vec3 {
+        let extent = |c: Flt| radius * (flt(1.0) - c * c).max(flt(0.0)).sqrt();
+
+        Vec3::new_flt(extent(self.z.x()), extent(self.z.y()), extent(self.z.z()))
+    }
+}
+
+/// Intersection details in a local frame
+#[derive(Debug, Clone)]
+pub struct LocalHit {
+    /// The distance to the intersection
+    pub t: Flt,
+    /// The outward normal in the frame
+    pub normal: Vec3,
+    /// The x position of the intersection on the surface 0.0-1.0
+    pub u: Flt,
+    /// The y position of the intersection on the surface 0.0-1.0
+    pub v: Flt,
+    /// Surface tangent in the direction of increasing u in the frame
+    pub dpdu: Vec3,
+    /// Surface tangent in the direction of increasing v in the frame
+    pub dpdv: Vec3,
+}
+
+impl Frame {
+    /// Converts an intersection in the frame to a world space hit of a named object, if the
+    /// material registers it
+    pub fn to_world_hit<'a>(
+        &self,
+        rng: &mut ThreadRng,
+        ray: &Ray,
+        local: LocalHit,
+        material: &'a dyn Material,
+        object: &'static str,
+    ) -> Option<Hit<'a>> {
+        let p = ray.at(local.t);
+
+        // Check material registers a hit
+        if !material.hit(rng, local.u, local.v, &p) {
+            return None;
+        }
+
+        let mut hit = Hit::new(
+            p,
+            local.t,
+            local.u,
+            local.v,
+            ray,
+            &self.to_world(&local.normal),
+            material,
+            object,
+        );
+
+        hit.set_tangents(self.to_world(&local.dpdu), self.to_world(&local.dpdv));
+
+        Some(hit)
+    }
+}
+
+/// Returns the angle around the z axis of a local point scaled to 0..1
+pub fn azimuth(p: &Vec3) -> Flt {
+    let u = p.y().atan2(p.x()) / flt(2.0 * PI);
+
+    if u < 0.0 {
+        u + flt(1.0)
+    } else {
+        u
+    }
+}
+
+/// Returns the tangents of a disk around the z axis at a local point (zero at the centre)
+pub fn disk_tangents(local: &Vec3, radius: Flt) -> (Vec3, Vec3) {
+    let rho = (local.x() * local.x() + local.y() * local.y()).sqrt();
+
+    if rho > 0.0 {
+        (
+            Vec3::new_flt(-local.y(), local.x(), flt(0.0)) * flt(2.0 * PI),
+            Vec3::new_flt(local.x(), local.y(), flt(0.0)) * (radius / rho),
+        )
+    } else {
+        (Vec3::default(), Vec3::default())
+    }
+}
@@ -0,0 +1,389 @@
+//! Heightfield terrain shape
+
+use std::{ops::Range, path::Path};
+
+use image::ImageReader;
+use rand::rngs::ThreadRng;
+
+use crate::{
+    files::find_file,
+    float::*,
+    hits::{aabb::Aabb, hit::Hit, hittable::Hittable},
+    materials::material::{MatRef, Material},
+    ray::Ray,
+    triple::{Point3, Vec3},
+};
+
+/// Heightfield details
+#[derive(Debug)]
+pub struct Heightfield<'a> {
+    /// Number of samples along x and z
+    dims: [usize; 2],
+    /// Heights above the corner with x varying fastest, then z
+    heights: Vec<Flt>,
+    /// Smoothed normal at each sample
+    normals: Vec<Vec3>,
+    /// Corner of the terrain with the lowest x and z at height 0
+    corner: Point3,
+    /// Size of a grid cell along x and z
+    cell: [Flt; 2],
+    /// Size of the terrain along x and z
+    size: [Flt; 2],
+    /// Material to use
+    material: MatRef<'a>,
+    /// Bounding box
+    bbox: Aabb,
+}
+
+impl<'a> Heightfield<'a> {
+    /// Creates a new heightfield from a grid of heights with x varying fastest, then z. The
+    /// terrain covers the given size along x and z from the corner, and the heights are
+    /// multiplied by the y size. Material object
+    pub fn new(
+        heights: Vec<FltPrim>,
+        dims: [usize; 2],
+        corner: Point3,
+        size: Vec3,
+        material: &'a dyn Material,
+    ) -> Self {
+        Self::new_with_matref(heights, dims, corner, size, MatRef::Borrow(material))
+    }
+
+    /// Creates a new heightfield from a grayscale image, with black at the corner height and
+    /// white at the corner height plus the y size. The top row of the image is at the lowest z.
+    /// Material object
+    pub fn new_from_file(
+        file: &Path,
+        corner: Point3,
+        size: Vec3,
+        material: &'a dyn Material,
+    ) -> Self {
+        Self::new_from_file_with_matref(file, corner, size, MatRef::Borrow(material))
+    }
+
+    /// Creates a new heightfield from a grayscale image, with black at the corner height and
+    /// white at the corner height plus the y size. The top row of the image is at the lowest z.
+    /// Material reference
+    pub fn new_from_file_with_matref(
+        file: &Path,
+        corner: Point3,
+        size: Vec3,
+        material: MatRef<'a>,
+    ) -> Self {
+        let file_path = find_file(file, "images");
+
+        let img = ImageReader::open(file_path).expect("Unable to open image");
+        let dynimg = img.decode().expect("Unable to decode image");
+        let dims = [dynimg.width() as usize, dynimg.height() as usize];
+
+        // Use 16 bits per sample to keep the precision of elevation data
+        let heights = dynimg
+            .into_luma16()
+            .into_vec()
+            .into_iter()
+            .map(|h| h as FltPrim / u16::MAX as FltPrim)
+            .collect();
+
+        Self::new_with_matref(heights, dims, corner, size, material)
+    }
+
+    /// Creates a new heightfield from a grid of heights with x varying fastest, then z. The
+    /// terrain covers the given size along x and z from the corner, and the heights are
+    /// multiplied by the y size. Material reference
+    pub fn new_with_matref(
+        heights: Vec<FltPrim>,
+        dims: [usize; 2],
+        corner: Point3,
+        size: Vec3,
+        material: MatRef<'a>,
+    ) -> Self {
+        assert!(
+            dims.iter().all(|&d| d >= 2),
+            "Heightfield must have at least two samples on each axis"
+        );
+        assert_eq!(
+            heights.len(),
+            dims[0] * dims[1],
+            "Heightfield data does not match dimensions"
+        );
+
+        let heights: Vec<Flt> = heights.into_iter().map(|h| flt(h) * size.y()).collect();
+
+        let cell = [
+            size.x() / flt((dims[0] - 1) as FltPrim),
+            size.z() / flt((dims[1] - 1) as FltPrim),
+        ];
+
+        let normals = Self::calc_normals(&heights, dims, cell);
+
+        let min = heights.iter().fold(flt_max(), |min, &h| min.min(h));
+        let max = heights.iter().fold(flt_min(), |max, &h| max.max(h));
+
+        let bbox = Aabb::new_from_ranges(
+            corner.x()..(corner.x() + size.x()),
+            (corner.y() + min)..(corner.y() + max),
+            corner.z()..(corner.z() + size.z()),
+        );
+
+        Self {
+            dims,
+            heights,
+            normals,
+            corner,
+            cell,
+            size: [size.x(), size.z()],
+            material,
+            bbox,
+        }
+    }
+
+    /// Calculates the normal at each sample from the slopes to its neighbours
+    fn calc_normals(heights: &[Flt], dims: [usize; 2], cell: [Flt; 2]) -> Vec<Vec3> {
+        let height = |x: usize, z: usize| heights[z * dims[0] + x];
+
+        let mut normals = Vec::with_capacity(heights.len());
+
+        for z in 0..dims[1] {
+            for x in 0..dims[0] {
+                let (x0, x1) = (x.saturating_sub(1), (x + 1).min(dims[0] - 1));
+                let (z0, z1) = (z.saturating_sub(1), (z + 1).min(dims[1] - 1));
+
+                let slope_x =
+                    (height(x1, z) - height(x0, z)) / (cell[0] * flt((x1 - x0) as FltPrim));
+                let slope_z =
+                    (height(x, z1) - height(x, z0)) / (cell[1] * flt((z1 - z0) as FltPrim));
+
+                normals.push(Vec3::new_flt(-slope_x, flt(1.0), -slope_z).unit_vector());
+            }
+        }
+
+        normals
+    }
+
+    fn index(&self, x: usize, z: usize) -> usize {
+        z * self.dims[0] + x
+    }
+
+    fn vertex(&self, x: usize, z: usize) -> Point3 {
+        Point3::new_flt(
+            self.corner.x() + flt(x as FltPrim) * self.cell[0],
+            self.corner.y() + self.heights[self.index(x, z)],
+            self.corner.z() + flt(z as FltPrim) * self.cell[1],
+        )
+    }
+
+    /// Returns the grid cell containing a point, clamped to the grid
+    fn cell_at(&self, p: &Point3) -> [usize; 2] {
+        let offsets = [p.x() - self.corner.x(), p.z() - self.corner.z()];
+
+        [0, 1].map(|axis| {
+            let pos = flt_prim((offsets[axis] / self.cell[axis]).floor()).max(0.0);
+
+            (pos as usize).min(self.dims[axis] - 2)
+        })
+    }
+
+    /// Tests the two triangles of a grid cell, returning the distance, vertex indices and
+    /// barycentric coordinates of the nearest hit
+    fn hit_cell(
+        &self,
+        ray: &Ray,
+        cell: [usize; 2],
+        t_range: &Range<Flt>,
+    ) -> Option<(Flt, [usize; 3], Flt, Flt)> {
+        let [x, z] = cell;
+
+        // Triangles wound so that the front face is upwards
+        let triangles = [
+            [(x, z), (x, z + 1), (x + 1, z + 1)],
+            [(x, z), (x + 1, z + 1), (x + 1, z)],
+        ];
+
+        let mut nearest = None;
+        let mut closest = t_range.end;
+
+        for triangle in triangles {
+            let [a, b, c] = triangle.map(|(x, z)| self.vertex(x, z));
+
+            if let Some((t, u, v)) = Self::hit_triangle(ray, &a, &b, &c, &(t_range.start..closest))
+            {
+                closest = t;
+                nearest = Some((t, triangle.map(|(x, z)| self.index(x, z)), u, v));
+            }
+        }
+
+        nearest
+    }
+
+    /// Möller-Trumbore ray triangle intersection returning the distance and the barycentric
+    /// coordinates of b and c
+    fn hit_triangle(
+        ray: &Ray,
+        a: &Point3,
+        b: &Point3,
+        c: &Point3,
+        t_range: &Range<Flt>,
+    ) -> Option<(Flt, Flt, Flt)> {
+        let edge1 = a.vec_to(b);
+        let edge2 = a.vec_to(c);
+
+        let pvec = ray.direction().cross(&edge2);
+        let det = edge1.dot(&pvec);
+
+        if det.abs() < 1e-12 {
+            return None;
+        }
+
+        let inv_det = det.recip();
+
+        let tvec = a.vec_to(ray.origin());
+        let u = tvec.dot(&pvec) * inv_det;
+
+        if !(flt(0.0)..=flt(1.0)).contains(&u) {
+            return None;
+        }
+
+        let qvec = tvec.cross(&edge1);
+        let v = ray.direction().dot(&qvec) * inv_det;
+
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+
+        let t = edge2.dot(&qvec) * inv_det;
+
+        if !t_range.contains(&t) {
+            return None;
+        }
+
+        Some((t, u, v))
+    }
+}
+
+impl<'a> Hittable<'a> for Heightfield<'a> {
+    fn hit(&self, rng: &mut ThreadRng, ray: &Ray, t_range: Range<Flt>) -> Option<Hit> {
+        // Only traverse the part of the ray inside the terrain
+        let range = self.bbox.clip(ray, &t_range)?;
+
+        let direction = [ray.direction().x(), ray.direction().z()];
+
+        // Walk the grid cells along the ray in x and z (2D DDA)
+        let mut cell = self.cell_at(&ray.at(range.start));
+
+        let mut step = [0isize; 2];
+        let mut t_next = [flt_max(); 2];
+        let mut t_delta = [flt_max(); 2];
+
+        for axis in 0..2 {
+            if direction[axis].abs() < 1e-12 {
+                continue;
+            }
+
+            let corner = [self.corner.x(), self.corner.z()][axis];
+            let origin = [ray.origin().x(), ray.origin().z()][axis];
+
+            let boundary = if direction[axis] > 0.0 {
+                step[axis] = 1;
+                corner + flt((cell[axis] + 1) as FltPrim) * self.cell[axis]
+            } else {
+                step[axis] = -1;
+                corner + flt(cell[axis] as FltPrim) * self.cell[axis]
+            };
+
+            t_next[axis] = (boundary - origin) / direction[axis];
+            t_delta[axis] = self.cell[axis] / direction[axis].abs();
+        }
+
+        let mut t_enter = range.start;
+
+        let (t, indices, u, v) = loop {
+            let t_exit = t_next[0].min(t_next[1]).min(range.end);
+
+            // Skip the cell if the ray passes entirely above or below it
+            let y_enter = ray.at(t_enter).y();
+            let y_exit = ray.at(t_exit).y();
+
+            let [x, z] = cell;
+            let cell_heights = [(x, z), (x + 1, z), (x, z + 1), (x + 1, z + 1)]
+                .map(|(x, z)| self.corner.y() + self.heights[self.index(x, z)]);
+
+            let low = cell_heights.iter().fold(flt_max(), |min, &h| min.min(h));
+            let high = cell_heights.iter().fold(flt_min(), |max, &h| max.max(h));
+
+            if y_enter.min(y_exit) <= high && y_enter.max(y_exit) >= low {
+                if let Some(hit) = self.hit_cell(ray, cell, &t_range) {
+                    break hit;
+                }
+            }
+
+            if t_exit >= range.end {
+                return None;
+            }
+
+            // Move to the next cell
+            let axis = if t_next[0] < t_next[1] { 0 } else { 1 };
+
+            let next = cell[axis] as isize + step[axis];
+
+            if next < 0 || next as usize > self.dims[axis] - 2 {
+                return None;
+            }
+
+            cell[axis] = next as usize;
+            t_enter = t_next[axis];
+            t_next[axis] += t_delta[axis];
+        };
+
+        let p = ray.at(t);
+
+        // Texture coordinates span the terrain with v = 1 at the lowest z like an image
+        let tu = clamp((p.x() - self.corner.x()) / self.size[0], flt(0.0), flt(1.0));
+        let tv = clamp(
+            flt(1.0) - (p.z() - self.corner.z()) / self.size[1],
+            flt(0.0),
+            flt(1.0),
+        );
+
+        // Check material registers a hit
+        if !self.material.hit(rng, tu, tv, &p) {
+            return None;
+        }
+
+        // Geometric normal decides the face, interpolated normal is used for shading
+        let [a, b, c] = indices.map(|i| self.vertex(i % self.dims[0], i / self.dims[0]));
+        let geometric = a.vec_to(&b).cross(&a.vec_to(&c)).unit_vector();
+
+        let smooth = ((flt(1.0) - u - v) * &self.normals[indices[0]]
+            + u * &self.normals[indices[1]]
+            + v * &self.normals[indices[2]])
+            .unit_vector();
+
+        let mut hit = Hit::new(
+            p,
+            t,
+            tu,
+            tv,
+            ray,
+            &geometric,
+            self.material.get_ref(),
+            self.name(),
+        );
+
+        hit.normal = if hit.front_face { smooth } else { -smooth };
+
+        // Tangents follow the triangle plane along x and against z
+        let slope_x = -geometric.x() / geometric.y();
+        let slope_z = -geometric.z() / geometric.y();
+
+        hit.set_tangents(
+            Vec3::new_flt(self.size[0], slope_x * self.size[0], flt(0.0)),
+            Vec3::new_flt(flt(0.0), -slope_z * self.size[1], -self.size[1]),
+        );
+
+        Some(hit)
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        &self.bbox
+    }
+}
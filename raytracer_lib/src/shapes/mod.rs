@@ -1,5 +1,16 @@
 //! Shapes
 
 pub mod boxcomp;
+pub mod cone;
+pub mod csg;
+pub mod cylinder;
+pub mod disk;
+pub mod frame;
+pub mod heightfield;
+pub mod plane;
 pub mod quad;
+pub mod sdf;
+pub mod sdf_shape;
 pub mod sphere;
+pub mod torus;
+pub mod triangle;
@@ -0,0 +1,131 @@
+//! Infinite plane shape
+
+use std::ops::Range;
+
+use rand::rngs::ThreadRng;
+
+use crate::{
+    float::*,
+    hits::{aabb::Aabb, hit::Hit, hittable::Hittable},
+    materials::material::{MatRef, Material},
+    ray::Ray,
+    triple::{Point3, Vec3},
+};
+
+use super::frame::{Frame, LocalHit};
+
+/// Infinite plane details
+#[derive(Debug)]
+pub struct Plane<'a> {
+    /// Point on the plane at time 0
+    point0: Point3,
+    /// Is moving?
+    moving: bool,
+    /// Movement per time unit
+    movement: Vec3,
+    /// Frame with z along the normal
+    frame: Frame,
+    /// Material to use
+    material: MatRef<'a>,
+    /// Bounding box (around the point only as the plane is unbounded)
+    bbox: Aabb,
+}
+
+impl<'a> Plane<'a> {
+    /// Creates a new infinite plane through a given point with a given normal. Texture
+    /// coordinates repeat every unit along the plane. Material object
+    pub fn new(point: Point3, normal: Vec3, material: &'a dyn Material) -> Self {
+        Self::new_moving(point.clone(), point, normal, material)
+    }
+
+    /// Creates a new infinite plane through a given point with a given normal. Texture
+    /// coordinates repeat every unit along the plane. Material reference
+    pub fn new_with_matref(point: Point3, normal: Vec3, material: MatRef<'a>) -> Self {
+        Self::new_moving_with_matref(point.clone(), point, normal, material)
+    }
+
+    /// Creates a new moving infinite plane through given points with a given normal. Material
+    /// object
+    pub fn new_moving(
+        point0: Point3,
+        point1: Point3,
+        normal: Vec3,
+        material: &'a dyn Material,
+    ) -> Self {
+        Self::new_moving_with_matref(point0, point1, normal, MatRef::Borrow(material))
+    }
+
+    /// Creates a new moving infinite plane through given points with a given normal. Material
+    /// reference
+    pub fn new_moving_with_matref(
+        point0: Point3,
+        point1: Point3,
+        normal: Vec3,
+        material: MatRef<'a>,
+    ) -> Self {
+        let movement = point0.vec_to(&point1);
+        let moving = movement.length() > 0.0;
+
+        let bbox = Aabb::new_from_points(&point0, &point1);
+
+        Self {
+            point0,
+            moving,
+            movement,
+            frame: Frame::new(&normal),
+            material,
+            bbox,
+        }
+    }
+
+    fn position_at_time(&self, time: Flt) -> Point3 {
+        if self.moving {
+            &self.point0 + (time * &self.movement)
+        } else {
+            self.point0.clone()
+        }
+    }
+}
+
+impl<'a> Hittable<'a> for Plane<'a> {
+    fn hit(&self, rng: &mut ThreadRng, ray: &Ray, t_range: Range<Flt>) -> Option<Hit> {
+        let point = self.position_at_time(ray.motion_time());
+
+        // Work in the plane's frame
+        let origin = self.frame.to_local(&point.vec_to(ray.origin()));
+        let direction = self.frame.to_local(ray.direction());
+
+        // No hit if the ray is parallel to the plane
+        if direction.z().abs() < 1e-8 {
+            return None;
+        }
+
+        let t = -origin.z() / direction.z();
+
+        if !t_range.contains(&t) {
+            return None;
+        }
+
+        let local = &origin + (t * &direction);
+
+        let local_hit = LocalHit {
+            t,
+            normal: Vec3::new(0.0, 0.0, 1.0),
+            u: local.x() - local.x().floor(),
+            v: local.y() - local.y().floor(),
+            dpdu: Vec3::new(1.0, 0.0, 0.0),
+            dpdv: Vec3::new(0.0, 1.0, 0.0),
+        };
+
+        self.frame
+            .to_world_hit(rng, ray, local_hit, self.material.get_ref(), self.name())
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        &self.bbox
+    }
+
+    fn bounded(&self) -> bool {
+        false
+    }
+}
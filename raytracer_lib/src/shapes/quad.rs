@@ -40,12 +40,18 @@ pub struct Quad<'a> {
 }
 
 impl<'a> Quad<'a> {
-    /// Creates a new quadrilateral at a given point and two edge vectors
+    /// Creates a new quadrilateral at a given point and two edge vectors. Material object
     pub fn new(p: Point3, u: Vec3, v: Vec3, material: &'a dyn Material) -> Self {
         Self::new_moving(p.clone(), u.clone(), v.clone(), p, u, v, material)
     }
 
-    /// Creates a new moving quadrilateral given two sets of points and two edge vectors
+    /// Creates a new quadrilateral at a given point and two edge vectors. Material reference
+    pub fn new_with_matref(p: Point3, u: Vec3, v: Vec3, material: MatRef<'a>) -> Self {
+        Self::new_moving_with_matref(p.clone(), u.clone(), v.clone(), p, u, v, material)
+    }
+
+    /// Creates a new moving quadrilateral given two sets of points and two edge vectors.
+    /// Material object
     pub fn new_moving(
         p0: Point3,
         u0: Vec3,
@@ -54,6 +60,20 @@ impl<'a> Quad<'a> {
         u1: Vec3,
         v1: Vec3,
         material: &'a dyn Material,
+    ) -> Self {
+        Self::new_moving_with_matref(p0, u0, v0, p1, u1, v1, MatRef::Borrow(material))
+    }
+
+    /// Creates a new moving quadrilateral given two sets of points and two edge vectors.
+    /// Material reference
+    pub fn new_moving_with_matref(
+        p0: Point3,
+        u0: Vec3,
+        v0: Vec3,
+        p1: Point3,
+        u1: Vec3,
+        v1: Vec3,
+        material: MatRef<'a>,
     ) -> Self {
         // Calculate movement
         let p_movement = p0.vec_to(&p1);
@@ -89,7 +109,7 @@ impl<'a> Quad<'a> {
             u_movement,
             v_movement,
             normal_movement: n_movement,
-            material: MatRef::Borrow(material),
+            material,
             bbox,
         }
     }
@@ -122,7 +142,7 @@ impl<'a> Quad<'a> {
 
 impl<'a> Hittable<'a> for Quad<'a> {
     fn hit(&self, rng: &mut ThreadRng, ray: &Ray, t_range: Range<Flt>) -> Option<Hit> {
-        let (p, u, v, normal) = self.position_at_time(ray.time());
+        let (p, u, v, normal) = self.position_at_time(ray.motion_time());
 
         let dot = normal.dot(&p);
 
@@ -166,7 +186,7 @@ impl<'a> Hittable<'a> for Quad<'a> {
         }
 
         // Ray hits the 2D shape
-        Some(Hit::new(
+        let mut hit = Hit::new(
             intersection,
             t,
             alpha,
@@ -174,7 +194,12 @@ impl<'a> Hittable<'a> for Quad<'a> {
             ray,
             &normal,
             self.material.get_ref(),
-        ))
+            self.name(),
+        );
+
+        hit.set_tangents(u, v);
+
+        Some(hit)
     }
 
     fn bounding_box(&self) -> &Aabb {
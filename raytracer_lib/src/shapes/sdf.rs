@@ -0,0 +1,473 @@
+//! Signed distance functions
+
+use std::fmt::Debug;
+
+use crate::{
+    float::*,
+    hits::aabb::Aabb,
+    perlin::PerlinNoise,
+    triple::{Point3, Vec3},
+};
+
+/// Signed distance function trait
+pub trait Sdf: Debug + Send + Sync {
+    /// Returns the signed distance from a point to the surface (negative inside)
+    fn distance(&self, p: &Point3) -> Flt;
+
+    /// Returns a bounding box enclosing the surface
+    fn bounding_box(&self) -> Aabb;
+
+    /// Returns the maximum rate of change of the distance. Values above 1 indicate that the
+    /// distance is an overestimate and sphere tracing must take shorter steps
+    fn lipschitz(&self) -> Flt {
+        flt(1.0)
+    }
+}
+
+/// Returns a bounding box grown by a given amount on all sides
+fn pad(bbox: &Aabb, amount: Flt) -> Aabb {
+    let range = |axis: usize| (bbox.ranges[axis].start - amount)..(bbox.ranges[axis].end + amount);
+
+    Aabb::new_from_ranges(range(0), range(1), range(2))
+}
+
+/// Sphere distance function
+#[derive(Debug)]
+pub struct SdfSphere {
+    center: Point3,
+    radius: Flt,
+}
+
+impl SdfSphere {
+    /// Creates a new sphere distance function with a given centre and radius
+    pub fn new(center: Point3, radius: FltPrim) -> Self {
+        Self {
+            center,
+            radius: flt(radius),
+        }
+    }
+}
+
+impl Sdf for SdfSphere {
+    fn distance(&self, p: &Point3) -> Flt {
+        self.center.vec_to(p).length() - self.radius
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        let rvec = Vec3::new_flt(self.radius, self.radius, self.radius);
+
+        Aabb::new_from_points(&(&self.center - &rvec), &(&self.center + &rvec))
+    }
+}
+
+/// Axis aligned box distance function with optionally rounded edges
+#[derive(Debug)]
+pub struct SdfBox {
+    center: Point3,
+    half_size: Vec3,
+    rounding: Flt,
+}
+
+impl SdfBox {
+    /// Creates a new box distance function with a given centre, size and edge rounding radius
+    pub fn new(center: Point3, size: Vec3, rounding: FltPrim) -> Self {
+        Self {
+            center,
+            half_size: size * flt(0.5),
+            rounding: flt(rounding),
+        }
+    }
+}
+
+impl Sdf for SdfBox {
+    fn distance(&self, p: &Point3) -> Flt {
+        let local = self.center.vec_to(p);
+
+        let q = [0, 1, 2].map(|axis| local[axis].abs() - self.half_size[axis] + self.rounding);
+
+        let outside = Vec3::new_from_array(q.map(|v| v.max(flt(0.0)))).length();
+        let inside = q[0].max(q[1]).max(q[2]).min(flt(0.0));
+
+        outside + inside - self.rounding
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        Aabb::new_from_points(
+            &(&self.center - &self.half_size),
+            &(&self.center + &self.half_size),
+        )
+    }
+}
+
+/// Torus distance function with the axis of symmetry along y
+#[derive(Debug)]
+pub struct SdfTorus {
+    center: Point3,
+    major_radius: Flt,
+    minor_radius: Flt,
+}
+
+impl SdfTorus {
+    /// Creates a new torus distance function with a given centre, major (ring) radius and minor
+    /// (tube) radius
+    pub fn new(center: Point3, major_radius: FltPrim, minor_radius: FltPrim) -> Self {
+        Self {
+            center,
+            major_radius: flt(major_radius),
+            minor_radius: flt(minor_radius),
+        }
+    }
+}
+
+impl Sdf for SdfTorus {
+    fn distance(&self, p: &Point3) -> Flt {
+        let local = self.center.vec_to(p);
+
+        let rho = (local.x() * local.x() + local.z() * local.z()).sqrt() - self.major_radius;
+
+        (rho * rho + local.y() * local.y()).sqrt() - self.minor_radius
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        let outer = self.major_radius + self.minor_radius;
+        let extent = Vec3::new_flt(outer, self.minor_radius, outer);
+
+        Aabb::new_from_points(&(&self.center - &extent), &(&self.center + &extent))
+    }
+}
+
+/// Smooth union of two distance functions
+#[derive(Debug)]
+pub struct SmoothUnion {
+    a: Box<dyn Sdf>,
+    b: Box<dyn Sdf>,
+    smoothness: Flt,
+}
+
+impl SmoothUnion {
+    /// Creates a smooth union of two distance functions. The smoothness is the distance over
+    /// which the surfaces are blended
+    pub fn new(a: impl Sdf + 'static, b: impl Sdf + 'static, smoothness: FltPrim) -> Self {
+        Self {
+            a: Box::new(a),
+            b: Box::new(b),
+            smoothness: flt(smoothness),
+        }
+    }
+}
+
+impl Sdf for SmoothUnion {
+    fn distance(&self, p: &Point3) -> Flt {
+        let d1 = self.a.distance(p);
+        let d2 = self.b.distance(p);
+
+        if self.smoothness <= 0.0 {
+            return d1.min(d2);
+        }
+
+        // Polynomial smooth minimum
+        let h = clamp(
+            flt(0.5) + flt(0.5) * (d2 - d1) / self.smoothness,
+            flt(0.0),
+            flt(1.0),
+        );
+
+        d2 + (d1 - d2) * h - self.smoothness * h * (flt(1.0) - h)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        // The blend can extend the surface by up to a quarter of the smoothness
+        pad(
+            &Aabb::new_from_bbox(&self.a.bounding_box(), &self.b.bounding_box()),
+            self.smoothness * flt(0.25),
+        )
+    }
+
+    fn lipschitz(&self) -> Flt {
+        self.a.lipschitz().max(self.b.lipschitz())
+    }
+}
+
+/// Limited repetition of a distance function
+#[derive(Debug)]
+pub struct Repeat {
+    inner: Box<dyn Sdf>,
+    period: Vec3,
+    counts: [u32; 3],
+}
+
+impl Repeat {
+    /// Creates a grid of copies of a distance function spaced by a given period along each
+    /// axis. The counts give the number of extra copies either side of the original. The
+    /// original should fit within a single period for the distance to remain a bound
+    pub fn new(inner: impl Sdf + 'static, period: Vec3, counts: [u32; 3]) -> Self {
+        Self {
+            inner: Box::new(inner),
+            period,
+            counts,
+        }
+    }
+}
+
+impl Sdf for Repeat {
+    fn distance(&self, p: &Point3) -> Flt {
+        let local = [0, 1, 2].map(|axis| {
+            let period = self.period[axis];
+
+            if period <= 0.0 {
+                return p[axis];
+            }
+
+            let count = flt(self.counts[axis] as FltPrim);
+            let cell = clamp((p[axis] / period).round(), -count, count);
+
+            p[axis] - period * cell
+        });
+
+        self.inner.distance(&Point3::new_from_array(local))
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        let inner = self.inner.bounding_box();
+
+        let range = |axis: usize| {
+            let offset = self.period[axis].abs() * flt(self.counts[axis] as FltPrim);
+
+            (inner.ranges[axis].start - offset)..(inner.ranges[axis].end + offset)
+        };
+
+        Aabb::new_from_ranges(range(0), range(1), range(2))
+    }
+
+    fn lipschitz(&self) -> Flt {
+        self.inner.lipschitz()
+    }
+}
+
+/// Twist of a distance function about the y axis
+#[derive(Debug)]
+pub struct Twist {
+    inner: Box<dyn Sdf>,
+    rate: Flt,
+    radius: Flt,
+}
+
+impl Twist {
+    /// Creates a twist of a distance function about the y axis by a given angle in radians per
+    /// unit of height
+    pub fn new(inner: impl Sdf + 'static, rate: FltPrim) -> Self {
+        let bbox = inner.bounding_box();
+
+        // Furthest distance of the surface from the axis
+        let radius =
+            [&bbox.ranges[0], &bbox.ranges[2]].map(|range| range.start.abs().max(range.end.abs()));
+        let radius = (radius[0] * radius[0] + radius[1] * radius[1]).sqrt();
+
+        Self {
+            inner: Box::new(inner),
+            rate: flt(rate),
+            radius,
+        }
+    }
+}
+
+impl Sdf for Twist {
+    fn distance(&self, p: &Point3) -> Flt {
+        let (sin, cos) = (self.rate * p.y()).sin_cos();
+
+        let local = Point3::new_flt(cos * p.x() - sin * p.z(), p.y(), sin * p.x() + cos * p.z());
+
+        self.inner.distance(&local)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        let inner = self.inner.bounding_box();
+
+        Aabb::new_from_ranges(
+            -self.radius..self.radius,
+            inner.ranges[1].clone(),
+            -self.radius..self.radius,
+        )
+    }
+
+    fn lipschitz(&self) -> Flt {
+        let shear = self.rate * self.radius;
+
+        self.inner.lipschitz() * (flt(1.0) + shear * shear).sqrt()
+    }
+}
+
+/// Displacement of a distance function by Perlin noise
+#[derive(Debug)]
+pub struct Displace {
+    inner: Box<dyn Sdf>,
+    noise: PerlinNoise,
+    scale: Flt,
+    amplitude: Flt,
+}
+
+impl Displace {
+    /// Creates a displacement of a distance function by Perlin noise with a given spatial scale
+    /// and amplitude
+    pub fn new(inner: impl Sdf + 'static, scale: FltPrim, amplitude: FltPrim) -> Self {
+        Self {
+            inner: Box::new(inner),
+            noise: PerlinNoise::new(),
+            scale: flt(scale),
+            amplitude: flt(amplitude),
+        }
+    }
+}
+
+impl Sdf for Displace {
+    fn distance(&self, p: &Point3) -> Flt {
+        self.inner.distance(p) + self.amplitude * self.noise.noise(&(p * self.scale))
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        pad(&self.inner.bounding_box(), self.amplitude.abs())
+    }
+
+    fn lipschitz(&self) -> Flt {
+        // Perlin noise changes by at most about 3 per unit
+        self.inner.lipschitz() + flt(3.0) * (self.amplitude * self.scale).abs()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{thread_rng, Rng};
+
+    use super::*;
+
+    fn assert_distance(sdf: &impl Sdf, p: Point3, expected: FltPrim) {
+        let distance = flt_prim(sdf.distance(&p));
+
+        assert!(
+            (distance - expected).abs() < 1e-4,
+            "{distance} != {expected}"
+        );
+    }
+
+    /// Checks that points outside the bounding box are outside the surface
+    fn assert_bounded(sdf: &impl Sdf) {
+        let bbox = sdf.bounding_box();
+        let mut rng = thread_rng();
+
+        for _ in 0..1000 {
+            let p = Point3::new_from_array([0, 1, 2].map(|axis| {
+                let range = &bbox.ranges[axis];
+                let size = range.end - range.start;
+
+                range.start + size * flt(rng.gen_range(-0.5..1.5))
+            }));
+
+            let inside = (0..3).all(|axis| bbox.ranges[axis].contains(&p[axis]));
+
+            if !inside {
+                assert!(sdf.distance(&p) > 0.0, "{p} is inside the surface");
+            }
+        }
+    }
+
+    #[test]
+    fn test_sphere() {
+        let sphere = SdfSphere::new(Point3::new(1.0, 2.0, 3.0), 2.0);
+
+        assert_distance(&sphere, Point3::new(1.0, 2.0, 3.0), -2.0);
+        assert_distance(&sphere, Point3::new(3.0, 2.0, 3.0), 0.0);
+        assert_distance(&sphere, Point3::new(1.0, 2.0, 8.0), 3.0);
+        assert_bounded(&sphere);
+    }
+
+    #[test]
+    fn test_box() {
+        let cube = SdfBox::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(2.0, 4.0, 6.0), 0.0);
+
+        assert_distance(&cube, Point3::new(0.0, 0.0, 0.0), -1.0);
+        assert_distance(&cube, Point3::new(3.0, 0.0, 0.0), 2.0);
+        assert_distance(&cube, Point3::new(2.0, 3.0, 0.0), (2.0 as FltPrim).sqrt());
+        assert_bounded(&cube);
+
+        // Rounded edges are the rounding radius from an inner box
+        let rounded = SdfBox::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(2.0, 2.0, 2.0), 0.5);
+
+        assert_distance(&rounded, Point3::new(1.0, 0.0, 0.0), 0.0);
+        assert_distance(
+            &rounded,
+            Point3::new(1.5, 1.5, 0.0),
+            (2.0 as FltPrim).sqrt() - 0.5,
+        );
+        assert_bounded(&rounded);
+    }
+
+    #[test]
+    fn test_torus() {
+        let torus = SdfTorus::new(Point3::new(0.0, 1.0, 0.0), 3.0, 1.0);
+
+        assert_distance(&torus, Point3::new(3.0, 1.0, 0.0), -1.0);
+        assert_distance(&torus, Point3::new(0.0, 1.0, -4.0), 0.0);
+        assert_distance(&torus, Point3::new(0.0, 1.0, 0.0), 2.0);
+        assert_bounded(&torus);
+    }
+
+    #[test]
+    fn test_smooth_union() {
+        let union = SmoothUnion::new(
+            SdfSphere::new(Point3::new(-1.0, 0.0, 0.0), 1.0),
+            SdfSphere::new(Point3::new(1.0, 0.0, 0.0), 1.0),
+            0.5,
+        );
+
+        // Away from the blend the distance is the nearest surface
+        assert_distance(&union, Point3::new(-3.0, 0.0, 0.0), 1.0);
+
+        // In the blend the surface is pulled outwards
+        assert!(union.distance(&Point3::new(0.0, 1.0, 0.0)) < flt((2.0 as FltPrim).sqrt() - 1.0));
+        assert_bounded(&union);
+    }
+
+    #[test]
+    fn test_repeat() {
+        let repeat = Repeat::new(
+            SdfSphere::new(Point3::new(0.0, 0.0, 0.0), 1.0),
+            Vec3::new(4.0, 0.0, 0.0),
+            [2, 0, 0],
+        );
+
+        assert_distance(&repeat, Point3::new(8.0, 0.0, 0.0), -1.0);
+        assert_distance(&repeat, Point3::new(-6.0, 0.0, 0.0), 1.0);
+
+        // No copies beyond the count
+        assert_distance(&repeat, Point3::new(16.0, 0.0, 0.0), 7.0);
+        assert_bounded(&repeat);
+    }
+
+    #[test]
+    fn test_twist() {
+        let cube = || SdfBox::new(Point3::new(0.0, 0.0, 0.0), Vec3::new(2.0, 4.0, 1.0), 0.0);
+
+        // A quarter turn at y = 1 swaps the x and z extents
+        let twist = Twist::new(cube(), PI / 2.0);
+
+        assert_distance(&twist, Point3::new(0.0, 1.0, 0.5), -0.5);
+        assert_distance(&twist, Point3::new(0.0, 1.0, 1.5), 0.5);
+        assert_distance(&twist, Point3::new(0.0, 0.0, 1.5), 1.0);
+        assert!(twist.lipschitz() > cube().lipschitz());
+        assert_bounded(&twist);
+    }
+
+    #[test]
+    fn test_displace() {
+        let displace = Displace::new(SdfSphere::new(Point3::new(0.0, 0.0, 0.0), 2.0), 3.0, 0.2);
+
+        for x in 0..20 {
+            let p = Point3::new(x as FltPrim * 0.3 - 3.0, 0.1, 0.2);
+            let offset = displace.distance(&p) - displace.inner.distance(&p);
+
+            assert!(offset.abs() <= 0.2 + 1e-6);
+        }
+
+        assert_bounded(&displace);
+    }
+}
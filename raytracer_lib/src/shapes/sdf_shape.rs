@@ -0,0 +1,203 @@
+//! Signed distance function shape rendered by sphere tracing
+
+use std::ops::Range;
+
+use rand::rngs::ThreadRng;
+
+use crate::{
+    float::*,
+    hits::{aabb::Aabb, hit::Hit, hittable::Hittable},
+    materials::material::{MatRef, Material},
+    ray::Ray,
+    triple::{Point3, Vec3},
+};
+
+use super::sdf::Sdf;
+
+/// Maximum number of sphere tracing steps
+const MAX_STEPS: usize = 512;
+
+/// Distance from the surface treated as a hit, relative to the extent of the shape
+const EPSILON: FltPrim = 1e-5;
+
+/// Offset used to estimate the gradient, relative to the extent of the shape
+const GRADIENT_DELTA: FltPrim = 1e-5;
+
+/// Signed distance function shape details
+#[derive(Debug)]
+pub struct SdfShape<'a> {
+    /// Distance function
+    sdf: Box<dyn Sdf>,
+    /// Material to use
+    material: MatRef<'a>,
+    /// Bounding box
+    bbox: Aabb,
+    /// Distance from the surface treated as a hit
+    epsilon: Flt,
+    /// Offset used to estimate the gradient
+    gradient_delta: Flt,
+}
+
+impl<'a> SdfShape<'a> {
+    /// Creates a new shape from a signed distance function. Material object
+    pub fn new(sdf: impl Sdf + 'static, material: &'a dyn Material) -> Self {
+        Self::new_with_matref(sdf, MatRef::Borrow(material))
+    }
+
+    /// Creates a new shape from a signed distance function. Material reference
+    pub fn new_with_matref(sdf: impl Sdf + 'static, material: MatRef<'a>) -> Self {
+        let bbox = sdf.bounding_box();
+
+        // The precision of the distance falls as the coordinates grow, so scale the tolerances
+        // by the largest coordinate in the bounding box
+        let extent = bbox
+            .ranges
+            .iter()
+            .map(|range| range.start.abs().max(range.end.abs()))
+            .fold(flt(1.0), |a, b| a.max(b));
+
+        Self {
+            sdf: Box::new(sdf),
+            material,
+            bbox,
+            epsilon: flt(EPSILON) * extent,
+            gradient_delta: flt(GRADIENT_DELTA) * extent,
+        }
+    }
+
+    /// Estimates the outward normal from the gradient of the distance function
+    fn normal(&self, p: &Point3) -> Option<Vec3> {
+        let delta = self.gradient_delta;
+
+        let gradient = [0, 1, 2].map(|axis| {
+            let mut offset = [flt(0.0); 3];
+            offset[axis] = delta;
+            let offset = Vec3::new_from_array(offset);
+
+            self.sdf.distance(&(p + &offset)) - self.sdf.distance(&(p - &offset))
+        });
+
+        let gradient = Vec3::new_from_array(gradient);
+
+        if gradient.length_squared() <= 0.0 {
+            None
+        } else {
+            Some(gradient.unit_vector())
+        }
+    }
+
+    fn get_uv(&self, p: &Point3) -> (Flt, Flt) {
+        // Spherical coordinates about the centre of the bounding box, as for a sphere
+        let center = Point3::new_from_array(
+            [0, 1, 2].map(|axis| (self.bbox.ranges[axis].start + self.bbox.ranges[axis].end) / 2.0),
+        );
+
+        let dir = center.vec_to(p);
+        let length = dir.length();
+
+        if length <= 0.0 {
+            return (flt(0.5), flt(0.5));
+        }
+
+        let dir = dir / length;
+
+        let theta = clamp(-dir.y(), flt(-1.0), flt(1.0)).acos();
+        let phi = (-dir.z()).atan2(dir.x()) + PI;
+
+        (phi / (2.0 * PI), theta / PI)
+    }
+}
+
+impl<'a> Hittable<'a> for SdfShape<'a> {
+    fn hit(&self, rng: &mut ThreadRng, ray: &Ray, t_range: Range<Flt>) -> Option<Hit> {
+        // Only march inside the bounding box
+        let range = self.bbox.clip(ray, &t_range)?;
+
+        let speed = ray.direction().length();
+        let lipschitz = self.sdf.lipschitz();
+        let epsilon = self.epsilon;
+
+        let mut t = range.start;
+        let mut distance = self.sdf.distance(&ray.at(t));
+        let mut steps = 0;
+
+        // Step off the surface if the ray starts on it (eg after scattering). A ray entering
+        // the bounding box may meet the surface straight away, which is a hit
+        while range.start == t_range.start && distance.abs() < epsilon && steps < MAX_STEPS {
+            t += epsilon * 2.0 / speed;
+            distance = self.sdf.distance(&ray.at(t));
+            steps += 1;
+        }
+
+        // Which side of the surface the ray starts on
+        let side = if distance < 0.0 { flt(-1.0) } else { flt(1.0) };
+
+        while steps < MAX_STEPS && t < range.end {
+            let step = side * distance;
+
+            if step < epsilon {
+                let p = ray.at(t);
+                let outward_normal = self.normal(&p).unwrap_or_else(|| -ray.direction());
+
+                let (u, v) = self.get_uv(&p);
+
+                // Check material registers a hit
+                if !self.material.hit(rng, u, v, &p) {
+                    return None;
+                }
+
+                return Some(Hit::new(
+                    p,
+                    t,
+                    u,
+                    v,
+                    ray,
+                    &outward_normal,
+                    self.material.get_ref(),
+                    self.name(),
+                ));
+            }
+
+            t += step / (lipschitz * speed);
+            distance = self.sdf.distance(&ray.at(t));
+            steps += 1;
+        }
+
+        None
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        &self.bbox
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::thread_rng;
+
+    use crate::{hits::hittable::T_MIN, materials::lambertian::Lambertian, triple::Colour};
+
+    use super::{super::sdf::SdfSphere, *};
+
+    #[test]
+    fn test_distant_shape() {
+        let material = Lambertian::new_with_colour(Colour::new(0.5, 0.5, 0.5));
+
+        // Far from the origin the fixed tolerances are below the precision of the distance
+        let center = Point3::new(5000.0, 3000.0, -4000.0);
+        let shape = SdfShape::new(SdfSphere::new(center.clone(), 10.0), &material);
+
+        let ray = Ray::new(
+            Point3::new(5000.0, 3000.0, -3900.0),
+            Vec3::new(0.0, 0.0, -1.0),
+            flt(0.0),
+        );
+
+        let hit = shape
+            .hit(&mut thread_rng(), &ray, flt(T_MIN)..flt_max())
+            .expect("No hit");
+
+        assert!((hit.t - 90.0).abs() < 0.01, "{}", hit.t);
+        assert!(hit.normal.z() > 0.999, "{}", hit.normal);
+    }
+}
@@ -88,28 +88,9 @@ impl<'a> Sphere<'a> {
         }
     }
 
-    fn get_uv(p: &Vec3) -> (Flt, Flt) {
-        // p: a given vector from the centre of the sphere of length 1
-        // u: returned value [0,1] of angle around the Y axis from X=-1
-        // v: returned value [0,1] of angle from Y=-1 to Y=+1
-        //     <1 0 0> yields <0.50 0.50>       <-1  0  0> yields <0.00 0.50>
-        //     <0 1 0> yields <0.50 1.00>       < 0 -1  0> yields <0.50 0.00>
-        //     <0 0 1> yields <0.25 0.50>       < 0  0 -1> yields <0.75 0.50>
-
-        let theta = (-p.y()).acos();
-        let phi = (-p.z()).atan2(p.x()) + PI;
-
-        let u = phi / (2.0 * PI);
-        let v = theta / PI;
-
-        (u, v)
-    }
-}
-
-impl<'a> Hittable<'a> for Sphere<'a> {
-    fn hit(&self, rng: &mut ThreadRng, ray: &Ray, t_range: Range<Flt>) -> Option<Hit> {
-        let center = self.position_at_time(ray.time());
-        let oc = ray.origin().vec_to(&center);
+    /// Returns the near and far intersection distances of a ray with the sphere
+    fn roots(&self, center: &Point3, ray: &Ray) -> Option<(Flt, Flt)> {
+        let oc = ray.origin().vec_to(center);
         let a = ray.direction().length_squared();
         let h = ray.direction().dot(&oc);
         let c = oc.length_squared() - self.radius * self.radius;
@@ -122,17 +103,11 @@ impl<'a> Hittable<'a> for Sphere<'a> {
 
         let sqrtd = discriminant.sqrt();
 
-        // Find the nearest root that lies in the acceptable range.
-        let mut t = (h - sqrtd) / a;
-
-        if !t_range.contains(&t) {
-            t = (h + sqrtd) / a;
-
-            if !t_range.contains(&t) {
-                return None;
-            }
-        }
+        Some(((h - sqrtd) / a, (h + sqrtd) / a))
+    }
 
+    /// Builds the hit details for an intersection distance
+    fn make_hit(&self, rng: &mut ThreadRng, ray: &Ray, center: &Point3, t: Flt) -> Option<Hit> {
         let p = ray.at(t);
         let outward_normal = center.vec_to(&p) / self.radius;
 
@@ -143,7 +118,9 @@ impl<'a> Hittable<'a> for Sphere<'a> {
             return None;
         }
 
-        Some(Hit::new(
+        let (dpdu, dpdv) = self.get_tangents(&outward_normal);
+
+        let mut hit = Hit::new(
             p,
             t,
             u,
@@ -151,7 +128,82 @@ impl<'a> Hittable<'a> for Sphere<'a> {
             ray,
             &outward_normal,
             self.material.get_ref(),
-        ))
+            self.name(),
+        );
+
+        hit.set_tangents(dpdu, dpdv);
+
+        Some(hit)
+    }
+
+    fn get_uv(p: &Vec3) -> (Flt, Flt) {
+        // p: a given vector from the centre of the sphere of length 1
+        // u: returned value [0,1] of angle around the Y axis from X=-1
+        // v: returned value [0,1] of angle from Y=-1 to Y=+1
+        //     <1 0 0> yields <0.50 0.50>       <-1  0  0> yields <0.00 0.50>
+        //     <0 1 0> yields <0.50 1.00>       < 0 -1  0> yields <0.50 0.00>
+        //     <0 0 1> yields <0.25 0.50>       < 0  0 -1> yields <0.75 0.50>
+
+        let theta = (-p.y()).acos();
+        let phi = (-p.z()).atan2(p.x()) + PI;
+
+        let u = phi / (2.0 * PI);
+        let v = theta / PI;
+
+        (u, v)
+    }
+
+    fn get_tangents(&self, n: &Vec3) -> (Vec3, Vec3) {
+        // n: a given vector from the centre of the sphere of length 1
+        // Returns the partial derivatives of the surface point with respect to u and v
+        // (zero at the poles where the parameterisation is degenerate)
+
+        let sin_theta = (flt(1.0) - n.y() * n.y()).max(flt(0.0)).sqrt();
+
+        if sin_theta < 1e-8 {
+            return (Vec3::default(), Vec3::default());
+        }
+
+        let dpdu = Vec3::new_flt(n.z(), flt(0.0), -n.x()) * (flt(2.0 * PI) * self.radius);
+
+        let dpdv = Vec3::new_flt(
+            -n.x() * n.y() / sin_theta,
+            sin_theta,
+            -n.z() * n.y() / sin_theta,
+        ) * (flt(PI) * self.radius);
+
+        (dpdu, dpdv)
+    }
+}
+
+impl<'a> Hittable<'a> for Sphere<'a> {
+    fn hit(&self, rng: &mut ThreadRng, ray: &Ray, t_range: Range<Flt>) -> Option<Hit> {
+        let center = self.position_at_time(ray.motion_time());
+        let (near, far) = self.roots(&center, ray)?;
+
+        // Find the nearest root that lies in the acceptable range.
+        let t = if t_range.contains(&near) {
+            near
+        } else if t_range.contains(&far) {
+            far
+        } else {
+            return None;
+        };
+
+        self.make_hit(rng, ray, &center, t)
+    }
+
+    fn hits(&self, rng: &mut ThreadRng, ray: &Ray, t_range: Range<Flt>) -> Vec<Hit> {
+        let center = self.position_at_time(ray.motion_time());
+
+        match self.roots(&center, ray) {
+            None => Vec::new(),
+            Some((near, far)) => [near, far]
+                .into_iter()
+                .filter(|t| t_range.contains(t))
+                .filter_map(|t| self.make_hit(rng, ray, &center, t))
+                .collect(),
+        }
     }
 
     fn bounding_box(&self) -> &Aabb {
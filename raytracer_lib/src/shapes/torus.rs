@@ -0,0 +1,245 @@
+//! Torus shape
+
+use std::ops::Range;
+
+use rand::rngs::ThreadRng;
+
+use crate::{
+    float::*,
+    hits::{aabb::Aabb, hit::Hit, hittable::Hittable},
+    materials::material::{MatRef, Material},
+    poly::roots_in_range,
+    ray::Ray,
+    triple::{Point3, Vec3},
+};
+
+use super::frame::{azimuth, Frame, LocalHit};
+
+/// Torus details
+#[derive(Debug)]
+pub struct Torus<'a> {
+    /// Centre at time 0
+    center0: Point3,
+    /// Is moving?
+    moving: bool,
+    /// Movement per time unit
+    movement: Vec3,
+    /// Frame with z along the axis of symmetry
+    frame: Frame,
+    /// Distance from the centre to the centre of the tube
+    major_radius: FltPrim,
+    /// Radius of the tube
+    minor_radius: FltPrim,
+    /// Material to use
+    material: MatRef<'a>,
+    /// Bounding box
+    bbox: Aabb,
+}
+
+impl<'a> Torus<'a> {
+    /// Creates a new torus with a given centre, axis of symmetry, major (ring) radius and minor
+    /// (tube) radius. Material object
+    pub fn new(
+        center: Point3,
+        axis: Vec3,
+        major_radius: FltPrim,
+        minor_radius: FltPrim,
+        material: &'a dyn Material,
+    ) -> Self {
+        Self::new_moving(
+            center.clone(),
+            center,
+            axis,
+            major_radius,
+            minor_radius,
+            material,
+        )
+    }
+
+    /// Creates a new torus with a given centre, axis of symmetry, major (ring) radius and minor
+    /// (tube) radius. Material reference
+    pub fn new_with_matref(
+        center: Point3,
+        axis: Vec3,
+        major_radius: FltPrim,
+        minor_radius: FltPrim,
+        material: MatRef<'a>,
+    ) -> Self {
+        Self::new_moving_with_matref(
+            center.clone(),
+            center,
+            axis,
+            major_radius,
+            minor_radius,
+            material,
+        )
+    }
+
+    /// Creates a new moving torus with given centres, axis of symmetry and radii. Material object
+    pub fn new_moving(
+        center0: Point3,
+        center1: Point3,
+        axis: Vec3,
+        major_radius: FltPrim,
+        minor_radius: FltPrim,
+        material: &'a dyn Material,
+    ) -> Self {
+        Self::new_moving_with_matref(
+            center0,
+            center1,
+            axis,
+            major_radius,
+            minor_radius,
+            MatRef::Borrow(material),
+        )
+    }
+
+    /// Creates a new moving torus with given centres, axis of symmetry and radii. Material
+    /// reference
+    pub fn new_moving_with_matref(
+        center0: Point3,
+        center1: Point3,
+        axis: Vec3,
+        major_radius: FltPrim,
+        minor_radius: FltPrim,
+        material: MatRef<'a>,
+    ) -> Self {
+        let movement = center0.vec_to(&center1);
+        let moving = movement.length() > 0.0;
+
+        let frame = Frame::new(&axis);
+
+        let mut extent = frame.disk_extent(flt(major_radius));
+        extent += flt(minor_radius);
+
+        let box1 = Aabb::new_from_points(&(&center0 - &extent), &(&center0 + &extent));
+        let box2 = Aabb::new_from_points(&(&center1 - &extent), &(&center1 + &extent));
+
+        Self {
+            center0,
+            moving,
+            movement,
+            frame,
+            major_radius,
+            minor_radius,
+            material,
+            bbox: Aabb::new_from_bbox(&box1, &box2),
+        }
+    }
+
+    fn position_at_time(&self, time: Flt) -> Point3 {
+        if self.moving {
+            &self.center0 + (time * &self.movement)
+        } else {
+            self.center0.clone()
+        }
+    }
+
+    /// Finds the nearest intersection in the local frame
+    fn intersect(&self, origin: &Vec3, direction: &Vec3, t_range: &Range<Flt>) -> Option<LocalHit> {
+        let big_r = self.major_radius;
+        let small_r = self.minor_radius;
+
+        // Work with a unit direction so distances along the ray are well scaled
+        let length = flt_prim(direction.length());
+        let d = [
+            flt_prim(direction.x()) / length,
+            flt_prim(direction.y()) / length,
+            flt_prim(direction.z()) / length,
+        ];
+        let o = [
+            flt_prim(origin.x()),
+            flt_prim(origin.y()),
+            flt_prim(origin.z()),
+        ];
+
+        // Limit the search to the bounding sphere
+        let bound = big_r + small_r;
+        let od = o[0] * d[0] + o[1] * d[1] + o[2] * d[2];
+        let oo = o[0] * o[0] + o[1] * o[1] + o[2] * o[2];
+        let discriminant = od * od - (oo - bound * bound);
+
+        if discriminant <= 0.0 {
+            return None;
+        }
+
+        let sqrtd = discriminant.sqrt();
+
+        let start = (-od - sqrtd).max(flt_prim(t_range.start) * length);
+        let end = (-od + sqrtd).min(flt_prim(t_range.end) * length);
+
+        if start >= end {
+            return None;
+        }
+
+        // Move the origin to the start of the search for better conditioning
+        let o = [
+            o[0] + start * d[0],
+            o[1] + start * d[1],
+            o[2] + start * d[2],
+        ];
+
+        // (|p|² + R² - r²)² = 4R²(x² + y²) as a quartic in the distance along the ray
+        let f = o[0] * d[0] + o[1] * d[1] + o[2] * d[2];
+        let e = o[0] * o[0] + o[1] * o[1] + o[2] * o[2] + big_r * big_r - small_r * small_r;
+        let four_r_sq = 4.0 * big_r * big_r;
+
+        let coeffs = [
+            e * e - four_r_sq * (o[0] * o[0] + o[1] * o[1]),
+            4.0 * f * e - 2.0 * four_r_sq * (o[0] * d[0] + o[1] * d[1]),
+            2.0 * e + 4.0 * f * f - four_r_sq * (d[0] * d[0] + d[1] * d[1]),
+            4.0 * f,
+            1.0,
+        ];
+
+        let s = *roots_in_range(&coeffs, 0.0..(end - start)).first()?;
+
+        let t = flt((start + s) / length);
+        let local = origin + (t * direction);
+
+        // Normal points away from the centre of the tube
+        let rho = (local.x() * local.x() + local.y() * local.y()).sqrt();
+
+        if rho <= 0.0 {
+            return None;
+        }
+
+        let tube_center = Vec3::new_flt(local.x(), local.y(), flt(0.0)) * (flt(big_r) / rho);
+        let normal = (&local - &tube_center) / flt(small_r);
+
+        let v = local.z().atan2(rho - flt(big_r)) / flt(2.0 * PI);
+        let v = if v < 0.0 { v + flt(1.0) } else { v };
+
+        Some(LocalHit {
+            t,
+            normal,
+            u: azimuth(&local),
+            v,
+            dpdu: Vec3::new_flt(-local.y(), local.x(), flt(0.0)) * flt(2.0 * PI),
+            dpdv: Vec3::new_flt(
+                -local.z() * local.x() / rho,
+                -local.z() * local.y() / rho,
+                rho - flt(big_r),
+            ) * flt(2.0 * PI),
+        })
+    }
+}
+
+impl<'a> Hittable<'a> for Torus<'a> {
+    fn hit(&self, rng: &mut ThreadRng, ray: &Ray, t_range: Range<Flt>) -> Option<Hit> {
+        let center = self.position_at_time(ray.motion_time());
+
+        // Work in the torus's frame
+        let origin = self.frame.to_local(&center.vec_to(ray.origin()));
+        let direction = self.frame.to_local(ray.direction());
+
+        let local_hit = self.intersect(&origin, &direction, &t_range)?;
+
+        self.frame
+            .to_world_hit(rng, ray, local_hit, self.material.get_ref(), self.name())
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        &self.bbox
+    }
+}
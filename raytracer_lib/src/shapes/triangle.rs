@@ -0,0 +1,169 @@
+//! Triangle shape
+
+use std::ops::Range;
+
+use rand::rngs::ThreadRng;
+
+use crate::{
+    float::*,
+    hits::{aabb::Aabb, hit::Hit, hittable::Hittable},
+    materials::material::{MatRef, Material},
+    ray::Ray,
+    triple::{Point3, Vec3},
+};
+
+/// Triangle details
+#[derive(Debug)]
+pub struct Triangle<'a> {
+    /// Vertices at time 0
+    vertices: [Point3; 3],
+    /// Is moving?
+    moving: bool,
+    /// Vertex movement per time unit
+    movement: [Vec3; 3],
+    /// Material to use
+    material: MatRef<'a>,
+    /// Bounding box
+    bbox: Aabb,
+}
+
+impl<'a> Triangle<'a> {
+    /// Creates a new triangle from three vertices. The front face is the side from which the
+    /// vertices appear anticlockwise. Material object
+    pub fn new(a: Point3, b: Point3, c: Point3, material: &'a dyn Material) -> Self {
+        Self::new_with_matref(a, b, c, MatRef::Borrow(material))
+    }
+
+    /// Creates a new triangle from three vertices. The front face is the side from which the
+    /// vertices appear anticlockwise. Material reference
+    pub fn new_with_matref(a: Point3, b: Point3, c: Point3, material: MatRef<'a>) -> Self {
+        Self::new_moving_with_matref(a.clone(), b.clone(), c.clone(), a, b, c, material)
+    }
+
+    /// Creates a new moving triangle given two sets of vertices. Material object
+    pub fn new_moving(
+        a0: Point3,
+        b0: Point3,
+        c0: Point3,
+        a1: Point3,
+        b1: Point3,
+        c1: Point3,
+        material: &'a dyn Material,
+    ) -> Self {
+        Self::new_moving_with_matref(a0, b0, c0, a1, b1, c1, MatRef::Borrow(material))
+    }
+
+    /// Creates a new moving triangle given two sets of vertices. Material reference
+    pub fn new_moving_with_matref(
+        a0: Point3,
+        b0: Point3,
+        c0: Point3,
+        a1: Point3,
+        b1: Point3,
+        c1: Point3,
+        material: MatRef<'a>,
+    ) -> Self {
+        let movement = [a0.vec_to(&a1), b0.vec_to(&b1), c0.vec_to(&c1)];
+        let moving = movement.iter().any(|m| m.length() > 0.0);
+
+        let bbox = Aabb::new_from_bbox(
+            &Self::calc_bbox(&a0, &b0, &c0),
+            &Self::calc_bbox(&a1, &b1, &c1),
+        );
+
+        Self {
+            vertices: [a0, b0, c0],
+            moving,
+            movement,
+            material,
+            bbox,
+        }
+    }
+
+    fn calc_bbox(a: &Point3, b: &Point3, c: &Point3) -> Aabb {
+        Aabb::new_from_bbox(&Aabb::new_from_points(a, b), &Aabb::new_from_points(a, c))
+    }
+
+    fn position_at_time(&self, time: Flt) -> (Point3, Vec3, Vec3) {
+        let [a, b, c] = if self.moving {
+            [
+                &self.vertices[0] + (time * &self.movement[0]),
+                &self.vertices[1] + (time * &self.movement[1]),
+                &self.vertices[2] + (time * &self.movement[2]),
+            ]
+        } else {
+            self.vertices.clone()
+        };
+
+        let edge1 = a.vec_to(&b);
+        let edge2 = a.vec_to(&c);
+
+        (a, edge1, edge2)
+    }
+}
+
+impl<'a> Hittable<'a> for Triangle<'a> {
+    fn hit(&self, rng: &mut ThreadRng, ray: &Ray, t_range: Range<Flt>) -> Option<Hit> {
+        let (a, edge1, edge2) = self.position_at_time(ray.motion_time());
+
+        // Möller-Trumbore intersection
+        let pvec = ray.direction().cross(&edge2);
+        let det = edge1.dot(&pvec);
+
+        // No hit if the ray is parallel to the triangle
+        if det.abs() < 1e-12 {
+            return None;
+        }
+
+        let inv_det = det.recip();
+
+        let tvec = a.vec_to(ray.origin());
+        let u = tvec.dot(&pvec) * inv_det;
+
+        if !(flt(0.0)..=flt(1.0)).contains(&u) {
+            return None;
+        }
+
+        let qvec = tvec.cross(&edge1);
+        let v = ray.direction().dot(&qvec) * inv_det;
+
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+
+        let t = edge2.dot(&qvec) * inv_det;
+
+        if !t_range.contains(&t) {
+            return None;
+        }
+
+        let p = ray.at(t);
+
+        // Check material registers a hit
+        if !self.material.hit(rng, u, v, &p) {
+            return None;
+        }
+
+        let normal = edge1.cross(&edge2).unit_vector();
+
+        // Texture coordinates are the barycentric coordinates of b and c
+        let mut hit = Hit::new(
+            p,
+            t,
+            u,
+            v,
+            ray,
+            &normal,
+            self.material.get_ref(),
+            self.name(),
+        );
+
+        hit.set_tangents(edge1, edge2);
+
+        Some(hit)
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        &self.bbox
+    }
+}
@@ -0,0 +1,209 @@
+//! Spectral rendering support (hero wavelength sampling)
+
+use std::sync::OnceLock;
+
+use rand::{rngs::ThreadRng, Rng};
+
+use crate::{float::*, triple::Colour};
+
+/// Maximum number of wavelengths carried by a ray
+pub const MAX_WAVELENGTHS: usize = 4;
+
+/// Shortest sampled wavelength (nm)
+pub const LAMBDA_MIN: FltPrim = 380.0;
+
+/// Longest sampled wavelength (nm)
+pub const LAMBDA_MAX: FltPrim = 730.0;
+
+/// Sodium D line wavelength (nm), used for the refractive index in RGB mode
+pub const LAMBDA_D: FltPrim = 589.3;
+
+/// Spectral values at each of a ray's wavelengths
+pub type SampledSpectrum = [Flt; MAX_WAVELENGTHS];
+
+/// Set of wavelengths carried by a ray. The first is the hero wavelength, the rest are
+/// spaced evenly across the visible range from it
+#[derive(Debug, Clone, Default)]
+pub struct Wavelengths {
+    lambda: [Flt; MAX_WAVELENGTHS],
+    count: usize,
+}
+
+impl Wavelengths {
+    /// Creates a new random set of wavelengths
+    pub fn new_random(rng: &mut ThreadRng, count: usize) -> Self {
+        let count = count.clamp(1, MAX_WAVELENGTHS);
+        let hero = rng.gen_range(0.0..1.0);
+
+        let mut lambda = [flt(0.0); MAX_WAVELENGTHS];
+
+        for (i, l) in lambda.iter_mut().take(count).enumerate() {
+            let offset = (hero + i as FltPrim / count as FltPrim).fract();
+            *l = flt(LAMBDA_MIN + offset * (LAMBDA_MAX - LAMBDA_MIN));
+        }
+
+        Self { lambda, count }
+    }
+
+    /// Returns the hero wavelength
+    pub fn hero(&self) -> Flt {
+        self.lambda[0]
+    }
+
+    /// Returns the number of active wavelengths
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Returns the active wavelengths
+    pub fn lambdas(&self) -> &[Flt] {
+        &self.lambda[..self.count]
+    }
+
+    /// Terminates all but the hero wavelength (for wavelength dependent directions)
+    pub fn terminate_secondary(&mut self) {
+        self.count = 1;
+    }
+
+    /// Returns a copy with the secondary wavelengths terminated
+    pub fn hero_only(&self) -> Self {
+        let mut result = self.clone();
+        result.terminate_secondary();
+        result
+    }
+}
+
+/// Returns the spectral value of an RGB colour at a given wavelength
+pub fn rgb_to_spectrum(colour: &Colour, lambda: Flt) -> Flt {
+    // Partition of unity basis so that white maps to a flat spectrum
+    let blue = flt(1.0) - smoothstep(flt(460.0), flt(510.0), lambda);
+    let red = smoothstep(flt(560.0), flt(610.0), lambda);
+    let green = flt(1.0) - blue - red;
+
+    colour[0] * red + colour[1] * green + colour[2] * blue
+}
+
+/// Returns the spectral values of an RGB colour at each of a set of wavelengths
+pub fn upsample(colour: &Colour, wavelengths: &Wavelengths) -> SampledSpectrum {
+    let mut result = [flt(0.0); MAX_WAVELENGTHS];
+
+    for (r, &lambda) in result.iter_mut().zip(wavelengths.lambdas()) {
+        *r = rgb_to_spectrum(colour, lambda);
+    }
+
+    result
+}
+
+/// Converts spectral samples taken at a set of wavelengths to an RGB colour estimate
+pub fn spectrum_to_rgb(values: &SampledSpectrum, wavelengths: &Wavelengths) -> Colour {
+    let balance = white_balance();
+
+    let mut rgb = [flt(0.0); 3];
+
+    for (&value, &lambda) in values.iter().zip(wavelengths.lambdas()) {
+        let response = lambda_to_rgb(flt_prim(lambda));
+
+        for c in 0..3 {
+            rgb[c] += value * flt(response[c]);
+        }
+    }
+
+    // Uniform wavelength pdf and white balance so a flat spectrum maps to white
+    let scale = (LAMBDA_MAX - LAMBDA_MIN) / wavelengths.count() as FltPrim;
+
+    Colour::new_flt(
+        rgb[0] * flt(scale / balance[0]),
+        rgb[1] * flt(scale / balance[1]),
+        rgb[2] * flt(scale / balance[2]),
+    )
+}
+
+/// Returns the CIE 1931 colour matching functions at a given wavelength (nm) using the
+/// multi-lobe gaussian fit from Wyman, Sloan & Shirley (2013)
+pub fn cie_xyz(lambda: FltPrim) -> [FltPrim; 3] {
+    let g = |mu: FltPrim, sigma1: FltPrim, sigma2: FltPrim| {
+        let t = (lambda - mu) / if lambda < mu { sigma1 } else { sigma2 };
+        (-0.5 * t * t).exp()
+    };
+
+    [
+        1.056 * g(599.8, 37.9, 31.0) + 0.362 * g(442.0, 16.0, 26.7) - 0.065 * g(501.1, 20.4, 26.2),
+        0.821 * g(568.8, 46.9, 40.5) + 0.286 * g(530.9, 16.3, 31.1),
+        1.217 * g(437.0, 11.8, 36.0) + 0.681 * g(459.0, 26.0, 13.8),
+    ]
+}
+
+/// Returns the linear sRGB response at a given wavelength (nm)
+fn lambda_to_rgb(lambda: FltPrim) -> [FltPrim; 3] {
+    let [x, y, z] = cie_xyz(lambda);
+
+    [
+        3.2406 * x - 1.5372 * y - 0.4986 * z,
+        -0.9689 * x + 1.8758 * y + 0.0415 * z,
+        0.0557 * x - 0.2040 * y + 1.0570 * z,
+    ]
+}
+
+/// Returns the integral of the sRGB response over the sampled range
+fn white_balance() -> &'static [FltPrim; 3] {
+    static BALANCE: OnceLock<[FltPrim; 3]> = OnceLock::new();
+
+    BALANCE.get_or_init(|| {
+        const STEPS: usize = 3500;
+        let step = (LAMBDA_MAX - LAMBDA_MIN) / STEPS as FltPrim;
+
+        let mut result = [0.0; 3];
+
+        for i in 0..STEPS {
+            let response = lambda_to_rgb(LAMBDA_MIN + (i as FltPrim + 0.5) * step);
+
+            for c in 0..3 {
+                result[c] += response[c] * step;
+            }
+        }
+
+        result
+    })
+}
+
+fn smoothstep(edge0: Flt, edge1: Flt, x: Flt) -> Flt {
+    let t = clamp((x - edge0) / (edge1 - edge0), flt(0.0), flt(1.0));
+    t * t * (flt(3.0) - flt(2.0) * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::thread_rng;
+
+    use super::*;
+
+    #[test]
+    fn test_flat_spectrum_is_white() {
+        let mut rng = thread_rng();
+        let samples = 20000;
+
+        let mut sum = Colour::default();
+
+        for _ in 0..samples {
+            let wavelengths = Wavelengths::new_random(&mut rng, MAX_WAVELENGTHS);
+            let values = upsample(&Colour::new_white(), &wavelengths);
+            sum += spectrum_to_rgb(&values, &wavelengths);
+        }
+
+        let mean = sum / flt(samples as FltPrim);
+
+        for c in 0..3 {
+            assert!((mean[c] - 1.0).abs() < 0.02, "channel {c} is {}", mean[c]);
+        }
+    }
+
+    #[test]
+    fn test_hero_only() {
+        let mut rng = thread_rng();
+        let wavelengths = Wavelengths::new_random(&mut rng, 4);
+        let hero = wavelengths.hero_only();
+
+        assert_eq!(hero.count(), 1);
+        assert_eq!(hero.hero(), wavelengths.hero());
+    }
+}
@@ -1,10 +1,11 @@
 //! Image map texture
 
-use image::io::Reader as ImageReader;
+use image::ImageReader;
 use rand::{rngs::ThreadRng, Rng};
-use std::path::{Path, PathBuf};
+use std::path::Path;
 
 use crate::{
+    files::find_file,
     float::*,
     triple::{Colour, Point3},
 };
@@ -23,11 +24,7 @@ pub struct Image {
 impl Image {
     /// Create a new image map from an image file
     pub fn new_from_file(file: &Path) -> Self {
-        let file_path = if file.exists() {
-            PathBuf::from(file)
-        } else {
-            Self::find_file(file).expect("Unable to find file")
-        };
+        let file_path = find_file(file, "images");
 
         let img = ImageReader::open(file_path).expect("Unable to open image");
         let dynimg = img.decode().expect("Unable to decode image");
@@ -45,25 +42,6 @@ impl Image {
         }
     }
 
-    fn find_file(file: &Path) -> Option<PathBuf> {
-        for parent in 0..8 {
-            let mut buf = PathBuf::new();
-
-            for _ in 0..parent {
-                buf.push("..")
-            }
-
-            buf.push("images");
-            buf.push(file);
-
-            if buf.is_file() {
-                return Some(buf);
-            }
-        }
-
-        None
-    }
-
     fn map_coords(&self, u: Flt, v: Flt) -> usize {
         // Clamp input texture coordinates to [0,1] x [1,0]
         let uc = clamp(u, flt(0.0), flt(1.0));
@@ -1,7 +1,7 @@
 //! Texture trait
 
 use rand::rngs::ThreadRng;
-use std::{fmt::Debug, ops::Deref};
+use std::{fmt::Debug, ops::Deref, sync::Arc};
 
 use crate::{
     float::*,
@@ -17,15 +17,27 @@ pub trait Texture: Debug + Sync + Send {
 
     /// Return the colour of a texture at a given point
     fn value(&self, u: Flt, v: Flt, p: &Point3) -> Colour;
+
+    /// Return the texture value at a given point as a scalar (mean of the colour channels)
+    fn scalar(&self, u: Flt, v: Flt, p: &Point3) -> Flt {
+        let colour = self.value(u, v, p);
+
+        (colour[0] + colour[1] + colour[2]) / flt(3.0)
+    }
 }
 
-/// Texture reference, either borrowed or owned
+/// Texture reference with no borrows (owned, shared or 'static)
+pub type OwnedTexRef = TexRef<'static>;
+
+/// Texture reference, either borrowed, owned or shared
 #[derive(Debug)]
 pub enum TexRef<'a> {
     /// Borrowed texture reference
     Borrow(&'a dyn Texture),
     /// Owned texture
     Box(Box<dyn Texture + 'a>),
+    /// Shared texture
+    Arc(Arc<dyn Texture + 'a>),
 }
 
 impl<'a> TexRef<'a> {
@@ -34,11 +46,34 @@ impl<'a> TexRef<'a> {
         Self::Box(Box::new(tex))
     }
 
+    /// Create new shared texture reference
+    pub fn shared(tex: impl Texture + 'a) -> Self {
+        Self::Arc(Arc::new(tex))
+    }
+
+    /// Converts an owned texture reference to a shared one so that it can be shared
+    pub fn into_shared(self) -> Self {
+        match self {
+            Self::Box(boxed) => Self::Arc(Arc::from(boxed)),
+            other => other,
+        }
+    }
+
+    /// Creates another reference to the same texture if it is borrowed or shared
+    pub fn share(&self) -> Option<Self> {
+        match self {
+            Self::Borrow(refer) => Some(Self::Borrow(*refer)),
+            Self::Box(_) => None,
+            Self::Arc(shared) => Some(Self::Arc(shared.clone())),
+        }
+    }
+
     /// Gets a reference to the texture
     pub fn get_ref(&'a self) -> &dyn Texture {
         match self {
             Self::Borrow(refer) => *refer,
             Self::Box(boxed) => boxed.as_ref(),
+            Self::Arc(shared) => shared.as_ref(),
         }
     }
 }
@@ -50,6 +85,7 @@ impl<'a> Deref for TexRef<'a> {
         match self {
             TexRef::Borrow(refer) => *refer,
             TexRef::Box(boxed) => boxed.as_ref(),
+            TexRef::Arc(shared) => shared.as_ref(),
         }
     }
 }
@@ -0,0 +1,322 @@
+//! Affine transformation (linear map followed by a translation)
+
+use crate::{
+    float::*,
+    hits::{aabb::Aabb, hit::Hit},
+    ray::Ray,
+    triple::{Point3, Vec3},
+};
+
+type Matrix = [[Flt; 3]; 3];
+
+/// Affine transformation details
+#[derive(Debug, Clone)]
+pub struct Affine {
+    /// Linear part, applied before the translation
+    linear: Matrix,
+    /// Inverse of the linear part
+    inverse: Matrix,
+    /// Translation
+    translation: Vec3,
+}
+
+impl Affine {
+    /// Creates a new transformation from a 3x3 matrix (rows) and a translation. Panics if the
+    /// matrix cannot be inverted
+    pub fn new(linear: [[FltPrim; 3]; 3], translation: Vec3) -> Self {
+        Self::new_from_matrix(linear.map(|row| row.map(flt)), translation)
+    }
+
+    /// Creates the identity transformation
+    pub fn new_identity() -> Self {
+        Self::new(
+            [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+            Vec3::default(),
+        )
+    }
+
+    /// Creates a translation
+    pub fn new_translation(offset: Vec3) -> Self {
+        Self::new([[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]], offset)
+    }
+
+    /// Creates a scaling along each axis
+    pub fn new_scale(x: FltPrim, y: FltPrim, z: FltPrim) -> Self {
+        Self::new(
+            [[x, 0.0, 0.0], [0.0, y, 0.0], [0.0, 0.0, z]],
+            Vec3::default(),
+        )
+    }
+
+    /// Creates a rotation by an angle in degrees about an axis through the origin
+    pub fn new_rotation(axis: &Vec3, angle: FltPrim) -> Self {
+        let axis = axis.unit_vector();
+        let (sin, cos) = flt(angle).to_radians().sin_cos();
+        let one_minus_cos = flt(1.0) - cos;
+
+        let (x, y, z) = (axis.x(), axis.y(), axis.z());
+
+        // Rodrigues' rotation formula
+        let linear = [
+            [
+                cos + x * x * one_minus_cos,
+                x * y * one_minus_cos - z * sin,
+                x * z * one_minus_cos + y * sin,
+            ],
+            [
+                y * x * one_minus_cos + z * sin,
+                cos + y * y * one_minus_cos,
+                y * z * one_minus_cos - x * sin,
+            ],
+            [
+                z * x * one_minus_cos - y * sin,
+                z * y * one_minus_cos + x * sin,
+                cos + z * z * one_minus_cos,
+            ],
+        ];
+
+        Self::new_from_matrix(linear, Vec3::default())
+    }
+
+    /// Creates a scaling along each axis, followed by rotations in degrees about x, then y, then
+    /// z, followed by a translation. This is cheaper than combining the separate
+    /// transformations. Panics if any scale is zero
+    pub fn new_scale_rotation_translation(
+        scale: &Vec3,
+        rotation: &Vec3,
+        translation: Vec3,
+    ) -> Self {
+        assert!(
+            (0..3).all(|c| scale[c] != 0.0),
+            "Transformation matrix is not invertible"
+        );
+
+        let [(sa, ca), (sb, cb), (sg, cg)] = [0, 1, 2].map(|c| rotation[c].to_radians().sin_cos());
+
+        // Rotation about z * rotation about y * rotation about x
+        let rotate = [
+            [cg * cb, cg * sb * sa - sg * ca, cg * sb * ca + sg * sa],
+            [sg * cb, sg * sb * sa + cg * ca, sg * sb * ca - cg * sa],
+            [-sb, cb * sa, cb * ca],
+        ];
+
+        // The inverse of a rotation is its transpose
+        Self {
+            linear: [0, 1, 2].map(|r| [0, 1, 2].map(|c| rotate[r][c] * scale[c])),
+            inverse: [0, 1, 2].map(|r| [0, 1, 2].map(|c| rotate[c][r] / scale[r])),
+            translation,
+        }
+    }
+
+    fn new_from_matrix(linear: Matrix, translation: Vec3) -> Self {
+        let inverse = Self::invert(&linear).expect("Transformation matrix is not invertible");
+
+        Self {
+            linear,
+            inverse,
+            translation,
+        }
+    }
+
+    /// Returns the transformation that applies this one followed by another
+    pub fn then(&self, next: &Affine) -> Self {
+        Self {
+            linear: Self::multiply(&next.linear, &self.linear),
+            inverse: Self::multiply(&self.inverse, &next.inverse),
+            translation: next.vector(&self.translation) + &next.translation,
+        }
+    }
+
+    /// Returns the inverse transformation
+    pub fn inverse(&self) -> Self {
+        Self {
+            linear: self.inverse,
+            inverse: self.linear,
+            translation: -Vec3::new_from_array(Self::apply(&self.inverse, &self.translation.e)),
+        }
+    }
+
+    /// Transforms a point
+    pub fn point(&self, p: &Point3) -> Point3 {
+        Point3::new_from_array(Self::apply(&self.linear, &p.e)) + &self.translation
+    }
+
+    /// Transforms a direction vector (ignoring the translation)
+    pub fn vector(&self, v: &Vec3) -> Vec3 {
+        Vec3::new_from_array(Self::apply(&self.linear, &v.e))
+    }
+
+    /// Transforms a surface normal so that it stays perpendicular to the transformed surface.
+    /// The result is not normalised
+    pub fn normal(&self, n: &Vec3) -> Vec3 {
+        // Multiply by the transpose of the inverse
+        let m = &self.inverse;
+
+        Vec3::new_from_array([0, 1, 2].map(|c| m[0][c] * n[0] + m[1][c] * n[1] + m[2][c] * n[2]))
+    }
+
+    /// Transforms a point by the inverse transformation
+    pub fn inverse_point(&self, p: &Point3) -> Point3 {
+        let offset = p - &self.translation;
+
+        Point3::new_from_array(Self::apply(&self.inverse, &offset.e))
+    }
+
+    /// Transforms a direction vector by the inverse transformation
+    pub fn inverse_vector(&self, v: &Vec3) -> Vec3 {
+        Vec3::new_from_array(Self::apply(&self.inverse, &v.e))
+    }
+
+    /// Changes a ray from world space to object space. The direction is not normalised so
+    /// distances along the ray are the same in both spaces
+    pub fn inverse_ray(&self, ray: &Ray) -> Ray {
+        Ray::new_from(
+            ray,
+            self.inverse_point(ray.origin()),
+            self.inverse_vector(ray.direction()),
+        )
+    }
+
+    /// Changes an intersection from object space to world space
+    pub fn to_world_hit(&self, hit: &mut Hit) {
+        hit.p = self.point(&hit.p);
+        hit.normal = self.normal(&hit.normal).unit_vector();
+
+        let dpdu = self.vector(&hit.dpdu);
+        let dpdv = self.vector(&hit.dpdv);
+        hit.set_tangents(dpdu, dpdv);
+    }
+
+    /// Returns a bounding box enclosing a transformed bounding box
+    pub fn bounding_box(&self, bbox: &Aabb) -> Aabb {
+        let mut min = Point3::new_flt(flt_max(), flt_max(), flt_max());
+        let mut max = Point3::new_flt(flt_min(), flt_min(), flt_min());
+
+        for corner in 0..8 {
+            let p = Point3::new_from_array([0, 1, 2].map(|axis| {
+                if corner & (1 << axis) == 0 {
+                    bbox.ranges[axis].start
+                } else {
+                    bbox.ranges[axis].end
+                }
+            }));
+
+            let p = self.point(&p);
+
+            for c in 0..3 {
+                min.e[c] = min[c].min(p[c]);
+                max.e[c] = max[c].max(p[c]);
+            }
+        }
+
+        Aabb::new_from_points(&min, &max)
+    }
+
+    fn apply(m: &Matrix, v: &[Flt; 3]) -> [Flt; 3] {
+        m.map(|row| row[0] * v[0] + row[1] * v[1] + row[2] * v[2])
+    }
+
+    fn multiply(a: &Matrix, b: &Matrix) -> Matrix {
+        [0, 1, 2]
+            .map(|r| [0, 1, 2].map(|c| a[r][0] * b[0][c] + a[r][1] * b[1][c] + a[r][2] * b[2][c]))
+    }
+
+    fn invert(m: &Matrix) -> Option<Matrix> {
+        // Cofactor matrix, transposed
+        let cofactor = |r: usize, c: usize| {
+            let (r1, r2) = ((r + 1) % 3, (r + 2) % 3);
+            let (c1, c2) = ((c + 1) % 3, (c + 2) % 3);
+
+            m[r1][c1] * m[r2][c2] - m[r1][c2] * m[r2][c1]
+        };
+
+        let det = m[0][0] * cofactor(0, 0) + m[0][1] * cofactor(0, 1) + m[0][2] * cofactor(0, 2);
+
+        if det.abs() < 1e-12 {
+            return None;
+        }
+
+        let inv_det = det.recip();
+
+        Some([0, 1, 2].map(|r| [0, 1, 2].map(|c| cofactor(c, r) * inv_det)))
+    }
+}
+
+impl Default for Affine {
+    fn default() -> Self {
+        Self::new_identity()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Tolerance for comparisons, allowing for rounding in the float type used
+    fn tolerance() -> FltPrim {
+        FltPrim::EPSILON.sqrt()
+    }
+
+    fn assert_near(a: &Vec3, b: &Vec3) {
+        assert!((a - b).length() < tolerance(), "{a} != {b}");
+    }
+
+    #[test]
+    fn test_compose_and_invert() {
+        let transform = Affine::new_scale(2.0, 1.0, 0.5)
+            .then(&Affine::new_rotation(&Vec3::new(1.0, 2.0, 3.0), 40.0))
+            .then(&Affine::new_translation(Vec3::new(1.0, -2.0, 3.0)));
+
+        let p = Point3::new(0.3, -1.2, 2.5);
+        let q = transform.point(&p);
+
+        assert_near(&transform.inverse_point(&q).to_vec3(), &p.clone().to_vec3());
+        assert_near(&transform.inverse().point(&q).to_vec3(), &p.to_vec3());
+
+        // Rotation about z by 90 degrees takes x to y
+        let rotation = Affine::new_rotation(&Vec3::new(0.0, 0.0, 1.0), 90.0);
+
+        assert_near(
+            &rotation.vector(&Vec3::new(1.0, 0.0, 0.0)),
+            &Vec3::new(0.0, 1.0, 0.0),
+        );
+
+        // Normals stay perpendicular to transformed tangents
+        let tangent = Vec3::new(1.0, 1.0, 0.0);
+        let normal = Vec3::new(1.0, -1.0, 0.0);
+
+        assert!(
+            transform
+                .vector(&tangent)
+                .dot(&transform.normal(&normal))
+                .abs()
+                < tolerance()
+        );
+    }
+
+    #[test]
+    fn test_scale_rotation_translation() {
+        let scale = Vec3::new(2.0, -1.0, 0.5);
+        let rotation = Vec3::new(30.0, -75.0, 110.0);
+        let translation = Vec3::new(1.0, -2.0, 3.0);
+
+        let combined =
+            Affine::new_scale_rotation_translation(&scale, &rotation, translation.clone());
+
+        let composed = Affine::new_scale(2.0, -1.0, 0.5)
+            .then(&Affine::new_rotation(&Vec3::new(1.0, 0.0, 0.0), 30.0))
+            .then(&Affine::new_rotation(&Vec3::new(0.0, 1.0, 0.0), -75.0))
+            .then(&Affine::new_rotation(&Vec3::new(0.0, 0.0, 1.0), 110.0))
+            .then(&Affine::new_translation(translation));
+
+        let p = Point3::new(0.3, -1.2, 2.5);
+        let q = combined.point(&p);
+
+        assert_near(&q.clone().to_vec3(), &composed.point(&p).to_vec3());
+        assert_near(&combined.inverse_point(&q).to_vec3(), &p.to_vec3());
+
+        let n = Vec3::new(0.6, 0.0, -0.8);
+
+        assert_near(&combined.normal(&n), &composed.normal(&n));
+    }
+}
@@ -0,0 +1,134 @@
+//! Keyframed transformation of an object
+
+use std::ops::Range;
+
+use rand::rngs::ThreadRng;
+
+use crate::{
+    animation::Track,
+    float::*,
+    hits::{
+        aabb::Aabb,
+        hit::Hit,
+        hittable::{Hittable, HittableRef},
+    },
+    ray::Ray,
+    triple::Vec3,
+};
+
+use super::affine::Affine;
+
+/// Number of samples between keys used to bound the motion
+const BBOX_SAMPLES: usize = 32;
+
+/// Animation details
+#[derive(Debug)]
+pub struct Animate<'a> {
+    object: HittableRef<'a>,
+    /// Translation track
+    translation: Track<Vec3>,
+    /// Rotation track (degrees about x, then y, then z)
+    rotation: Track<Vec3>,
+    /// Scale track (along x, y and z)
+    scale: Track<Vec3>,
+    bbox: Aabb,
+}
+
+impl<'a> Animate<'a> {
+    /// Creates a new animated object, initially without movement
+    pub fn new(object: impl Hittable<'a> + 'a) -> Self {
+        Self::new_with_hittable_ref(HittableRef::boxed(object))
+    }
+
+    /// Creates a new animated object reference, initially without movement
+    pub fn new_with_hittable_ref(object: HittableRef<'a>) -> Self {
+        let bbox = object.bounding_box().clone();
+
+        Self {
+            object,
+            translation: Track::new_constant(Vec3::new(0.0, 0.0, 0.0)),
+            rotation: Track::new_constant(Vec3::new(0.0, 0.0, 0.0)),
+            scale: Track::new_constant(Vec3::new(1.0, 1.0, 1.0)),
+            bbox,
+        }
+    }
+
+    /// Sets the translation track
+    pub fn set_translation(&mut self, translation: Track<Vec3>) {
+        self.translation = translation;
+        self.recalculate();
+    }
+
+    /// Sets the rotation track (degrees about x, then y, then z)
+    pub fn set_rotation(&mut self, rotation: Track<Vec3>) {
+        self.rotation = rotation;
+        self.recalculate();
+    }
+
+    /// Sets the scale track (along x, y and z)
+    pub fn set_scale(&mut self, scale: Track<Vec3>) {
+        self.scale = scale;
+        self.recalculate();
+    }
+
+    /// Returns the transformation at a given time
+    pub fn transform_at(&self, time: Flt) -> Affine {
+        Affine::new_scale_rotation_translation(
+            &self.scale.value_at(time),
+            &self.rotation.value_at(time),
+            self.translation.value_at(time),
+        )
+    }
+
+    /// Recalculates the bounding box to enclose the object over the whole animation
+    fn recalculate(&mut self) {
+        let mut times: Vec<Flt> = self
+            .translation
+            .times()
+            .chain(self.rotation.times())
+            .chain(self.scale.times())
+            .collect();
+
+        times.sort_by(|a, b| a.partial_cmp(b).expect("Invalid float in sort"));
+        times.dedup();
+
+        let object_bbox = self.object.bounding_box();
+
+        let mut bbox = self.transform_at(times[0]).bounding_box(object_bbox);
+
+        // Sample the motion between each pair of keys
+        for pair in times.windows(2) {
+            for step in 1..=BBOX_SAMPLES {
+                let s = flt(step as FltPrim / BBOX_SAMPLES as FltPrim);
+                let time = pair[0] + s * (pair[1] - pair[0]);
+
+                bbox =
+                    Aabb::new_from_bbox(&bbox, &self.transform_at(time).bounding_box(object_bbox));
+            }
+        }
+
+        self.bbox = bbox;
+    }
+}
+
+impl<'a> Hittable<'a> for Animate<'a> {
+    fn hit(&self, rng: &mut ThreadRng, ray: &Ray, t_range: Range<Flt>) -> Option<Hit> {
+        let transform = self.transform_at(ray.time());
+
+        let local = transform.inverse_ray(ray);
+
+        let mut hit = self.object.hit(rng, &local, t_range)?;
+
+        transform.to_world_hit(&mut hit);
+
+        Some(hit)
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        &self.bbox
+    }
+
+    fn bounded(&self) -> bool {
+        self.object.bounded()
+    }
+}
@@ -14,6 +14,7 @@ use crate::{
     materials::{
         isotropic::Isotropic,
         material::{MatRef, Material},
+        phase::{Phase, PhaseFunction},
     },
     ray::Ray,
     triple::{Colour, Vec3},
@@ -41,6 +42,20 @@ impl<'a> ConstantMedium<'a> {
         )
     }
 
+    /// Creates a new constant medium with colour and phase function
+    pub fn new_with_phase_function(
+        boundary: impl Hittable<'a> + 'a,
+        density: FltPrim,
+        function: PhaseFunction,
+        colour: Colour,
+    ) -> Self {
+        Self::new_with_matref(
+            HittableRef::boxed(boundary),
+            density,
+            MatRef::boxed(Phase::new_with_colour(function, colour)),
+        )
+    }
+
     /// Creates a new constant medium with material
     pub fn new_with_material(
         boundary: impl Hittable<'a> + 'a,
@@ -118,6 +133,7 @@ impl<'a> Hittable<'a> for ConstantMedium<'a> {
             ray,
             &Vec3::new(1.0, 0.0, 0.0),
             self.phase_function.get_ref(),
+            self.name(),
         ))
     }
 
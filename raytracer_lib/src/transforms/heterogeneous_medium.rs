@@ -0,0 +1,214 @@
+//! Heterogeneous medium (density varying through a grid)
+
+use std::ops::Range;
+
+use rand::{rngs::ThreadRng, Rng};
+
+use crate::{
+    density_grid::DensityGrid,
+    float::*,
+    hits::{
+        aabb::Aabb,
+        hit::Hit,
+        hittable::{Hittable, HittableRef, T_MIN},
+    },
+    materials::{
+        isotropic::Isotropic,
+        material::{MatRef, Material, Scattered},
+    },
+    ray::Ray,
+    textures::{
+        solid::Solid,
+        texture::{TexRef, Texture},
+    },
+    triple::{Colour, Point3, Vec3},
+};
+
+/// Heterogeneous medium details
+///
+/// The density grid is stretched over the bounding box of the boundary and scaled by the
+/// density factor. Collisions are found by delta tracking against the maximum density, and
+/// the optional emission (for fire and explosions) is added at each collision, so emission
+/// is proportional to the local density.
+#[derive(Debug)]
+pub struct HeterogeneousMedium<'a> {
+    boundary: HittableRef<'a>,
+    grid: DensityGrid,
+    density: Flt,
+    majorant: Flt,
+    collision: MediumCollision<'a>,
+}
+
+impl<'a> HeterogeneousMedium<'a> {
+    /// Creates a new heterogeneous medium with colour
+    pub fn new_with_colour(
+        boundary: impl Hittable<'a> + 'a,
+        grid: DensityGrid,
+        density: FltPrim,
+        colour: Colour,
+    ) -> Self {
+        Self::new_with_matref(
+            HittableRef::boxed(boundary),
+            grid,
+            density,
+            MatRef::boxed(Isotropic::new_with_colour(colour)),
+        )
+    }
+
+    /// Creates a new heterogeneous medium with material
+    pub fn new_with_material(
+        boundary: impl Hittable<'a> + 'a,
+        grid: DensityGrid,
+        density: FltPrim,
+        material: &'a dyn Material,
+    ) -> Self {
+        Self::new_with_matref(
+            HittableRef::boxed(boundary),
+            grid,
+            density,
+            MatRef::Borrow(material),
+        )
+    }
+
+    /// Creates a new heterogeneous medium with material reference
+    pub fn new_with_matref(
+        boundary: HittableRef<'a>,
+        grid: DensityGrid,
+        density: FltPrim,
+        phase_function: MatRef<'a>,
+    ) -> Self {
+        let density = flt(density);
+        let majorant = density * grid.max();
+
+        Self {
+            boundary,
+            grid,
+            density,
+            majorant,
+            collision: MediumCollision {
+                phase_function,
+                emission: None,
+            },
+        }
+    }
+
+    /// Sets the emitted colour
+    pub fn set_emission_colour(&mut self, colour: Colour) {
+        self.set_emission_texref(TexRef::boxed(Solid::new(colour)));
+    }
+
+    /// Sets the emission texture (evaluated at the collision point)
+    pub fn set_emission_texture(&mut self, texture: &'a dyn Texture) {
+        self.set_emission_texref(TexRef::Borrow(texture));
+    }
+
+    /// Sets the emission texture reference (evaluated at the collision point)
+    pub fn set_emission_texref(&mut self, texture: TexRef<'a>) {
+        self.collision.emission = Some(texture);
+    }
+
+    /// Returns the range of the ray inside the boundary, limited to a given range
+    fn inside_range(
+        &self,
+        rng: &mut ThreadRng,
+        ray: &Ray,
+        t_range: Range<Flt>,
+    ) -> Option<Range<Flt>> {
+        if self.majorant <= 0.0 {
+            return None;
+        }
+
+        // Does this ray enter the boundary?
+        let hit1 = self.boundary.hit(rng, ray, flt_min()..flt_max())?;
+
+        // Does the ray exit the boundary again?
+        let hit2 = self.boundary.hit(rng, ray, (hit1.t + T_MIN)..flt_max())?;
+
+        let start = hit1.t.max(t_range.start).max(flt(0.0));
+        let end = hit2.t.min(t_range.end);
+
+        if start >= end {
+            None
+        } else {
+            Some(start..end)
+        }
+    }
+
+    /// Samples a tentative collision distance along the ray against the majorant
+    fn step(&self, rng: &mut ThreadRng, ray: &Ray) -> Flt {
+        let distance = -(flt(1.0) - flt(rng.gen_range(0.0..1.0))).ln() / self.majorant;
+
+        distance / ray.direction().length()
+    }
+
+    /// Returns the density at a point
+    fn density(&self, p: &Point3) -> Flt {
+        let bbox = self.boundary.bounding_box();
+
+        let unit = |axis: usize, c: Flt| {
+            let range = &bbox.ranges[axis];
+            (c - range.start) / (range.end - range.start)
+        };
+
+        let local = Point3::new_flt(unit(0, p.x()), unit(1, p.y()), unit(2, p.z()));
+
+        self.density * self.grid.density(&local)
+    }
+}
+
+impl<'a> Hittable<'a> for HeterogeneousMedium<'a> {
+    fn hit(&self, rng: &mut ThreadRng, ray: &Ray, t_range: Range<Flt>) -> Option<Hit> {
+        let inside = self.inside_range(rng, ray, t_range)?;
+
+        // Delta tracking - tentative collisions are real with probability density / majorant
+        let mut t = inside.start;
+
+        loop {
+            t += self.step(rng, ray);
+
+            if t >= inside.end {
+                return None;
+            }
+
+            let p = ray.at(t);
+
+            if self.density(&p) > self.majorant * flt(rng.gen_range(0.0..1.0)) {
+                return Some(Hit::new(
+                    p,
+                    t,
+                    flt(0.0),
+                    flt(0.0),
+                    ray,
+                    &Vec3::new(1.0, 0.0, 0.0),
+                    &self.collision,
+                    self.name(),
+                ));
+            }
+        }
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        self.boundary.bounding_box()
+    }
+}
+
+/// Real collision in the medium - scatters with the phase function and adds any emission
+#[derive(Debug)]
+struct MediumCollision<'a> {
+    phase_function: MatRef<'a>,
+    emission: Option<TexRef<'a>>,
+}
+
+impl<'a> Material for MediumCollision<'a> {
+    fn scatter(&self, rng: &mut ThreadRng, ray: &Ray, hit: &Hit) -> Scattered {
+        let (attenuation, emitted, scattered) = self.phase_function.scatter(rng, ray, hit);
+
+        let emitted = match (&self.emission, emitted) {
+            (Some(emission), Some(emitted)) => Some(emitted + emission.value(hit.u, hit.v, &hit.p)),
+            (Some(emission), None) => Some(emission.value(hit.u, hit.v, &hit.p)),
+            (None, emitted) => emitted,
+        };
+
+        (attenuation, emitted, scattered)
+    }
+}
@@ -0,0 +1,80 @@
+//! Instance of shared geometry with its own transformation and material
+
+use std::{ops::Range, sync::Arc};
+
+use rand::rngs::ThreadRng;
+
+use crate::{
+    float::*,
+    hits::{
+        aabb::Aabb,
+        hit::Hit,
+        hittable::{Hittable, HittableRef},
+    },
+    materials::material::{MatRef, Material},
+    ray::Ray,
+};
+
+use super::affine::Affine;
+
+/// Instance details
+#[derive(Debug)]
+pub struct Instance<'a> {
+    object: HittableRef<'a>,
+    transform: Affine,
+    material: Option<MatRef<'a>>,
+    bbox: Aabb,
+}
+
+impl<'a> Instance<'a> {
+    /// Creates a new instance of shared geometry placed by a transformation
+    pub fn new(object: &Arc<dyn Hittable<'a> + 'a>, transform: Affine) -> Self {
+        Self::new_with_hittable_ref(HittableRef::Arc(object.clone()), transform)
+    }
+
+    /// Creates a new instance of an object reference placed by a transformation
+    pub fn new_with_hittable_ref(object: HittableRef<'a>, transform: Affine) -> Self {
+        let bbox = transform.bounding_box(object.bounding_box());
+
+        Self {
+            object,
+            transform,
+            material: None,
+            bbox,
+        }
+    }
+
+    /// Sets a material to use in place of the geometry's own. Material object
+    pub fn set_material(&mut self, material: &'a dyn Material) {
+        self.set_matref(MatRef::Borrow(material));
+    }
+
+    /// Sets a material to use in place of the geometry's own. Material reference
+    pub fn set_matref(&mut self, material: MatRef<'a>) {
+        self.material = Some(material);
+    }
+}
+
+impl<'a> Hittable<'a> for Instance<'a> {
+    fn hit(&self, rng: &mut ThreadRng, ray: &Ray, t_range: Range<Flt>) -> Option<Hit> {
+        let local = self.transform.inverse_ray(ray);
+
+        let mut hit = self.object.hit(rng, &local, t_range)?;
+
+        self.transform.to_world_hit(&mut hit);
+
+        if let Some(material) = &self.material {
+            hit.material = material.get_ref();
+        }
+
+        Some(hit)
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        &self.bbox
+    }
+
+    fn bounded(&self) -> bool {
+        self.object.bounded()
+    }
+}
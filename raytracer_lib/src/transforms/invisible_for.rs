@@ -43,4 +43,8 @@ impl<'a> Hittable<'a> for InvisibleFor<'a> {
     fn bounding_box(&self) -> &Aabb {
         self.object.bounding_box()
     }
+
+    fn bounded(&self) -> bool {
+        self.object.bounded()
+    }
 }
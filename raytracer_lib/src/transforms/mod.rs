@@ -1,6 +1,11 @@
 //! Transforms
 
+pub mod affine;
+pub mod animate;
 pub mod constant_medium;
+pub mod heterogeneous_medium;
+pub mod instance;
 pub mod invisible_for;
 pub mod rotate_y;
+pub mod subsurface;
 pub mod translate;
@@ -85,7 +85,7 @@ impl<'a> Hittable<'a> for RotateY<'a> {
         direction.e[0] = self.cos_theta * ray.direction()[0] - self.sin_theta * ray.direction()[2];
         direction.e[2] = self.sin_theta * ray.direction()[0] + self.cos_theta * ray.direction()[2];
 
-        let rotated = Ray::new(origin, direction, ray.time());
+        let rotated = Ray::new_from(ray, origin, direction);
 
         // Determine whether an intersection exists in object space (and if so, where)
         match self.object.hit(rng, &rotated, t_range) {
@@ -101,8 +101,18 @@ impl<'a> Hittable<'a> for RotateY<'a> {
                 normal.e[0] = self.cos_theta * hit.normal[0] + self.sin_theta * hit.normal[2];
                 normal.e[2] = -self.sin_theta * hit.normal[0] + self.cos_theta * hit.normal[2];
 
+                // Change the surface tangents from object space to world space
+                let mut dpdu = hit.dpdu.clone();
+                dpdu.e[0] = self.cos_theta * hit.dpdu[0] + self.sin_theta * hit.dpdu[2];
+                dpdu.e[2] = -self.sin_theta * hit.dpdu[0] + self.cos_theta * hit.dpdu[2];
+
+                let mut dpdv = hit.dpdv.clone();
+                dpdv.e[0] = self.cos_theta * hit.dpdv[0] + self.sin_theta * hit.dpdv[2];
+                dpdv.e[2] = -self.sin_theta * hit.dpdv[0] + self.cos_theta * hit.dpdv[2];
+
                 hit.p = p;
                 hit.normal = normal;
+                hit.set_tangents(dpdu, dpdv);
 
                 Some(hit)
             }
@@ -112,4 +122,8 @@ impl<'a> Hittable<'a> for RotateY<'a> {
     fn bounding_box(&self) -> &Aabb {
         &self.bbox
     }
+
+    fn bounded(&self) -> bool {
+        self.object.bounded()
+    }
 }
@@ -0,0 +1,154 @@
+//! Subsurface scattering medium (random walk inside a boundary)
+
+use std::ops::Range;
+
+use rand::{rngs::ThreadRng, Rng};
+
+use crate::{
+    float::*,
+    hits::{
+        aabb::Aabb,
+        hit::Hit,
+        hittable::{Hittable, HittableRef},
+    },
+    materials::{
+        isotropic::Isotropic,
+        material::{MatRef, Material, Scattered},
+    },
+    ray::Ray,
+    triple::{Colour, Vec3},
+};
+
+/// Subsurface scattering details
+///
+/// The boundary object's own material (normally a Dielectric) handles light entering and
+/// leaving the object. Inside the boundary the ray performs a random walk through a medium
+/// with a per colour channel mean free path and albedo. Collisions are sampled against the
+/// shortest mean free path (delta tracking); null collisions continue the ray unchanged but
+/// reweight the colour channels. Each collision, real or null, counts as a ray bounce against
+/// the camera's maximum depth, so objects which are large compared to the mean free path need a
+/// higher maximum depth. Null collisions are only taken when the channels' mean free paths
+/// differ.
+#[derive(Debug)]
+pub struct Subsurface<'a> {
+    boundary: HittableRef<'a>,
+    neg_inv_majorant: Flt,
+    real_prob: Flt,
+    real: MatRef<'a>,
+    null: MatRef<'a>,
+}
+
+impl<'a> Subsurface<'a> {
+    /// Creates a new subsurface scattering object from a boundary, mean free path and albedo.
+    /// Panics if the mean free path of any channel is not positive and finite
+    pub fn new(boundary: impl Hittable<'a> + 'a, mean_free_path: Colour, albedo: Colour) -> Self {
+        Self::new_with_hittable_ref(HittableRef::boxed(boundary), mean_free_path, albedo)
+    }
+
+    /// Creates a new subsurface scattering object from a boundary reference, mean free path and albedo
+    pub fn new_with_hittable_ref(
+        boundary: HittableRef<'a>,
+        mean_free_path: Colour,
+        albedo: Colour,
+    ) -> Self {
+        assert!(
+            (0..3).all(|c| mean_free_path[c] > 0.0 && mean_free_path[c].is_finite()),
+            "Mean free path must be positive and finite"
+        );
+
+        // Extinction coefficients per channel
+        let sigma_t = Colour::new_flt(
+            mean_free_path[0].recip(),
+            mean_free_path[1].recip(),
+            mean_free_path[2].recip(),
+        );
+
+        let majorant = sigma_t[0].max(sigma_t[1]).max(sigma_t[2]);
+        let mean = (sigma_t[0] + sigma_t[1] + sigma_t[2]) / flt(3.0);
+
+        // Probability of a tentative collision being treated as real
+        let real_prob = mean / majorant;
+
+        // Weights for real and null collisions
+        let real_weight = (&albedo * &sigma_t) / (majorant * real_prob);
+
+        let null_weight = if real_prob < 1.0 {
+            (Colour::new_grey(1.0) * majorant - sigma_t) / (majorant * (flt(1.0) - real_prob))
+        } else {
+            Colour::default()
+        };
+
+        Self {
+            boundary,
+            neg_inv_majorant: flt(-1.0) / majorant,
+            real_prob,
+            real: MatRef::boxed(Isotropic::new_with_colour(real_weight)),
+            null: MatRef::boxed(NullCollision {
+                weight: null_weight,
+            }),
+        }
+    }
+}
+
+impl<'a> Hittable<'a> for Subsurface<'a> {
+    fn hit(&self, rng: &mut ThreadRng, ray: &Ray, t_range: Range<Flt>) -> Option<Hit> {
+        // Find the next boundary crossing
+        let surface = self.boundary.hit(rng, ray, t_range.start..flt_max())?;
+
+        if surface.front_face {
+            // Outside the object - the surface material handles entry
+            return if t_range.contains(&surface.t) {
+                Some(surface)
+            } else {
+                None
+            };
+        }
+
+        // Inside the object - sample the distance to the next collision
+        let ray_length = ray.direction().length();
+        let hit_distance = self.neg_inv_majorant * rng.gen::<FltPrim>().ln();
+        let t = t_range.start + hit_distance / ray_length;
+
+        if t < surface.t && t < t_range.end {
+            let material = if self.real_prob > rng.gen_range(0.0..1.0) {
+                self.real.get_ref()
+            } else {
+                self.null.get_ref()
+            };
+
+            Some(Hit::new(
+                ray.at(t),
+                t,
+                flt(0.0),
+                flt(0.0),
+                ray,
+                &Vec3::new(1.0, 0.0, 0.0),
+                material,
+                self.name(),
+            ))
+        } else if t_range.contains(&surface.t) {
+            // Reached the surface from the inside
+            Some(surface)
+        } else {
+            None
+        }
+    }
+
+    fn bounding_box(&self) -> &Aabb {
+        self.boundary.bounding_box()
+    }
+}
+
+/// Null collision - continues the ray in the same direction with a channel weight
+#[derive(Debug)]
+struct NullCollision {
+    weight: Colour,
+}
+
+impl Material for NullCollision {
+    fn scatter(&self, _rng: &mut ThreadRng, ray: &Ray, hit: &Hit) -> Scattered {
+        let scattered = Ray::new(hit.p.clone(), ray.direction().clone(), ray.time());
+
+        (self.weight.clone(), None, Some(scattered))
+    }
+}
@@ -39,11 +39,7 @@ impl<'a> Translate<'a> {
 impl<'a> Hittable<'a> for Translate<'a> {
     fn hit(&self, rng: &mut ThreadRng, ray: &Ray, t_range: Range<Flt>) -> Option<Hit> {
         // Move the ray backwards by the offset
-        let offset_ray = Ray::new(
-            ray.origin() - &self.offset,
-            ray.direction().clone(),
-            ray.time(),
-        );
+        let offset_ray = Ray::new_from(ray, ray.origin() - &self.offset, ray.direction().clone());
 
         // Determine whether an intersection exists along the offset ray (and if so, where)
         match self.object.hit(rng, &offset_ray, t_range) {
@@ -59,4 +55,8 @@ impl<'a> Hittable<'a> for Translate<'a> {
     fn bounding_box(&self) -> &Aabb {
         &self.bbox
     }
+
+    fn bounded(&self) -> bool {
+        self.object.bounded()
+    }
 }
@@ -28,6 +28,18 @@ impl Colour {
         Self::new(level, level, level)
     }
 
+    /// Returns the relative luminance of the colour
+    #[inline]
+    pub fn luminance(&self) -> Flt {
+        flt(0.2126) * self[0] + flt(0.7152) * self[1] + flt(0.0722) * self[2]
+    }
+
+    /// Linearly interpolates between this colour and another colour
+    #[inline]
+    pub fn lerp(&self, other: &Colour, t: Flt) -> Colour {
+        (flt(1.0) - t) * self + t * other
+    }
+
     /// Convert to RGB with optional gamma correction
     #[inline]
     pub fn to_rgb(&self, gamma: &Gamma) -> (u8, u8, u8) {